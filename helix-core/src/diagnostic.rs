@@ -60,6 +60,10 @@ pub struct Diagnostic {
     pub tags: Vec<DiagnosticTag>,
     pub source: Option<String>,
     pub data: Option<serde_json::Value>,
+    /// Compact ` → file.rs:42` pointer to the first resolvable
+    /// related-information location, precomputed at conversion time for
+    /// renderers that opt in via `lsp.diagnostic-related-suffix`
+    pub related_suffix: Option<String>,
 }
 
 // TODO turn this into an enum + feature flag when lsp becomes optional
@@ -178,6 +178,42 @@ pub fn compare_ropes(before: &Rope, after: &Rope) -> Transaction {
     res
 }
 
+/// A `imara_diff::Sink` collecting the changed char ranges of an intra-line diff.
+struct ChangedCharRanges {
+    res: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl imara_diff::Sink for ChangedCharRanges {
+    type Out = Vec<(Range<usize>, Range<usize>)>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        self.res.push((
+            before.start as usize..before.end as usize,
+            after.start as usize..after.end as usize,
+        ));
+    }
+
+    fn finish(self) -> Self::Out {
+        self.res
+    }
+}
+
+/// Computes the character-level differences between two versions of a line.
+///
+/// Each returned pair holds the char range removed from `before` and the char
+/// range inserted into `after`; one side of a pair is empty for a pure
+/// insertion or removal. Intended for word-diff highlighting in previews of
+/// proposed edits, where styling only the changed spans makes a small change
+/// inside an otherwise unchanged line easy to spot.
+pub fn changed_char_ranges(before: &str, after: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut hunk = InternedInput::default();
+    hunk.update_before(before.chars());
+    hunk.update_after(after.chars());
+    // as in compare_ropes, the histogram heuristic does not work well for
+    // characters because the same characters often reoccur; use myer diff
+    imara_diff::diff(Algorithm::Myers, &hunk, ChangedCharRanges { res: Vec::new() })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,4 +254,36 @@ mod tests {
     fn deleted_file() {
         test_identity("foo", "");
     }
+
+    #[test]
+    fn changed_char_ranges_equal_lines() {
+        assert_eq!(changed_char_ranges("foo bar", "foo bar"), vec![]);
+    }
+
+    #[test]
+    fn changed_char_ranges_at_line_edges() {
+        // replacement at the start of the line
+        assert_eq!(changed_char_ranges("foo bar", "qux bar"), vec![(0..3, 0..3)]);
+        // removal at the start, insertion at the end
+        assert_eq!(changed_char_ranges("foobar", "bar"), vec![(0..3, 0..0)]);
+        assert_eq!(changed_char_ranges("foo", "foobar"), vec![(3..3, 3..6)]);
+    }
+
+    #[test]
+    fn changed_char_ranges_multiple_edits() {
+        let res = changed_char_ranges("let foo = bar;", "let food = barn;");
+        assert_eq!(res.len(), 2);
+        // `d` inserted into `foo`, `n` inserted into `bar`
+        assert_eq!(&"let food = barn;"[7..8], "d");
+        assert_eq!(res[0].0.len(), 0);
+        assert_eq!(res[0].1, 7..8);
+        assert_eq!(res[1].0.len(), 0);
+        assert_eq!(res[1].1, 14..15);
+    }
+
+    #[test]
+    fn changed_char_ranges_multibyte() {
+        // ranges are char indices, not byte offsets
+        assert_eq!(changed_char_ranges("naïve", "naive"), vec![(2..3, 2..3)]);
+    }
 }
@@ -169,6 +169,15 @@ pub struct LanguageConfiguration {
     pub workspace_lsp_roots: Option<Vec<PathBuf>>,
     #[serde(default)]
     pub persistent_diagnostic_sources: Vec<String>,
+
+    /// Hide symbols that look private from the symbol pickers; `A-i` in the
+    /// picker temporarily reveals them. The default heuristic treats a
+    /// leading underscore in the name as private; `private-symbol-regex`
+    /// replaces it for languages with explicit visibility conventions.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hide_private_symbols: bool,
+    #[serde(default, skip_serializing, deserialize_with = "deserialize_regex")]
+    pub private_symbol_regex: Option<Regex>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -329,8 +338,10 @@ pub enum LanguageServerFeature {
     WorkspaceSymbols,
     // Symbols, use bitflags, see above?
     Diagnostics,
+    PullDiagnostics,
     RenameSymbol,
     InlayHints,
+    InlineValues,
 }
 
 impl Display for LanguageServerFeature {
@@ -352,8 +363,10 @@ impl Display for LanguageServerFeature {
             DocumentSymbols => "document-symbols",
             WorkspaceSymbols => "workspace-symbols",
             Diagnostics => "diagnostics",
+            PullDiagnostics => "pull-diagnostics",
             RenameSymbol => "rename-symbol",
             InlayHints => "inlay-hints",
+            InlineValues => "inline-values",
         };
         write!(f, "{feature}",)
     }
@@ -471,6 +484,23 @@ pub struct LanguageServerConfiguration {
         deserialize_with = "deserialize_required_root_patterns"
     )]
     pub required_root_patterns: Option<GlobSet>,
+    /// Force the position encoding ("utf-8", "utf-16" or "utf-32") instead of
+    /// using the one the server advertises. This is a workaround for
+    /// non-compliant servers that mis-declare their encoding, which otherwise
+    /// leads to wrong positions in files with multibyte characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset_encoding: Option<String>,
+    /// Ask for confirmation before applying `workspace/applyEdit` requests
+    /// pushed by this server, useful for untrusted or buggy servers. Off by
+    /// default: edits are applied immediately.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub confirm_workspace_edits: bool,
+    /// Keep `window/showMessage` notifications that arrive while one of this
+    /// server's commands is executing off the status line; they are routed to
+    /// the log and the command output buffer instead. For servers that
+    /// narrate every refactor step with messages.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suppress_command_messages: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
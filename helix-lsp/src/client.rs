@@ -18,21 +18,55 @@ use parking_lot::Mutex;
 use serde::Deserialize;
 use serde_json::Value;
 use std::sync::{
-    atomic::{AtomicU64, Ordering},
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     Arc,
 };
 use std::{collections::HashMap, path::PathBuf};
 use std::{future::Future, sync::OnceLock};
 use std::{path::Path, process::Stdio};
 use tokio::{
-    io::{BufReader, BufWriter},
+    io::{AsyncBufRead, AsyncWrite, BufReader, BufWriter},
     process::{Child, Command},
     sync::{
         mpsc::{channel, UnboundedReceiver, UnboundedSender},
-        Notify, OnceCell,
+        Notify, OnceCell, Semaphore, SemaphorePermit,
     },
 };
 
+/// Parses a position encoding name as it appears in the protocol (and in the
+/// `offset-encoding` language server configuration).
+pub(crate) fn parse_offset_encoding(encoding: &str) -> Option<OffsetEncoding> {
+    match encoding {
+        "utf-8" => Some(OffsetEncoding::Utf8),
+        "utf-16" => Some(OffsetEncoding::Utf16),
+        "utf-32" => Some(OffsetEncoding::Utf32),
+        _ => None,
+    }
+}
+
+/// A forced encoding from the configuration wins over whatever the server
+/// advertises; without either the protocol mandates utf-16.
+pub(crate) fn resolve_offset_encoding(
+    forced: Option<OffsetEncoding>,
+    advertised: Option<&PositionEncodingKind>,
+) -> OffsetEncoding {
+    if let Some(encoding) = forced {
+        return encoding;
+    }
+    advertised
+        .and_then(|encoding| match parse_offset_encoding(encoding.as_str()) {
+            Some(encoding) => Some(encoding),
+            None => {
+                log::error!(
+                    "Server provided invalid position encoding {}, defaulting to utf-16",
+                    encoding.as_str()
+                );
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
 fn workspace_for_uri(uri: lsp::Url) -> WorkspaceFolder {
     lsp::WorkspaceFolder {
         name: uri
@@ -44,11 +78,94 @@ fn workspace_for_uri(uri: lsp::Url) -> WorkspaceFolder {
     }
 }
 
+/// How many background requests a server is handed at once; the rest wait
+/// their turn in FIFO order.
+const MAX_CONCURRENT_BACKGROUND_REQUESTS: usize = 4;
+/// How many background requests may wait for a free slot before new ones are
+/// dropped outright.
+const MAX_QUEUED_BACKGROUND_REQUESTS: usize = 16;
+
+/// Limits how many background requests are in flight per server. Some servers
+/// degrade badly under concurrent load: scrolling alone can queue dozens of
+/// inlay hint requests, starving interactive requests like hover and goto.
+/// Interactive requests bypass the limiter and are dispatched immediately.
+#[derive(Debug)]
+pub(crate) struct BackgroundLimiter {
+    slots: Semaphore,
+    queued: AtomicUsize,
+}
+
+impl BackgroundLimiter {
+    fn new() -> Self {
+        Self {
+            slots: Semaphore::new(MAX_CONCURRENT_BACKGROUND_REQUESTS),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    async fn acquire(&self) -> Result<SemaphorePermit<'_>> {
+        struct QueuedGuard<'a>(&'a AtomicUsize);
+        impl Drop for QueuedGuard<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+
+        if self.queued.load(Ordering::Relaxed) >= MAX_QUEUED_BACKGROUND_REQUESTS {
+            return Err(Error::BackgroundRequestDropped);
+        }
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        // decrements even if the request future is dropped while waiting
+        let _guard = QueuedGuard(&self.queued);
+        Ok(self
+            .slots
+            .acquire()
+            .await
+            .expect("background limiter semaphore is never closed"))
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    fn in_flight(&self) -> usize {
+        MAX_CONCURRENT_BACKGROUND_REQUESTS - self.slots.available_permits()
+    }
+}
+
+/// Requests that are not the direct result of a keypress and tend to be
+/// issued in bursts. These go through the [`BackgroundLimiter`].
+fn is_background_request(method: &str) -> bool {
+    use lsp::request::Request;
+    matches!(
+        method,
+        lsp::request::InlayHintRequest::METHOD
+            | lsp::request::WorkspaceSymbolRequest::METHOD
+            | lsp::request::DocumentDiagnosticRequest::METHOD
+    )
+}
+
+/// Requests whose results go stale as soon as the same command is issued
+/// again, e.g. by typing on in a dynamic picker. Sending a newer request of
+/// one of these kinds cancels the in-flight one via `$/cancelRequest`, so a
+/// slow server does not waste work on answers nobody will look at.
+fn is_superseded_request(method: &str) -> bool {
+    use lsp::request::Request;
+    matches!(
+        method,
+        lsp::request::References::METHOD
+            | lsp::request::WorkspaceSymbolRequest::METHOD
+            | lsp::request::CodeActionRequest::METHOD
+    )
+}
+
 #[derive(Debug)]
 pub struct Client {
     id: LanguageServerId,
     name: String,
-    _process: Child,
+    /// `None` for clients connected through [`Client::start_with_transport`],
+    /// which don't own a server process.
+    _process: Option<Child>,
     server_tx: UnboundedSender<Payload>,
     request_counter: AtomicU64,
     pub(crate) capabilities: OnceCell<lsp::ServerCapabilities>,
@@ -60,6 +177,29 @@ pub struct Client {
     initialize_notify: Arc<Notify>,
     /// workspace folders added while the server is still initializing
     req_timeout: u64,
+    /// Overrides the encoding the server advertises; workaround for
+    /// non-compliant servers that mis-declare their position encoding.
+    forced_offset_encoding: Option<OffsetEncoding>,
+    /// Whether `workspace/applyEdit` requests from this server need to be
+    /// confirmed by the user before they are applied.
+    confirm_workspace_edits: bool,
+    /// Whether `window/showMessage` notifications sent while one of this
+    /// server's commands is executing are kept off the status line.
+    suppress_command_messages: bool,
+    background_limiter: Arc<BackgroundLimiter>,
+    /// Set when a request fails because the server closed the stream; feature
+    /// queries skip a crashed server until it is restarted.
+    crashed: AtomicBool,
+    /// Last document version whose `didOpen`/`didChange` notification has
+    /// been handed to the server, per document. See
+    /// [`Client::document_sync_barrier`].
+    flushed_versions: Arc<Mutex<HashMap<lsp::Url, i32>>>,
+    /// Signalled whenever [`Client::flushed_versions`] advances.
+    flushed_notify: Arc<Notify>,
+    /// Id of the in-flight request per cancellable method, see
+    /// [`is_superseded_request`]. A newer request of the same kind replaces
+    /// the entry and cancels the id it displaced.
+    pending_requests: Arc<Mutex<HashMap<&'static str, jsonrpc::Id>>>,
 }
 
 impl Client {
@@ -182,6 +322,9 @@ impl Client {
         id: LanguageServerId,
         name: String,
         req_timeout: u64,
+        forced_offset_encoding: Option<OffsetEncoding>,
+        confirm_workspace_edits: bool,
+        suppress_command_messages: bool,
     ) -> Result<(
         Self,
         UnboundedReceiver<(LanguageServerId, Call)>,
@@ -210,12 +353,91 @@ impl Client {
         let (server_rx, server_tx, initialize_notify) =
             Transport::start(reader, writer, stderr, id, name.clone());
 
+        let client = Self::new(
+            Some(process),
+            server_tx,
+            initialize_notify.clone(),
+            config,
+            root_path,
+            root_uri,
+            id,
+            name,
+            req_timeout,
+            forced_offset_encoding,
+            confirm_workspace_edits,
+            suppress_command_messages,
+        );
+
+        Ok((client, server_rx, initialize_notify))
+    }
+
+    /// Creates a client that talks to a server over the given streams instead
+    /// of a spawned subprocess, e.g. a scripted server running in-process.
+    /// The caller is responsible for driving initialization, as
+    /// `Registry::register_client` does.
+    #[allow(clippy::type_complexity)]
+    pub fn start_with_transport(
+        reader: impl AsyncBufRead + Unpin + Send + 'static,
+        writer: impl AsyncWrite + Unpin + Send + 'static,
+        root_path: PathBuf,
+        root_uri: Option<lsp::Url>,
+        id: LanguageServerId,
+        name: String,
+        req_timeout: u64,
+    ) -> (
+        Self,
+        UnboundedReceiver<(LanguageServerId, Call)>,
+        Arc<Notify>,
+    ) {
+        // there is no stderr to watch; hand the transport one that is
+        // immediately at EOF
+        let (server_rx, server_tx, initialize_notify) = Transport::start(
+            reader,
+            writer,
+            BufReader::new(tokio::io::empty()),
+            id,
+            name.clone(),
+        );
+
+        let client = Self::new(
+            None,
+            server_tx,
+            initialize_notify.clone(),
+            None,
+            root_path,
+            root_uri,
+            id,
+            name,
+            req_timeout,
+            None,
+            false,
+            false,
+        );
+
+        (client, server_rx, initialize_notify)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        process: Option<Child>,
+        server_tx: UnboundedSender<Payload>,
+        initialize_notify: Arc<Notify>,
+        config: Option<Value>,
+        root_path: PathBuf,
+        root_uri: Option<lsp::Url>,
+        id: LanguageServerId,
+        name: String,
+        req_timeout: u64,
+        forced_offset_encoding: Option<OffsetEncoding>,
+        confirm_workspace_edits: bool,
+        suppress_command_messages: bool,
+    ) -> Self {
         let workspace_folders = root_uri
             .clone()
             .map(|root| vec![workspace_for_uri(root)])
             .unwrap_or_default();
 
-        let client = Self {
+        Self {
             id,
             name,
             _process: process,
@@ -228,10 +450,16 @@ impl Client {
             root_path,
             root_uri,
             workspace_folders: Mutex::new(workspace_folders),
-            initialize_notify: initialize_notify.clone(),
-        };
-
-        Ok((client, server_rx, initialize_notify))
+            initialize_notify,
+            forced_offset_encoding,
+            confirm_workspace_edits,
+            suppress_command_messages,
+            background_limiter: Arc::new(BackgroundLimiter::new()),
+            crashed: AtomicBool::new(false),
+            flushed_versions: Arc::new(Mutex::new(HashMap::new())),
+            flushed_notify: Arc::new(Notify::new()),
+            pending_requests: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 
     pub fn name(&self) -> &str {
@@ -343,6 +571,7 @@ impl Client {
                 Some(OneOf::Left(true) | OneOf::Right(_))
             ),
             LanguageServerFeature::Diagnostics => true, // there's no extra server capability
+            LanguageServerFeature::PullDiagnostics => capabilities.diagnostic_provider.is_some(),
             LanguageServerFeature::RenameSymbol => matches!(
                 capabilities.rename_provider,
                 Some(OneOf::Left(true)) | Some(OneOf::Right(_))
@@ -351,23 +580,26 @@ impl Client {
                 capabilities.inlay_hint_provider,
                 Some(OneOf::Left(true) | OneOf::Right(InlayHintServerCapabilities::Options(_)))
             ),
+            LanguageServerFeature::InlineValues => matches!(
+                capabilities.inline_value_provider,
+                Some(OneOf::Left(true) | OneOf::Right(_))
+            ),
         }
     }
 
+    pub fn confirm_workspace_edits(&self) -> bool {
+        self.confirm_workspace_edits
+    }
+
+    pub fn suppress_command_messages(&self) -> bool {
+        self.suppress_command_messages
+    }
+
     pub fn offset_encoding(&self) -> OffsetEncoding {
-        self.capabilities()
-            .position_encoding
-            .as_ref()
-            .and_then(|encoding| match encoding.as_str() {
-                "utf-8" => Some(OffsetEncoding::Utf8),
-                "utf-16" => Some(OffsetEncoding::Utf16),
-                "utf-32" => Some(OffsetEncoding::Utf32),
-                encoding => {
-                    log::error!("Server provided invalid position encoding {encoding}, defaulting to utf-16");
-                    None
-                },
-            })
-            .unwrap_or_default()
+        resolve_offset_encoding(
+            self.forced_offset_encoding,
+            self.capabilities().position_encoding.as_ref(),
+        )
     }
 
     pub fn config(&self) -> Option<&Value> {
@@ -423,12 +655,27 @@ impl Client {
     {
         let server_tx = self.server_tx.clone();
         let id = self.next_request_id();
+        let limiter = is_background_request(R::METHOD).then(|| self.background_limiter.clone());
+        let pending = is_superseded_request(R::METHOD).then(|| self.pending_requests.clone());
+        if let Some(pending) = &pending {
+            if let Some(superseded) = pending.lock().insert(R::METHOD, id.clone()) {
+                self.cancel(superseded);
+            }
+        }
 
         let params = serde_json::to_value(params);
         async move {
             use std::time::Duration;
             use tokio::time::timeout;
 
+            // Interactive requests skip the limiter so a burst of background
+            // requests can never delay them; the slot is held until the
+            // response arrives (or the request times out).
+            let _permit = match &limiter {
+                Some(limiter) => Some(limiter.acquire().await?),
+                None => None,
+            };
+
             let request = jsonrpc::MethodCall {
                 jsonrpc: Some(jsonrpc::Version::V2),
                 id: id.clone(),
@@ -446,6 +693,80 @@ impl Client {
                 .map_err(|e| Error::Other(e.into()))?;
 
             // TODO: delay other calls until initialize success
+            let res = match timeout(Duration::from_secs(timeout_secs), rx.recv()).await {
+                Err(_) => Err(Error::Timeout(id.clone())), // return Timeout
+                Ok(None) => Err(Error::StreamClosed),
+                Ok(Some(res)) => res,
+            };
+            if let Some(pending) = &pending {
+                let mut pending = pending.lock();
+                // only clear the slot if it was not claimed by a newer request
+                if pending.get(R::METHOD) == Some(&id) {
+                    pending.remove(R::METHOD);
+                }
+            }
+            res
+        }
+    }
+
+    /// Number of background requests waiting for a free slot, shown by
+    /// `:lsp-stats`.
+    pub fn background_requests_queued(&self) -> usize {
+        self.background_limiter.queue_depth()
+    }
+
+    /// Number of background requests currently being served.
+    pub fn background_requests_in_flight(&self) -> usize {
+        self.background_limiter.in_flight()
+    }
+
+    /// Marks the server as crashed, see [`Client::is_crashed`]. Returns
+    /// whether this was the first crash report, so the caller can surface the
+    /// crash once instead of per pending request.
+    pub fn mark_crashed(&self) -> bool {
+        !self.crashed.swap(true, Ordering::Relaxed)
+    }
+
+    /// Whether a request against this server failed because the server closed
+    /// the stream. Restarting spawns a fresh client, so the flag never needs
+    /// to be cleared.
+    pub fn is_crashed(&self) -> bool {
+        self.crashed.load(Ordering::Relaxed)
+    }
+
+    /// Execute a non-standard RPC request on the language server, e.g. the
+    /// `java/classFileContents` extension jdtls uses to serve sources for
+    /// `jdt://` URIs.
+    pub fn custom_request(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> impl Future<Output = Result<Value>> {
+        let server_tx = self.server_tx.clone();
+        let id = self.next_request_id();
+        let timeout_secs = self.req_timeout;
+        let method = method.to_owned();
+
+        async move {
+            use std::time::Duration;
+            use tokio::time::timeout;
+
+            let request = jsonrpc::MethodCall {
+                jsonrpc: Some(jsonrpc::Version::V2),
+                id: id.clone(),
+                method,
+                params: Self::value_into_params(params),
+            };
+
+            let (tx, mut rx) = channel::<Result<Value>>(1);
+
+            server_tx
+                .send(Payload::Request {
+                    chan: tx,
+                    value: request,
+                })
+                .map_err(|e| Error::Other(e.into()))?;
+
             timeout(Duration::from_secs(timeout_secs), rx.recv())
                 .await
                 .map_err(|_| Error::Timeout(id))? // return Timeout
@@ -453,6 +774,29 @@ impl Client {
         }
     }
 
+    /// Asks the server to stop working on an in-flight request via
+    /// `$/cancelRequest`. Servers are free to ignore this; those that honor
+    /// it answer the request with a `RequestCancelled` error, which reaches
+    /// the issuing command as any other error response would.
+    pub fn cancel(&self, id: jsonrpc::Id) {
+        use lsp::notification::Notification;
+
+        let id = match id {
+            jsonrpc::Id::Num(id) => lsp::NumberOrString::Number(id as i32),
+            jsonrpc::Id::Str(id) => lsp::NumberOrString::String(id),
+            jsonrpc::Id::Null => return,
+        };
+        let params = serde_json::to_value(lsp::CancelParams { id })
+            .expect("CancelParams serialization never fails");
+        let notification = jsonrpc::Notification {
+            jsonrpc: Some(jsonrpc::Version::V2),
+            method: lsp::notification::Cancel::METHOD.to_string(),
+            params: Self::value_into_params(params),
+        };
+        // the server being gone makes cancellation a no-op
+        let _ = self.server_tx.send(Payload::Notification(notification));
+    }
+
     /// Send a RPC notification to the language server.
     pub fn notify<R: lsp::notification::Notification>(
         &self,
@@ -656,6 +1000,13 @@ impl Client {
                         dynamic_registration: Some(false),
                         resolve_support: None,
                     }),
+                    inline_value: Some(lsp::InlineValueClientCapabilities {
+                        dynamic_registration: Some(false),
+                    }),
+                    diagnostic: Some(lsp::DiagnosticClientCapabilities {
+                        dynamic_registration: Some(false),
+                        related_document_support: Some(false),
+                    }),
                     document_symbol: Some(lsp::DocumentSymbolClientCapabilities {
                         hierarchical_document_symbol_support: Some(true),
                         ..Default::default()
@@ -795,6 +1146,73 @@ impl Client {
     // Text document
     // -------------------------------------------------------------------------------------------
 
+    /// Wraps a `didOpen`/`didChange` notification future so that, once the
+    /// notification has been handed to the server, the document's flushed
+    /// version is recorded and barrier waiters are woken.
+    fn record_flushed_version(
+        &self,
+        uri: lsp::Url,
+        version: i32,
+        notify: impl Future<Output = Result<()>>,
+    ) -> impl Future<Output = Result<()>> {
+        let flushed_versions = self.flushed_versions.clone();
+        let flushed_notify = self.flushed_notify.clone();
+        async move {
+            let result = notify.await;
+            if result.is_ok() {
+                flushed_versions.lock().insert(uri, version);
+                flushed_notify.notify_waiters();
+            }
+            result
+        }
+    }
+
+    /// A synchronization barrier for user-invoked requests: resolves once
+    /// every `didChange` notification for the document up to `version` has
+    /// been handed to this server, so a request whose position was computed
+    /// against `version` cannot overtake a change notification still in
+    /// flight — the server would answer for stale text and the jump would
+    /// land a few characters off. Logs when it actually had to wait, and
+    /// gives up after a second rather than stalling the request.
+    pub fn document_sync_barrier(&self, uri: lsp::Url, version: i32) -> impl Future<Output = ()> {
+        let flushed_versions = self.flushed_versions.clone();
+        let flushed_notify = self.flushed_notify.clone();
+        let name = self.name.clone();
+        async move {
+            let up_to_date = || {
+                flushed_versions
+                    .lock()
+                    .get(&uri)
+                    .map_or(true, |&flushed| flushed >= version)
+            };
+            if up_to_date() {
+                return;
+            }
+            let start = std::time::Instant::now();
+            let wait = async {
+                loop {
+                    // register before re-checking so a flush between the
+                    // check and the await cannot be missed
+                    let notified = flushed_notify.notified();
+                    if up_to_date() {
+                        break;
+                    }
+                    notified.await;
+                }
+            };
+            match tokio::time::timeout(std::time::Duration::from_secs(1), wait).await {
+                Ok(()) => log::debug!(
+                    "{name}: waited {:?} for didChange of {uri} (version {version}) to flush",
+                    start.elapsed()
+                ),
+                Err(_) => log::warn!(
+                    "{name}: didChange of {uri} (version {version}) still unflushed after {:?}, sending the request anyway",
+                    start.elapsed()
+                ),
+            }
+        }
+    }
+
     pub fn text_document_did_open(
         &self,
         uri: lsp::Url,
@@ -802,14 +1220,16 @@ impl Client {
         doc: &Rope,
         language_id: String,
     ) -> impl Future<Output = Result<()>> {
-        self.notify::<lsp::notification::DidOpenTextDocument>(lsp::DidOpenTextDocumentParams {
-            text_document: lsp::TextDocumentItem {
-                uri,
-                language_id,
-                version,
-                text: String::from(doc),
-            },
-        })
+        let notify =
+            self.notify::<lsp::notification::DidOpenTextDocument>(lsp::DidOpenTextDocumentParams {
+                text_document: lsp::TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id,
+                    version,
+                    text: String::from(doc),
+                },
+            });
+        self.record_flushed_version(uri, version, notify)
     }
 
     pub fn changeset_to_changes(
@@ -960,18 +1380,22 @@ impl Client {
             kind => unimplemented!("{:?}", kind),
         };
 
-        Some(self.notify::<lsp::notification::DidChangeTextDocument>(
+        let uri = text_document.uri.clone();
+        let version = text_document.version;
+        let notify = self.notify::<lsp::notification::DidChangeTextDocument>(
             lsp::DidChangeTextDocumentParams {
                 text_document,
                 content_changes: changes,
             },
-        ))
+        );
+        Some(self.record_flushed_version(uri, version, notify))
     }
 
     pub fn text_document_did_close(
         &self,
         text_document: lsp::TextDocumentIdentifier,
     ) -> impl Future<Output = Result<()>> {
+        self.flushed_versions.lock().remove(&text_document.uri);
         self.notify::<lsp::notification::DidCloseTextDocument>(lsp::DidCloseTextDocumentParams {
             text_document,
         })
@@ -1113,6 +1537,57 @@ impl Client {
         Some(self.call::<lsp::request::InlayHintRequest>(params))
     }
 
+    pub fn text_document_inline_values(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        range: lsp::Range,
+        context: lsp::InlineValueContext,
+        work_done_token: Option<lsp::ProgressToken>,
+    ) -> Option<impl Future<Output = Result<Value>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support inline values.
+        match capabilities.inline_value_provider {
+            Some(lsp::OneOf::Left(true) | lsp::OneOf::Right(_)) => (),
+            _ => return None,
+        }
+
+        let params = lsp::InlineValueParams {
+            text_document,
+            range,
+            context,
+            work_done_progress_params: lsp::WorkDoneProgressParams { work_done_token },
+        };
+
+        Some(self.call::<lsp::request::InlineValueRequest>(params))
+    }
+
+    pub fn text_document_diagnostic(
+        &self,
+        text_document: lsp::TextDocumentIdentifier,
+        previous_result_id: Option<String>,
+    ) -> Option<impl Future<Output = Result<Value>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support pull diagnostics.
+        let identifier = match capabilities.diagnostic_provider.as_ref()? {
+            lsp::DiagnosticServerCapabilities::Options(options) => options.identifier.clone(),
+            lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+                options.diagnostic_options.identifier.clone()
+            }
+        };
+
+        let params = lsp::DocumentDiagnosticParams {
+            text_document,
+            identifier,
+            previous_result_id,
+            work_done_progress_params: lsp::WorkDoneProgressParams::default(),
+            partial_result_params: lsp::PartialResultParams::default(),
+        };
+
+        Some(self.call::<lsp::request::DocumentDiagnosticRequest>(params))
+    }
+
     pub fn text_document_hover(
         &self,
         text_document: lsp::TextDocumentIdentifier,
@@ -1464,6 +1939,24 @@ impl Client {
         Some(self.call::<lsp::request::WorkspaceSymbolRequest>(params))
     }
 
+    pub fn resolve_workspace_symbol(
+        &self,
+        symbol: lsp::WorkspaceSymbol,
+    ) -> Option<impl Future<Output = Result<Value>>> {
+        let capabilities = self.capabilities.get().unwrap();
+
+        // Return early if the server does not support resolving workspace symbols.
+        match capabilities.workspace_symbol_provider {
+            Some(lsp::OneOf::Right(lsp::WorkspaceSymbolOptions {
+                resolve_provider: Some(true),
+                ..
+            })) => (),
+            _ => return None,
+        }
+
+        Some(self.call::<lsp::request::WorkspaceSymbolResolve>(symbol))
+    }
+
     pub fn code_actions(
         &self,
         text_document: lsp::TextDocumentIdentifier,
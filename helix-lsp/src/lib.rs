@@ -3,6 +3,8 @@ pub mod file_event;
 mod file_operations;
 pub mod jsonrpc;
 pub mod snippet;
+#[cfg(feature = "test-server")]
+pub mod test_server;
 mod transport;
 
 use arc_swap::ArcSwap;
@@ -18,7 +20,7 @@ use helix_core::syntax::{
 };
 use helix_stdx::path;
 use slotmap::SlotMap;
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::{mpsc::UnboundedReceiver, Notify};
 
 use std::{
     collections::HashMap,
@@ -45,6 +47,8 @@ pub enum Error {
     Timeout(jsonrpc::Id),
     #[error("server closed the stream")]
     StreamClosed,
+    #[error("dropped background request, server queue is full")]
+    BackgroundRequestDropped,
     #[error("Unhandled")]
     Unhandled,
     #[error(transparent)]
@@ -53,6 +57,14 @@ pub enum Error {
     Other(#[from] anyhow::Error),
 }
 
+impl Error {
+    /// Whether the error means the server process is gone (it closed the
+    /// stream), as opposed to a failure of the individual request.
+    pub fn is_server_exit(&self) -> bool {
+        matches!(self, Error::StreamClosed)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum OffsetEncoding {
     /// UTF-8 code units aka bytes
@@ -70,6 +82,40 @@ pub mod util {
     use helix_core::{chars, RopeSlice, SmallVec};
     use helix_core::{diagnostic::NumberOrString, Range, Rope, Selection, Tendril, Transaction};
 
+    /// Normalizes a `file://` URI received from a server in place. Well-behaved
+    /// servers echo back the URIs the client sent, but some (older ccls builds,
+    /// in-house servers) answer with `..` segments, gratuitous percent-encoding
+    /// or an uppercase drive letter. Left alone, those spellings fail
+    /// `Url::to_file_path` conversions downstream or track an already-open file
+    /// under a second path. Non-file and unconvertible URIs are left untouched.
+    pub fn normalize_uri(uri: &mut lsp::Url) {
+        if uri.scheme() != "file" {
+            return;
+        }
+        // resolve dot segments and canonicalize the percent-encoding by
+        // round-tripping through the decoded path representation
+        if let Ok(path) = uri.to_file_path() {
+            if let Ok(normalized) = lsp::Url::from_file_path(helix_stdx::path::normalize(path)) {
+                *uri = normalized;
+            }
+        }
+        // lowercase the drive letter: servers disagree on its case and
+        // `file:///C:/x` and `file:///c:/x` would count as two files
+        fn lowercase_drive(path: &str) -> Option<String> {
+            let rest = path.strip_prefix('/')?;
+            let drive = rest.chars().next().filter(char::is_ascii_uppercase)?;
+            let tail = &rest[1..];
+            let (colon, tail) = tail
+                .strip_prefix(':')
+                .map(|tail| (":", tail))
+                .or_else(|| tail.strip_prefix("%3A").map(|tail| ("%3A", tail)))?;
+            Some(format!("/{}{colon}{tail}", drive.to_ascii_lowercase()))
+        }
+        if let Some(lowered) = lowercase_drive(uri.path()) {
+            uri.set_path(&lowered);
+        }
+    }
+
     /// Converts a diagnostic in the document to [`lsp::Diagnostic`].
     ///
     /// Panics when [`pos_to_lsp_pos`] would for an invalid range on the diagnostic.
@@ -722,6 +768,44 @@ impl Registry {
         Ok(self.inner[id].clone())
     }
 
+    /// Registers a pre-built client under `name`, wiring its messages into
+    /// the registry and initializing it just like a spawned server. This is
+    /// the hook tests use to attach an in-process server through
+    /// [Client::start_with_transport]; documents whose language configuration
+    /// lists `name` pick the client up the next time their language servers
+    /// are refreshed.
+    pub fn register_client(
+        &mut self,
+        name: LanguageServerName,
+        enable_snippets: bool,
+        build: impl FnOnce(
+            LanguageServerId,
+        ) -> (
+            Client,
+            UnboundedReceiver<(LanguageServerId, Call)>,
+            Arc<Notify>,
+        ),
+    ) -> Arc<Client> {
+        let mut initialize_notify = None;
+        let id = self.inner.insert_with_key(|id| {
+            let (client, incoming, notify) = build(id);
+            self.incoming.push(UnboundedReceiverStream::new(incoming));
+            initialize_notify = Some(notify);
+            Arc::new(client)
+        });
+        let client = self.inner[id].clone();
+        initialize_client(
+            client.clone(),
+            initialize_notify.expect("build was called"),
+            enable_snippets,
+        );
+        self.inner_by_name
+            .entry(name)
+            .or_default()
+            .push(client.clone());
+        client
+    }
+
     /// If this method is called, all documents that have a reference to language servers used by the language config have to refresh their language servers,
     /// as it could be that language servers of these documents were stopped by this method.
     /// See helix_view::editor::Editor::refresh_language_servers
@@ -955,6 +1039,14 @@ fn start_client(
         }
     }
 
+    let forced_offset_encoding = ls_config.offset_encoding.as_deref().and_then(|encoding| {
+        let parsed = client::parse_offset_encoding(encoding);
+        if parsed.is_none() {
+            log::error!("Invalid offset-encoding {encoding:?} configured for {name}, ignoring");
+        }
+        parsed
+    });
+
     let (client, incoming, initialize_notify) = Client::start(
         &ls_config.command,
         &ls_config.args,
@@ -965,18 +1057,26 @@ fn start_client(
         id,
         name,
         ls_config.timeout,
+        forced_offset_encoding,
+        ls_config.confirm_workspace_edits,
+        ls_config.suppress_command_messages,
     )?;
 
     let client = Arc::new(client);
+    initialize_client(client.clone(), initialize_notify, enable_snippets);
+
+    Ok(NewClient(client, incoming))
+}
 
-    // Initialize the client asynchronously
-    let _client = client.clone();
+/// Initializes `client` asynchronously and pings `initialize_notify` once the
+/// handshake is complete so the transport starts draining queued messages.
+fn initialize_client(client: Arc<Client>, initialize_notify: Arc<Notify>, enable_snippets: bool) {
     tokio::spawn(async move {
         use futures_util::TryFutureExt;
-        let value = _client
+        let value = client
             .capabilities
             .get_or_try_init(|| {
-                _client
+                client
                     .initialize(enable_snippets)
                     .map_ok(|response| response.capabilities)
             })
@@ -988,7 +1088,7 @@ fn start_client(
         }
 
         // next up, notify<initialized>
-        let notification_result = _client
+        let notification_result = client
             .notify::<lsp::notification::Initialized>(lsp::InitializedParams {})
             .await;
 
@@ -1002,8 +1102,6 @@ fn start_client(
 
         initialize_notify.notify_one();
     });
-
-    Ok(NewClient(client, incoming))
 }
 
 /// Find an LSP workspace of a file using the following mechanism:
@@ -1070,6 +1168,67 @@ mod tests {
     use super::{lsp, util::*, OffsetEncoding};
     use helix_core::Rope;
 
+    #[test]
+    fn preserves_diagnostic_data() {
+        // servers use `data` to produce targeted quick fixes: the value must
+        // survive the round-trip into `CodeActionContext.diagnostics` unchanged
+        let doc = Rope::from("fn main() {}\n");
+        let data = serde_json::json!({ "rendered": "unused variable", "fix_id": 42 });
+        let diag = helix_core::diagnostic::Diagnostic {
+            range: helix_core::diagnostic::Range { start: 3, end: 7 },
+            ends_at_word: false,
+            starts_at_word: false,
+            zero_width: false,
+            line: 0,
+            message: "unused variable".to_string(),
+            severity: None,
+            code: None,
+            provider: helix_core::diagnostic::DiagnosticProvider::default(),
+            tags: Vec::new(),
+            source: None,
+            data: Some(data.clone()),
+            related_suffix: None,
+        };
+
+        let converted = diagnostic_to_lsp_diagnostic(&doc, &diag, OffsetEncoding::Utf16);
+        assert_eq!(converted.data, Some(data));
+    }
+
+    #[test]
+    fn normalizes_pathological_server_uris() {
+        fn normalized(uri: &str) -> String {
+            let mut uri = lsp::Url::parse(uri).unwrap();
+            normalize_uri(&mut uri);
+            uri.to_string()
+        }
+
+        // dot segments, as returned by older ccls builds
+        assert_eq!(
+            normalized("file:///home/user/../project/main.c"),
+            "file:///home/project/main.c"
+        );
+        assert_eq!(
+            normalized("file:///home/./user/main.c"),
+            "file:///home/user/main.c"
+        );
+        // gratuitous percent-encoding of unreserved characters
+        assert_eq!(
+            normalized("file:///home/%75ser/file%2Ec"),
+            "file:///home/user/file.c"
+        );
+        // uppercase drive letters
+        assert_eq!(
+            normalized("file:///C:/Users/me/file.c"),
+            "file:///c:/Users/me/file.c"
+        );
+        // already-normalized URIs and other schemes pass through unchanged
+        assert_eq!(
+            normalized("file:///home/user/file.c"),
+            "file:///home/user/file.c"
+        );
+        assert_eq!(normalized("untitled:Untitled-1"), "untitled:Untitled-1");
+    }
+
     #[test]
     fn converts_lsp_pos_to_pos() {
         macro_rules! test_case {
@@ -1077,7 +1236,8 @@ mod tests {
                 let doc = Rope::from($doc);
                 let pos = lsp::Position::new($x, $y);
                 assert_eq!($want, lsp_pos_to_pos(&doc, pos, OffsetEncoding::Utf16));
-                assert_eq!($want, lsp_pos_to_pos(&doc, pos, OffsetEncoding::Utf8))
+                assert_eq!($want, lsp_pos_to_pos(&doc, pos, OffsetEncoding::Utf8));
+                assert_eq!($want, lsp_pos_to_pos(&doc, pos, OffsetEncoding::Utf32))
             };
         }
 
@@ -1095,6 +1255,92 @@ mod tests {
         test_case!("", (u32::MAX, u32::MAX) => Some(0));
     }
 
+    #[test]
+    fn converts_pos_with_multibyte_chars() {
+        // '🎄' is 1 char, 2 UTF-16 code units and 4 UTF-8 bytes; the combining
+        // accent in "e\u{0301}" is 1 char, 1 UTF-16 code unit and 2 UTF-8 bytes.
+        let doc = Rope::from("a🎄b\ne\u{0301}x");
+
+        // 'b' is char index 2 on the first line
+        assert_eq!(
+            pos_to_lsp_pos(&doc, 2, OffsetEncoding::Utf8),
+            lsp::Position::new(0, 5)
+        );
+        assert_eq!(
+            pos_to_lsp_pos(&doc, 2, OffsetEncoding::Utf16),
+            lsp::Position::new(0, 3)
+        );
+        assert_eq!(
+            pos_to_lsp_pos(&doc, 2, OffsetEncoding::Utf32),
+            lsp::Position::new(0, 2)
+        );
+
+        // 'x' is char index 6, after the combining accent on the second line
+        assert_eq!(
+            pos_to_lsp_pos(&doc, 6, OffsetEncoding::Utf8),
+            lsp::Position::new(1, 3)
+        );
+        assert_eq!(
+            pos_to_lsp_pos(&doc, 6, OffsetEncoding::Utf16),
+            lsp::Position::new(1, 2)
+        );
+        assert_eq!(
+            pos_to_lsp_pos(&doc, 6, OffsetEncoding::Utf32),
+            lsp::Position::new(1, 2)
+        );
+    }
+
+    #[test]
+    fn lsp_pos_conversion_roundtrips_in_every_encoding() {
+        use helix_core::Range;
+
+        let doc = Rope::from("fn 🚀(e\u{0301}: ()) {}\n");
+        for encoding in [
+            OffsetEncoding::Utf8,
+            OffsetEncoding::Utf16,
+            OffsetEncoding::Utf32,
+        ] {
+            for pos in 0..=doc.len_chars() {
+                let lsp_pos = pos_to_lsp_pos(&doc, pos, encoding);
+                assert_eq!(
+                    lsp_pos_to_pos(&doc, lsp_pos, encoding),
+                    Some(pos),
+                    "pos {pos} does not roundtrip through {encoding:?}"
+                );
+            }
+
+            // the range helpers funnel through the same conversions
+            let range = Range::new(3, doc.len_chars() - 1);
+            let lsp_range = range_to_lsp_range(&doc, range, encoding);
+            assert_eq!(
+                lsp_range_to_range(&doc, lsp_range, encoding),
+                Some(range),
+                "range does not roundtrip through {encoding:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn forced_offset_encoding_overrides_advertised() {
+        use crate::client::{parse_offset_encoding, resolve_offset_encoding};
+
+        let advertised = lsp::PositionEncodingKind::UTF16;
+        // a deliberately mismatched override wins over the advertised encoding
+        assert_eq!(
+            resolve_offset_encoding(Some(OffsetEncoding::Utf8), Some(&advertised)),
+            OffsetEncoding::Utf8
+        );
+        // without an override the advertised encoding is used
+        assert_eq!(
+            resolve_offset_encoding(None, Some(&advertised)),
+            OffsetEncoding::Utf16
+        );
+        // the protocol default applies when nothing is advertised
+        assert_eq!(resolve_offset_encoding(None, None), OffsetEncoding::Utf16);
+        assert_eq!(parse_offset_encoding("utf-32"), Some(OffsetEncoding::Utf32));
+        assert_eq!(parse_offset_encoding("latin-1"), None);
+    }
+
     #[test]
     fn emoji_format_gh_4791() {
         use lsp_types::{Position, Range, TextEdit};
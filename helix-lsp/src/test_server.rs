@@ -0,0 +1,213 @@
+//! A scriptable in-process language server for integration tests.
+//!
+//! Spawning real language servers in tests is slow and depends on the
+//! environment, so this module provides a minimal server that speaks the LSP
+//! wire protocol over an in-memory pipe and answers requests from canned JSON
+//! fixtures. [Client::start_with_transport](crate::Client::start_with_transport)
+//! connects a regular client to it and
+//! [Registry::register_client](crate::Registry::register_client) hooks the
+//! pair into an editor, so code under test goes through exactly the same
+//! transport and dispatch paths as with a spawned server.
+//!
+//! The server answers `initialize` (with the capabilities it was constructed
+//! with), `shutdown` and `exit` by itself; everything else is looked up in
+//! the scripted responses. Unscripted requests fail with a "method not
+//! found" error so a missing fixture surfaces in the test instead of hanging
+//! it.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::jsonrpc;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream, ReadHalf,
+    WriteHalf,
+};
+use tokio::sync::mpsc::unbounded_channel;
+
+/// A canned reply for one request.
+#[derive(Debug, Clone)]
+pub struct ScriptedResponse {
+    result: Result<Value, (i64, String)>,
+    delay: Duration,
+}
+
+impl ScriptedResponse {
+    /// A successful reply carrying `result`.
+    pub fn ok(result: Value) -> Self {
+        Self {
+            result: Ok(result),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// An error reply with the given JSON-RPC error code and message.
+    pub fn error(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            result: Err((code, message.into())),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Delays the reply, e.g. to test what the editor does while a request
+    /// is in flight.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// The scripted server: the capabilities it advertises during the handshake
+/// and a queue of responses per request method.
+pub struct TestServer {
+    capabilities: Value,
+    responses: Mutex<HashMap<String, VecDeque<ScriptedResponse>>>,
+    received: Mutex<Vec<(String, Value)>>,
+}
+
+impl TestServer {
+    /// Creates a server advertising `capabilities` (the JSON value of the
+    /// `ServerCapabilities` sent back from `initialize`).
+    pub fn new(capabilities: Value) -> Arc<Self> {
+        Arc::new(Self {
+            capabilities,
+            responses: Mutex::new(HashMap::new()),
+            received: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Scripts the reply for the next request of `method`. Responses for the
+    /// same method are handed out in the order they were scripted; the last
+    /// one is reused if more requests come in than were scripted.
+    pub fn respond(&self, method: impl Into<String>, response: ScriptedResponse) {
+        self.responses
+            .lock()
+            .entry(method.into())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// The method and params of every request and notification received so
+    /// far, in order.
+    pub fn received(&self) -> Vec<(String, Value)> {
+        self.received.lock().clone()
+    }
+
+    /// Starts the server task and returns the client's half of the
+    /// connection, ready to be passed to
+    /// [Client::start_with_transport](crate::Client::start_with_transport).
+    pub fn connect(
+        self: &Arc<Self>,
+    ) -> (BufReader<ReadHalf<DuplexStream>>, WriteHalf<DuplexStream>) {
+        let (client_io, server_io) = tokio::io::duplex(1024 * 1024);
+        let (server_reader, server_writer) = tokio::io::split(server_io);
+        tokio::spawn(
+            self.clone()
+                .run(BufReader::new(server_reader), server_writer),
+        );
+        let (client_reader, client_writer) = tokio::io::split(client_io);
+        (BufReader::new(client_reader), client_writer)
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        mut reader: BufReader<ReadHalf<DuplexStream>>,
+        mut writer: WriteHalf<DuplexStream>,
+    ) {
+        // all replies are funneled through a single writer task so that
+        // delayed responses don't interleave mid-message
+        let (tx, mut rx) = unbounded_channel::<String>();
+        let writer_task = tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                let framed = format!("Content-Length: {}\r\n\r\n{}", msg.len(), msg);
+                if writer.write_all(framed.as_bytes()).await.is_err() {
+                    break;
+                }
+                let _ = writer.flush().await;
+            }
+        });
+
+        while let Some(msg) = recv_message(&mut reader).await {
+            let method = msg
+                .get("method")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            self.received
+                .lock()
+                .push((method.clone(), msg.get("params").cloned().unwrap_or(Value::Null)));
+
+            let Some(id) = msg.get("id").cloned() else {
+                // notification
+                if method == "exit" {
+                    break;
+                }
+                continue;
+            };
+
+            let response = match method.as_str() {
+                "initialize" => {
+                    ScriptedResponse::ok(json!({ "capabilities": self.capabilities }))
+                }
+                "shutdown" => ScriptedResponse::ok(Value::Null),
+                _ => self.next_response(&method),
+            };
+
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                if !response.delay.is_zero() {
+                    tokio::time::sleep(response.delay).await;
+                }
+                let body = match response.result {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+                let _ = tx.send(body.to_string());
+            });
+        }
+
+        drop(tx);
+        let _ = writer_task.await;
+    }
+
+    fn next_response(&self, method: &str) -> ScriptedResponse {
+        let mut responses = self.responses.lock();
+        match responses.get_mut(method) {
+            // the queue is never left empty so the last response sticks
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) => queue.front().cloned().unwrap(),
+            None => ScriptedResponse::error(
+                jsonrpc::ErrorCode::MethodNotFound.code(),
+                format!("no scripted response for {method}"),
+            ),
+        }
+    }
+}
+
+async fn recv_message(reader: &mut (impl AsyncBufRead + Unpin)) -> Option<Value> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await.ok()? == 0 {
+            return None;
+        }
+        if line == "\r\n" {
+            break;
+        }
+        if let Some(value) = line.trim().strip_prefix("Content-Length: ") {
+            content_length = value.parse().ok();
+        }
+    }
+
+    let mut content = vec![0; content_length?];
+    reader.read_exact(&mut content).await.ok()?;
+    serde_json::from_slice(&content).ok()
+}
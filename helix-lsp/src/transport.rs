@@ -6,8 +6,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::{
-    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
-    process::{ChildStderr, ChildStdin, ChildStdout},
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt},
     sync::{
         mpsc::{unbounded_channel, Sender, UnboundedReceiver, UnboundedSender},
         Mutex, Notify,
@@ -44,9 +43,9 @@ pub struct Transport {
 
 impl Transport {
     pub fn start(
-        server_stdout: BufReader<ChildStdout>,
-        server_stdin: BufWriter<ChildStdin>,
-        server_stderr: BufReader<ChildStderr>,
+        server_stdout: impl AsyncBufRead + Unpin + Send + 'static,
+        server_stdin: impl AsyncWrite + Unpin + Send + 'static,
+        server_stderr: impl AsyncBufRead + Unpin + Send + 'static,
         id: LanguageServerId,
         name: String,
     ) -> (
@@ -152,7 +151,7 @@ impl Transport {
 
     async fn send_payload_to_server(
         &self,
-        server_stdin: &mut BufWriter<ChildStdin>,
+        server_stdin: &mut (impl AsyncWrite + Unpin),
         payload: Payload,
     ) -> Result<()> {
         //TODO: reuse string
@@ -173,7 +172,7 @@ impl Transport {
 
     async fn send_string_to_server(
         &self,
-        server_stdin: &mut BufWriter<ChildStdin>,
+        server_stdin: &mut (impl AsyncWrite + Unpin),
         request: String,
         language_server_name: &str,
     ) -> Result<()> {
@@ -250,7 +249,7 @@ impl Transport {
 
     async fn recv(
         transport: Arc<Self>,
-        mut server_stdout: BufReader<ChildStdout>,
+        mut server_stdout: impl AsyncBufRead + Unpin + Send,
         client_tx: UnboundedSender<(LanguageServerId, jsonrpc::Call)>,
     ) {
         let mut recv_buffer = String::new();
@@ -311,7 +310,7 @@ impl Transport {
         }
     }
 
-    async fn err(transport: Arc<Self>, mut server_stderr: BufReader<ChildStderr>) {
+    async fn err(transport: Arc<Self>, mut server_stderr: impl AsyncBufRead + Unpin + Send) {
         let mut recv_buffer = String::new();
         loop {
             match Self::recv_server_error(&mut server_stderr, &mut recv_buffer, &transport.name)
@@ -328,7 +327,7 @@ impl Transport {
 
     async fn send(
         transport: Arc<Self>,
-        mut server_stdin: BufWriter<ChildStdin>,
+        mut server_stdin: impl AsyncWrite + Unpin + Send,
         client_tx: UnboundedSender<(LanguageServerId, jsonrpc::Call)>,
         mut client_rx: UnboundedReceiver<Payload>,
         initialize_notify: Arc<Notify>,
@@ -151,6 +151,30 @@ where
     path
 }
 
+/// Simplifies a path for display: strips the Windows extended-length (`\\?\`)
+/// prefix where possible and normalizes mixed `/` and `\` separators to the
+/// platform's main separator. On other platforms this is (nearly) a no-op.
+///
+/// Paths produced by `Url::to_file_path` or `std::fs::canonicalize` can carry
+/// the verbatim prefix on Windows and it is meaningless to users.
+pub fn simplified(path: &Path) -> PathBuf {
+    let path = dunce::simplified(path);
+    // Re-collecting the components normalizes the separators.
+    path.components().collect()
+}
+
+/// Formats a path for display in picker columns and the statusline: the path is
+/// [simplified] and then relativized against the current working directory
+/// (falling back to folding the home directory into `~`).
+///
+/// The result is only meant to be displayed, jumps and other file system
+/// operations must keep using the original path.
+pub fn display_path(path: impl AsRef<Path>) -> String {
+    get_relative_path(simplified(path.as_ref()))
+        .to_string_lossy()
+        .into_owned()
+}
+
 /// Returns a truncated filepath where the basepart of the path is reduced to the first
 /// char of the folder and the whole filename appended.
 ///
@@ -210,6 +234,40 @@ mod tests {
 
     use crate::path;
 
+    #[cfg(windows)]
+    #[test]
+    fn simplified_windows() {
+        // verbatim paths lose their extended-length prefix
+        assert_eq!(
+            path::simplified(Path::new(r"\\?\C:\foo\bar.txt")),
+            Path::new(r"C:\foo\bar.txt")
+        );
+        // verbatim UNC paths become plain UNC paths
+        assert_eq!(
+            path::simplified(Path::new(r"\\?\UNC\server\share\foo.txt")),
+            Path::new(r"\\server\share\foo.txt")
+        );
+        // mixed separators are normalized
+        assert_eq!(
+            path::simplified(Path::new(r"C:\foo/bar\baz.txt")),
+            Path::new(r"C:\foo\bar\baz.txt")
+        );
+        // drive-relative paths are left alone apart from separators
+        assert_eq!(
+            path::simplified(Path::new(r"C:foo/bar.txt")),
+            Path::new(r"C:foo\bar.txt")
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn simplified_is_a_no_op() {
+        assert_eq!(
+            path::simplified(Path::new("/home/user/file.txt")),
+            Path::new("/home/user/file.txt")
+        );
+    }
+
     #[test]
     fn expand_tilde() {
         for path in ["~", "~/foo"] {
@@ -10,7 +10,7 @@ use helix_stdx::path::get_relative_path;
 use helix_view::{
     align_view,
     document::DocumentSavedEventResult,
-    editor::{ConfigEvent, EditorEvent},
+    editor::{ConfigEvent, EditorEvent, LspFallback},
     events::DiagnosticsDidChange,
     graphics::Rect,
     theme,
@@ -22,6 +22,7 @@ use tui::backend::Backend;
 
 use crate::{
     args::Args,
+    commands,
     compositor::{Compositor, Event},
     config::Config,
     handlers,
@@ -33,7 +34,7 @@ use crate::{
 use log::{debug, error, info, warn};
 #[cfg(not(feature = "integration"))]
 use std::io::stdout;
-use std::{collections::btree_map::Entry, io::stdin, path::Path, sync::Arc};
+use std::{io::stdin, path::Path, sync::Arc};
 
 use anyhow::{Context, Error};
 
@@ -234,7 +235,7 @@ impl Application {
         ])
         .context("build signal handler")?;
 
-        let app = Self {
+        let mut app = Self {
             compositor,
             terminal,
             editor,
@@ -249,6 +250,8 @@ impl Application {
             lsp_progress: LspProgressMap::new(),
         };
 
+        helix_view::diagnostics_cache::load(&mut app.editor);
+
         Ok(app)
     }
 
@@ -322,6 +325,7 @@ impl Application {
                 }
                 Some(callback) = self.jobs.callbacks.recv() => {
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, Ok(Some(callback)));
+                    self.run_lsp_fallback();
                     self.render().await;
                 }
                 Some(msg) = self.jobs.status_messages.recv() => {
@@ -337,6 +341,7 @@ impl Application {
                 }
                 Some(callback) = self.jobs.wait_futures.next() => {
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
+                    self.run_lsp_fallback();
                     self.render().await;
                 }
                 event = self.editor.wait_event() => {
@@ -568,8 +573,23 @@ impl Application {
         let lines = doc_save_event.text.len_lines();
         let bytes = doc_save_event.text.len_bytes();
 
+        let old_path = doc.path().cloned();
         self.editor
             .set_doc_path(doc_save_event.doc_id, &doc_save_event.path);
+        // a save-as whose source file has since been removed from disk is
+        // effectively a rename, let the servers update their project model
+        // (`Editor::move_path` covers the willRename side for real renames)
+        if let Some(old_path) = old_path.filter(|old_path| {
+            old_path != &doc_save_event.path && !old_path.exists()
+        }) {
+            for language_server in self.editor.language_servers.iter_clients() {
+                if let Some(notification) =
+                    language_server.did_rename(&old_path, &doc_save_event.path, false)
+                {
+                    tokio::spawn(notification);
+                }
+            }
+        }
         // TODO: fix being overwritten by lsp
         self.editor.set_status(format!(
             "'{}' written, {}L {}B",
@@ -600,6 +620,11 @@ impl Application {
             EditorEvent::DebuggerEvent(payload) => {
                 let needs_render = self.editor.handle_debugger_message(payload).await;
                 if needs_render {
+                    // the debuggee may have stopped on a new frame
+                    commands::compute_inline_values_for_all_views(
+                        &mut self.editor,
+                        &mut self.jobs,
+                    );
                     self.render().await;
                 }
             }
@@ -653,6 +678,131 @@ impl Application {
         }
     }
 
+    /// Records a server's work-done progress in `Editor::lsp_busy` so that
+    /// commands can tell "no answer" from "still indexing". Not called for
+    /// `End`; see [`Self::server_became_ready`].
+    fn update_server_busy(&mut self, server_id: LanguageServerId, work: &lsp::WorkDoneProgress) {
+        let status = match work {
+            lsp::WorkDoneProgress::Begin(lsp::WorkDoneProgressBegin {
+                title,
+                message,
+                percentage,
+                ..
+            }) => {
+                let mut status = title.clone();
+                if let Some(message) = message {
+                    status = format!("{status}: {message}");
+                }
+                if let Some(percentage) = percentage {
+                    status = format!("{status} {percentage}%");
+                }
+                status
+            }
+            lsp::WorkDoneProgress::Report(lsp::WorkDoneProgressReport {
+                message,
+                percentage,
+                ..
+            }) => {
+                // keep the title from the begin notification when the report
+                // only carries a percentage
+                let mut status = match message {
+                    Some(message) => message.clone(),
+                    None => self
+                        .editor
+                        .lsp_busy
+                        .get(&server_id)
+                        .map_or("busy", |status| {
+                            status
+                                .trim_end_matches(|c: char| c.is_ascii_digit() || c == '%')
+                                .trim_end()
+                        })
+                        .to_string(),
+                };
+                if let Some(percentage) = percentage {
+                    status = format!("{status} {percentage}%");
+                }
+                status
+            }
+            lsp::WorkDoneProgress::End(_) => return,
+        };
+        self.editor.lsp_busy.insert(server_id, status);
+    }
+
+    /// Clears `server_id`'s busy marker and re-runs the user command parked
+    /// on it (`lsp.server-not-ready = "wait"`), if any. Called once the
+    /// server has no work-done progress left, and after `initialized` for
+    /// servers that never report any.
+    fn server_became_ready(&mut self, server_id: LanguageServerId) {
+        self.editor.lsp_busy.remove(&server_id);
+        let Some(pending) = self.editor.pending_lsp_command else {
+            return;
+        };
+        if pending.server != server_id
+            || !self
+                .editor
+                .language_server_by_id(server_id)
+                .map_or(false, |ls| ls.is_initialized())
+        {
+            return;
+        }
+        self.editor.pending_lsp_command = None;
+        let Some(command) = commands::MappableCommand::STATIC_COMMAND_LIST
+            .iter()
+            .find(|command| command.name() == pending.command)
+        else {
+            return;
+        };
+        let mut cx = commands::Context {
+            register: None,
+            count: None,
+            editor: &mut self.editor,
+            callback: Vec::new(),
+            on_next_key_callback: None,
+            jobs: &mut self.jobs,
+        };
+        command.execute(&mut cx);
+        for callback in cx.callback {
+            let mut cx = crate::compositor::Context {
+                editor: &mut self.editor,
+                jobs: &mut self.jobs,
+                scroll: None,
+            };
+            callback(&mut self.compositor, &mut cx);
+        }
+    }
+
+    /// Runs the fallback command of an `lsp_or` key binding whose primary
+    /// request came back empty (see [`LspFallback`]). Executed here rather
+    /// than in the result callback because job callbacks have no access to
+    /// a full command context.
+    fn run_lsp_fallback(&mut self) {
+        let Some(LspFallback::Triggered(fallback)) = &self.editor.lsp_fallback else {
+            return;
+        };
+        let Ok(command) = fallback.parse::<commands::MappableCommand>() else {
+            self.editor.lsp_fallback = None;
+            return;
+        };
+        self.editor.lsp_fallback = None;
+        let mut cx = commands::Context {
+            register: None,
+            count: None,
+            editor: &mut self.editor,
+            callback: Vec::new(),
+            on_next_key_callback: None,
+            jobs: &mut self.jobs,
+        };
+        command.execute(&mut cx);
+        for callback in cx.callback {
+            let mut cx = crate::compositor::Context {
+                editor: &mut self.editor,
+                jobs: &mut self.jobs,
+                scroll: None,
+            };
+            callback(&mut self.compositor, &mut cx);
+        }
+    }
+
     pub async fn handle_language_server_message(
         &mut self,
         call: helix_lsp::Call,
@@ -722,133 +872,92 @@ impl Application {
                                 language_id,
                             ));
                         }
+
+                        // a user command may be parked on this server
+                        // (`lsp.server-not-ready = "wait"`); if it doesn't
+                        // report start-up progress this is the only wake-up
+                        self.server_became_ready(server_id);
                     }
                     Notification::PublishDiagnostics(mut params) => {
-                        let path = match params.uri.to_file_path() {
-                            Ok(path) => helix_stdx::path::normalize(path),
-                            Err(_) => {
-                                log::error!("Unsupported file URI: {}", params.uri);
-                                return;
-                            }
-                        };
                         let language_server = language_server!();
                         if !language_server.is_initialized() {
                             log::error!("Discarding publishDiagnostic notification sent by an uninitialized server: {}", language_server.name());
                             return;
                         }
-                        // have to inline the function because of borrow checking...
-                        let doc = self.editor.documents.values_mut()
-                            .find(|doc| doc.path().map(|p| p == &path).unwrap_or(false))
-                            .filter(|doc| {
-                                if let Some(version) = params.version {
-                                    if version != doc.version() {
-                                        log::info!("Version ({version}) is out of date for {path:?} (expected ({}), dropping PublishDiagnostic notification", doc.version());
-                                        return false;
-                                    }
-                                }
-                                true
-                            });
-
-                        let mut unchanged_diag_sources = Vec::new();
-                        if let Some(doc) = &doc {
-                            let lang_conf = doc.language.clone();
-
-                            if let Some(lang_conf) = &lang_conf {
-                                if let Some(old_diagnostics) = self.editor.diagnostics.get(&path) {
-                                    if !lang_conf.persistent_diagnostic_sources.is_empty() {
-                                        // Sort diagnostics first by severity and then by line numbers.
-                                        // Note: The `lsp::DiagnosticSeverity` enum is already defined in decreasing order
-                                        params
-                                            .diagnostics
-                                            .sort_by_key(|d| (d.severity, d.range.start));
-                                    }
-                                    for source in &lang_conf.persistent_diagnostic_sources {
-                                        let new_diagnostics = params
-                                            .diagnostics
-                                            .iter()
-                                            .filter(|d| d.source.as_ref() == Some(source));
-                                        let old_diagnostics = old_diagnostics
-                                            .iter()
-                                            .filter(|(d, d_server)| {
-                                                *d_server == server_id
-                                                    && d.source.as_ref() == Some(source)
-                                            })
-                                            .map(|(d, _)| d);
-                                        if new_diagnostics.eq(old_diagnostics) {
-                                            unchanged_diag_sources.push(source.clone())
-                                        }
-                                    }
-                                }
+                        // key the diagnostics map by one spelling of the path,
+                        // whatever URI form the server chose
+                        helix_lsp::util::normalize_uri(&mut params.uri);
+                        // The focused document bypasses batching so diagnostics
+                        // for the file being edited keep their latency; bursts
+                        // for everything else (e.g. a server publishing for
+                        // hundreds of files after indexing) are coalesced and
+                        // applied in one pass.
+                        let focused = params.uri.to_file_path().is_ok_and(|path| {
+                            let path = helix_stdx::path::normalize(path);
+                            doc!(self.editor).path() == Some(&path)
+                        });
+                        if focused {
+                            if let Some(doc) = handlers::diagnostics::apply_published_diagnostics(
+                                &mut self.editor,
+                                server_id,
+                                params,
+                            ) {
+                                helix_event::dispatch(DiagnosticsDidChange {
+                                    editor: &mut self.editor,
+                                    doc,
+                                });
                             }
-                        }
-
-                        let diagnostics = params.diagnostics.into_iter().map(|d| (d, server_id));
-
-                        // Insert the original lsp::Diagnostics here because we may have no open document
-                        // for diagnosic message and so we can't calculate the exact position.
-                        // When using them later in the diagnostics picker, we calculate them on-demand.
-                        let diagnostics = match self.editor.diagnostics.entry(path) {
-                            Entry::Occupied(o) => {
-                                let current_diagnostics = o.into_mut();
-                                // there may entries of other language servers, which is why we can't overwrite the whole entry
-                                current_diagnostics.retain(|(_, lsp_id)| *lsp_id != server_id);
-                                current_diagnostics.extend(diagnostics);
-                                current_diagnostics
-                                // Sort diagnostics first by severity and then by line numbers.
-                            }
-                            Entry::Vacant(v) => v.insert(diagnostics.collect()),
-                        };
-
-                        // Sort diagnostics first by severity and then by line numbers.
-                        // Note: The `lsp::DiagnosticSeverity` enum is already defined in decreasing order
-                        diagnostics
-                            .sort_by_key(|(d, server_id)| (d.severity, d.range.start, *server_id));
-
-                        if let Some(doc) = doc {
-                            let diagnostic_of_language_server_and_not_in_unchanged_sources =
-                                |diagnostic: &lsp::Diagnostic, ls_id| {
-                                    ls_id == server_id
-                                        && diagnostic.source.as_ref().map_or(true, |source| {
-                                            !unchanged_diag_sources.contains(source)
-                                        })
-                                };
-                            let diagnostics = Editor::doc_diagnostics_with_filter(
-                                &self.editor.language_servers,
-                                &self.editor.diagnostics,
-                                doc,
-                                diagnostic_of_language_server_and_not_in_unchanged_sources,
-                            );
-                            doc.replace_diagnostics(
-                                diagnostics,
-                                &unchanged_diag_sources,
-                                Some(server_id),
+                            self.editor.recompute_workspace_diagnostic_counts();
+                        } else {
+                            helix_event::send_blocking(
+                                &self.editor.handlers.publish_diagnostics,
+                                helix_view::handlers::lsp::PublishDiagnosticsEvent {
+                                    server_id,
+                                    params,
+                                },
                             );
-
-                            let doc = doc.id();
-                            helix_event::dispatch(DiagnosticsDidChange {
-                                editor: &mut self.editor,
-                                doc,
-                            });
                         }
                     }
                     Notification::ShowMessage(params) => {
-                        log::warn!("unhandled window/showMessage: {:?}", params);
+                        // A server configured with `suppress-command-messages`
+                        // keeps its messages off the status line while one of
+                        // its commands is executing; they still land in the
+                        // log and the command output buffer.
+                        let suppress = self.editor.lsp_command_capture == Some(server_id)
+                            && self
+                                .editor
+                                .language_server_by_id(server_id)
+                                .is_some_and(|ls| ls.suppress_command_messages());
+                        if suppress {
+                            log::info!("window/showMessage (suppressed): {:?}", params);
+                            self.editor.append_lsp_command_output(&params.message);
+                        } else if params.typ == lsp::MessageType::ERROR {
+                            self.editor.set_error(params.message);
+                        } else {
+                            self.editor.set_status(params.message);
+                        }
                     }
                     Notification::LogMessage(params) => {
                         log::info!("window/logMessage: {:?}", params);
+                        if self.editor.lsp_command_capture == Some(server_id) {
+                            self.editor.append_lsp_command_output(&params.message);
+                        }
                     }
                     Notification::ProgressMessage(params)
                         if !self
                             .compositor
                             .has_component(std::any::type_name::<ui::Prompt>()) =>
                     {
+                        let lsp::ProgressParams { token, value } = params;
+
+                        let lsp::ProgressParamsValue::WorkDone(work) = value;
+                        if !matches!(work, lsp::WorkDoneProgress::End(_)) {
+                            self.update_server_busy(server_id, &work);
+                        }
                         let editor_view = self
                             .compositor
                             .find::<ui::EditorView>()
                             .expect("expected at least one EditorView");
-                        let lsp::ProgressParams { token, value } = params;
-
-                        let lsp::ProgressParamsValue::WorkDone(work) = value;
                         let parts = match &work {
                             lsp::WorkDoneProgress::Begin(lsp::WorkDoneProgressBegin {
                                 title,
@@ -868,8 +977,11 @@ impl Application {
                                     self.lsp_progress.end_progress(server_id, &token);
                                     if !self.lsp_progress.is_progressing(server_id) {
                                         editor_view.spinners_mut().get_or_create(server_id).stop();
+                                        self.editor.clear_status();
+                                        self.server_became_ready(server_id);
+                                    } else {
+                                        self.editor.clear_status();
                                     }
-                                    self.editor.clear_status();
 
                                     // we want to render to clear any leftover spinners or messages
                                     return;
@@ -907,10 +1019,15 @@ impl Application {
                             (None, None, None) => format!("[{}]", token_d),
                         };
 
+                        if self.editor.lsp_command_capture == Some(server_id) {
+                            self.editor.append_lsp_command_output(&status);
+                        }
+
                         if let lsp::WorkDoneProgress::End(_) = work {
                             self.lsp_progress.end_progress(server_id, &token);
                             if !self.lsp_progress.is_progressing(server_id) {
                                 editor_view.spinners_mut().get_or_create(server_id).stop();
+                                self.server_became_ready(server_id);
                             }
                         } else {
                             self.lsp_progress.update(server_id, token, work);
@@ -920,8 +1037,20 @@ impl Application {
                             self.editor.set_status(status);
                         }
                     }
-                    Notification::ProgressMessage(_params) => {
-                        // do nothing
+                    Notification::ProgressMessage(params) => {
+                        // a prompt is open: skip the status-line updates but
+                        // keep the progress bookkeeping (and with it parked
+                        // commands) moving
+                        let lsp::ProgressParamsValue::WorkDone(work) = params.value;
+                        if let lsp::WorkDoneProgress::End(_) = work {
+                            self.lsp_progress.end_progress(server_id, &params.token);
+                            if !self.lsp_progress.is_progressing(server_id) {
+                                self.server_became_ready(server_id);
+                            }
+                        } else {
+                            self.update_server_busy(server_id, &work);
+                            self.lsp_progress.update(server_id, params.token, work);
+                        }
                     }
                     Notification::Exit => {
                         self.editor.set_status("Language server exited");
@@ -934,6 +1063,7 @@ impl Application {
                         }
 
                         self.editor.diagnostics.retain(|_, diags| !diags.is_empty());
+                        self.editor.recompute_workspace_diagnostic_counts();
 
                         // Clear any diagnostics for documents with this server open.
                         for doc in self.editor.documents_mut() {
@@ -991,6 +1121,18 @@ impl Application {
                         let language_server = language_server!();
                         if language_server.is_initialized() {
                             let offset_encoding = language_server.offset_encoding();
+                            if language_server.confirm_workspace_edits() {
+                                // the reply is deferred until the user
+                                // answered the prompt
+                                let client = self
+                                    .editor
+                                    .language_servers
+                                    .get_by_id(server_id)
+                                    .expect("language server to be available")
+                                    .clone();
+                                self.prompt_workspace_edit(client, id, params, offset_encoding);
+                                return;
+                            }
                             let res = self
                                 .editor
                                 .apply_workspace_edit(offset_encoding, &params.edit);
@@ -1104,6 +1246,83 @@ impl Application {
         }
     }
 
+    /// Summarizes a `workspace/applyEdit` request in a prompt and defers the
+    /// reply to the server until the user accepted or rejected it. Used for
+    /// servers configured with `confirm-workspace-edits`.
+    fn prompt_workspace_edit(
+        &mut self,
+        client: Arc<helix_lsp::Client>,
+        request_id: helix_lsp::jsonrpc::Id,
+        params: lsp::ApplyWorkspaceEditParams,
+        offset_encoding: helix_lsp::OffsetEncoding,
+    ) {
+        let mut files = 0usize;
+        let mut edits = 0usize;
+        let mut resource_ops = 0usize;
+        if let Some(changes) = &params.edit.changes {
+            files += changes.len();
+            edits += changes.values().map(Vec::len).sum::<usize>();
+        }
+        match &params.edit.document_changes {
+            Some(lsp::DocumentChanges::Edits(document_edits)) => {
+                files += document_edits.len();
+                edits += document_edits.iter().map(|edit| edit.edits.len()).sum::<usize>();
+            }
+            Some(lsp::DocumentChanges::Operations(operations)) => {
+                for operation in operations {
+                    match operation {
+                        lsp::DocumentChangeOperation::Edit(edit) => {
+                            files += 1;
+                            edits += edit.edits.len();
+                        }
+                        lsp::DocumentChangeOperation::Op(_) => resource_ops += 1,
+                    }
+                }
+            }
+            None => (),
+        }
+
+        let label = params
+            .label
+            .as_deref()
+            .map(|label| format!(" ({label})"))
+            .unwrap_or_default();
+        let text = format!(
+            "{} requests a workspace edit{label}: {edits} edit(s) in {files} file(s), {resource_ops} resource op(s). Apply? (y/n): ",
+            client.name(),
+        );
+
+        let edit = params.edit;
+        let prompt = ui::Prompt::new(
+            text.into(),
+            None,
+            ui::completers::none,
+            move |cx, input: &str, event: ui::PromptEvent| {
+                if event != ui::PromptEvent::Validate && event != ui::PromptEvent::Abort {
+                    return;
+                }
+                let reply = if event == ui::PromptEvent::Validate
+                    && matches!(input, "y" | "Y" | "yes")
+                {
+                    let res = cx.editor.apply_workspace_edit(offset_encoding, &edit);
+                    Ok(json!(lsp::ApplyWorkspaceEditResponse {
+                        applied: res.is_ok(),
+                        failure_reason: res.as_ref().err().map(|err| err.kind.to_string()),
+                        failed_change: res.as_ref().err().map(|err| err.failed_change_idx as u32),
+                    }))
+                } else {
+                    Ok(json!(lsp::ApplyWorkspaceEditResponse {
+                        applied: false,
+                        failure_reason: Some("rejected by the user".to_string()),
+                        failed_change: None,
+                    }))
+                };
+                tokio::spawn(client.reply(request_id.clone(), reply));
+            },
+        );
+        self.compositor.push(Box::new(prompt));
+    }
+
     fn handle_show_document(
         &mut self,
         params: lsp::ShowDocumentParams,
@@ -1217,6 +1436,8 @@ impl Application {
         //        errors along the way
         let mut errs = Vec::new();
 
+        helix_view::diagnostics_cache::save(&self.editor);
+
         if let Err(err) = self
             .jobs
             .finish(&mut self.editor, Some(&mut self.compositor))
@@ -42,11 +42,11 @@ use helix_core::{
 };
 use helix_view::{
     document::{FormatterError, Mode, SCRATCH_BUFFER_NAME},
-    editor::Action,
+    editor::{Action, LspFallback},
     info::Info,
     input::KeyEvent,
     keyboard::KeyCode,
-    theme::Style,
+    theme::{Modifier, Style},
     tree,
     view::View,
     Document, DocumentId, Editor, ViewId,
@@ -193,6 +193,18 @@ pub enum MappableCommand {
         fun: fn(cx: &mut Context),
         doc: &'static str,
     },
+    /// Capability-aware fallthrough, written `lsp_or(primary, fallback)` in
+    /// the keymap: runs `primary` when an attached language server provides
+    /// the feature it requires and `fallback` otherwise. The check only
+    /// inspects server capabilities, no request is issued. With
+    /// `lsp.fallthrough-on-empty` the fallback also runs when the primary's
+    /// request comes back empty.
+    LspOr {
+        name: String,
+        doc: String,
+        primary: Box<MappableCommand>,
+        fallback: Box<MappableCommand>,
+    },
 }
 
 macro_rules! static_commands {
@@ -214,6 +226,9 @@ macro_rules! static_commands {
 
 impl MappableCommand {
     pub fn execute(&self, cx: &mut Context) {
+        // any new command invalidates a fallback armed by a previous `lsp_or`
+        // dispatch; `LspOr` re-arms below once its primary went out
+        cx.editor.lsp_fallback = None;
         match &self {
             Self::Typable { name, args, doc: _ } => {
                 let args: Vec<Cow<str>> = args.iter().map(Cow::from).collect();
@@ -229,6 +244,18 @@ impl MappableCommand {
                 }
             }
             Self::Static { fun, .. } => (fun)(cx),
+            Self::LspOr {
+                primary, fallback, ..
+            } => {
+                if primary.is_supported(doc!(cx.editor)) {
+                    primary.execute(cx);
+                    if cx.editor.config().lsp.fallthrough_on_empty {
+                        cx.editor.lsp_fallback = Some(LspFallback::Armed(fallback.spec()));
+                    }
+                } else {
+                    fallback.execute(cx);
+                }
+            }
         }
     }
 
@@ -236,6 +263,28 @@ impl MappableCommand {
         match &self {
             Self::Typable { name, .. } => name,
             Self::Static { name, .. } => name,
+            Self::LspOr { name, .. } => name,
+        }
+    }
+
+    /// The string form that parses back into this command via [`FromStr`].
+    fn spec(&self) -> String {
+        match self {
+            Self::Typable { name, args, .. } if !args.is_empty() => {
+                format!(":{} {}", name, args.join(" "))
+            }
+            Self::Typable { name, .. } => format!(":{name}"),
+            _ => self.name().to_string(),
+        }
+    }
+
+    /// Whether the command can work for `doc`: commands that require a
+    /// language server feature are unsupported when no attached server
+    /// provides it. See [`lsp::required_feature`].
+    pub fn is_supported(&self, doc: &Document) -> bool {
+        match lsp::required_feature(self.name()) {
+            Some(feature) => doc.language_servers_with_feature(feature).next().is_some(),
+            None => true,
         }
     }
 
@@ -243,6 +292,7 @@ impl MappableCommand {
         match &self {
             Self::Typable { doc, .. } => doc,
             Self::Static { doc, .. } => doc,
+            Self::LspOr { doc, .. } => doc,
         }
     }
 
@@ -340,15 +390,31 @@ impl MappableCommand {
         file_picker_in_current_buffer_directory, "Open file picker at current buffer's directory",
         file_picker_in_current_directory, "Open file picker at current working directory",
         code_action, "Perform code action",
+        code_action_favorites, "Perform code action, pre-filtered to the pinned favorites",
+        quick_fix_under_cursor, "Apply the quick fix for the diagnostic under the cursor",
+        apply_diagnostic_replacement, "Apply the diagnostic's suggested replacement text directly",
+        extract_and_rename, "Apply an extract refactor and rename the extracted symbol",
         buffer_picker, "Open buffer picker",
         jumplist_picker, "Open jumplist picker",
         symbol_picker, "Open symbol picker",
         symbol_method_picker, "Open method picker",
+        goto_nth_symbol_of_kind, "Jump to the count-th symbol of a kind after the cursor",
         changed_file_picker, "Open changed file picker",
         select_references_to_symbol_under_cursor, "Select symbol references",
+        next_document_highlight, "Move primary selection to next occurrence of the symbol",
+        prev_document_highlight, "Move primary selection to previous occurrence of the symbol",
         workspace_symbol_picker, "Open workspace symbol picker",
         diagnostics_picker, "Open diagnostic picker",
         workspace_diagnostics_picker, "Open workspace diagnostic picker",
+        todo_diagnostics_picker, "Open a picker of the TODO/FIXME-style diagnostics in the workspace",
+        server_diagnostics_picker, "Open workspace diagnostic picker scoped to one language server",
+        show_diagnostics_under_cursor, "Show the diagnostics under the cursor in full in a popup",
+        diagnostics_by_code, "Open a picker of diagnostic codes with counts, narrowing to one code",
+        lsp_command_output, "Toggle or focus the LSP command output buffer",
+        lsp_jump_picker, "Open a picker over recorded LSP navigation jumps",
+        apply_code_actions_kind_all_buffers, "Apply all code actions of a kind in every open buffer",
+        pull_diagnostics, "Request diagnostics for the current document (pull model)",
+        symbol_for_diagnostic, "Show the symbol containing the diagnostic under the cursor",
         last_picker, "Open last picker",
         insert_at_line_start, "Insert at start of line",
         insert_at_line_end, "Insert at end of line",
@@ -362,13 +428,24 @@ impl MappableCommand {
         add_newline_above, "Add newline above",
         add_newline_below, "Add newline below",
         goto_type_definition, "Goto type definition",
+        goto_definition_and_type, "Goto definition and type definition in one picker",
+        goto_expression_type_definition, "Goto the type definition of the enclosing expression",
         goto_implementation, "Goto implementation",
+        count_implementations, "Show the number of implementations of the item under the cursor",
+        implementations_picker, "Open a picker over the implementations found by count_implementations",
         goto_file_start, "Goto line number <n> else file start",
         goto_file_end, "Goto file end",
         goto_file, "Goto files/URLs in selections",
         goto_file_hsplit, "Goto files in selections (hsplit)",
         goto_file_vsplit, "Goto files in selections (vsplit)",
         goto_reference, "Goto references",
+        goto_reference_view, "Pin references into a persistent grep-style buffer",
+        toggle_goto_reference_declaration, "Toggle whether goto references includes the declaration this session",
+        symbol_info, "Show hover, definition and references for the symbol under the cursor in one popup",
+        diagnostic_related_picker, "Open the related information of the diagnostics under the cursor in a picker",
+        goto_first_related, "Goto the first related location of the diagnostic under the cursor",
+        references_view_refresh, "Re-run the query behind the references view",
+        references_view_jump, "Jump to the location on the current references view line",
         goto_window_top, "Goto window top",
         goto_window_center, "Goto window center",
         goto_window_bottom, "Goto window bottom",
@@ -397,6 +474,7 @@ impl MappableCommand {
         extend_to_line_end, "Extend to line end",
         extend_to_line_end_newline, "Extend to line end",
         signature_help, "Show signature help",
+        signature_help_here, "Request signature help at the cursor, bypassing typing triggers",
         smart_tab, "Insert tab if all cursors have all whitespace to their left; otherwise, run a separate command.",
         insert_tab, "Insert tab char",
         insert_newline, "Insert newline char",
@@ -546,6 +624,9 @@ impl fmt::Debug for MappableCommand {
                 .field(name)
                 .field(args)
                 .finish(),
+            MappableCommand::LspOr { name, .. } => {
+                f.debug_tuple("MappableCommand").field(name).finish()
+            }
         }
     }
 }
@@ -576,6 +657,30 @@ impl std::str::FromStr for MappableCommand {
                     args,
                 })
                 .ok_or_else(|| anyhow!("No TypableCommand named '{}'", s))
+        } else if let Some(rest) = s.strip_prefix("lsp_or(") {
+            let body = rest
+                .strip_suffix(')')
+                .ok_or_else(|| anyhow!("Expected closing ')' in '{}'", s))?;
+            // the primary is a bare feature-mapped command, so the first comma
+            // always separates it from the fallback; the fallback may itself
+            // carry commas (typable arguments, a nested `lsp_or`)
+            let (primary, fallback) = body
+                .split_once(',')
+                .ok_or_else(|| anyhow!("Expected 'lsp_or(primary, fallback)' in '{}'", s))?;
+            let primary: MappableCommand = primary.trim().parse()?;
+            if lsp::required_feature(primary.name()).is_none() {
+                bail!(
+                    "'{}' does not require a language server feature, nothing to fall through on",
+                    primary.name()
+                );
+            }
+            let fallback: MappableCommand = fallback.trim().parse()?;
+            Ok(MappableCommand::LspOr {
+                name: format!("lsp_or({}, {})", primary.spec(), fallback.spec()),
+                doc: format!("{}, or: {}", primary.doc(), fallback.doc()),
+                primary: Box::new(primary),
+                fallback: Box::new(fallback),
+            })
         } else {
             MappableCommand::STATIC_COMMAND_LIST
                 .iter()
@@ -619,6 +724,18 @@ impl PartialEq for MappableCommand {
                     name: second_name, ..
                 },
             ) => first_name == second_name,
+            (
+                MappableCommand::LspOr {
+                    primary: first_primary,
+                    fallback: first_fallback,
+                    ..
+                },
+                MappableCommand::LspOr {
+                    primary: second_primary,
+                    fallback: second_fallback,
+                    ..
+                },
+            ) => first_primary == second_primary && first_fallback == second_fallback,
             _ => false,
         }
     }
@@ -3146,9 +3263,9 @@ fn changed_file_picker(cx: &mut Context) {
 }
 
 impl ui::menu::Item for MappableCommand {
-    type Data = ReverseKeymap;
+    type Data = (ReverseKeymap, HashSet<String>);
 
-    fn format(&self, keymap: &Self::Data) -> Row {
+    fn format(&self, (keymap, unsupported): &Self::Data) -> Row {
         let fmt_binding = |bindings: &Vec<Vec<KeyEvent>>| -> String {
             bindings.iter().fold(String::new(), |mut acc, bind| {
                 if !acc.is_empty() {
@@ -3161,16 +3278,30 @@ impl ui::menu::Item for MappableCommand {
             })
         };
 
-        match self {
+        let label = match self {
             MappableCommand::Typable { doc, name, .. } => match keymap.get(name as &String) {
-                Some(bindings) => format!("{} ({}) [:{}]", doc, fmt_binding(bindings), name).into(),
-                None => format!("{} [:{}]", doc, name).into(),
+                Some(bindings) => format!("{} ({}) [:{}]", doc, fmt_binding(bindings), name),
+                None => format!("{} [:{}]", doc, name),
             },
             MappableCommand::Static { doc, name, .. } => match keymap.get(*name) {
-                Some(bindings) => format!("{} ({}) [{}]", doc, fmt_binding(bindings), name).into(),
-                None => format!("{} [{}]", doc, name).into(),
+                Some(bindings) => format!("{} ({}) [{}]", doc, fmt_binding(bindings), name),
+                None => format!("{} [{}]", doc, name),
             },
+            MappableCommand::LspOr { doc, name, .. } => match keymap.get(name as &String) {
+                Some(bindings) => format!("{} ({}) [{}]", doc, fmt_binding(bindings), name),
+                None => format!("{} [{}]", doc, name),
+            },
+        };
+        if unsupported.contains(self.name()) {
+            // dim entries whose required language server feature no attached
+            // server provides so it's clear why they would fail
+            return Span::styled(
+                format!("{label} (no language server support)"),
+                Style::default().add_modifier(Modifier::DIM),
+            )
+            .into();
         }
+        label.into()
     }
 }
 
@@ -3193,7 +3324,14 @@ pub fn command_palette(cx: &mut Context) {
                 }
             }));
 
-            let picker = Picker::new(commands, keymap, move |cx, command, _action| {
+            let doc = doc!(cx.editor);
+            let unsupported: HashSet<String> = commands
+                .iter()
+                .filter(|command| !command.is_supported(doc))
+                .map(|command| command.name().to_owned())
+                .collect();
+
+            let picker = Picker::new(commands, (keymap, unsupported), move |cx, command, _action| {
                 let mut ctx = Context {
                     register,
                     count,
@@ -3456,6 +3594,12 @@ fn open_above(cx: &mut Context) {
 }
 
 fn normal_mode(cx: &mut Context) {
+    // Escape discards a command parked on a busy language server
+    // (`lsp.server-not-ready = "wait"`)
+    if let Some(pending) = cx.editor.pending_lsp_command.take() {
+        cx.editor
+            .set_status(format!("Cancelled pending {}", pending.command));
+    }
     cx.editor.enter_normal_mode();
 }
 
@@ -1,13 +1,17 @@
-use futures_util::{stream::FuturesOrdered, FutureExt};
+use futures_util::{future::BoxFuture, stream::FuturesOrdered, FutureExt};
 use helix_lsp::{
     block_on,
     lsp::{
         self, CodeAction, CodeActionOrCommand, CodeActionTriggerKind, DiagnosticSeverity,
         NumberOrString,
     },
-    util::{diagnostic_to_lsp_diagnostic, lsp_range_to_range, range_to_lsp_range},
+    util::{
+        diagnostic_to_lsp_diagnostic, lsp_pos_to_pos, lsp_range_to_range, normalize_uri,
+        pos_to_lsp_pos, range_to_lsp_range,
+    },
     Client, LanguageServerId, OffsetEncoding,
 };
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use tokio_stream::StreamExt;
 use tui::{
     text::{Span, Spans},
@@ -16,28 +20,43 @@ use tui::{
 
 use super::{align_view, push_jump, Align, Context, Editor};
 
-use helix_core::{syntax::LanguageServerFeature, text_annotations::InlineAnnotation, Selection};
+use helix_core::{
+    find_workspace, movement::Direction, syntax::LanguageServerFeature,
+    text_annotations::InlineAnnotation, Range, Selection, Transaction,
+};
 use helix_stdx::path;
 use helix_view::{
-    document::{DocumentInlayHints, DocumentInlayHintsId},
-    editor::Action,
+    document::{DocumentInlayHints, DocumentInlayHintsId, SCRATCH_BUFFER_NAME},
+    editor::{
+        Action, AnnotationPriority, CachedCodeActions, CachedDocumentHighlights, CodeActionAnchor,
+        CodeActionPin, CodeActionSort, ConfigEvent, LspFallback, LspJump, PendingLspCommand,
+        ReferencesView,
+        ServerNotReady,
+    },
     handlers::lsp::SignatureHelpInvoked,
-    theme::Style,
-    Document, View,
+    lsp_position,
+    theme::{Modifier, Style},
+    Document, DocumentId, View, ViewId,
 };
 
 use crate::{
+    alt,
     compositor::{self, Compositor},
     job::Callback,
     ui::{self, overlay::overlaid, DynamicPicker, FileLocation, Picker, Popup, PromptEvent},
 };
 
 use std::{
+    borrow::Cow,
     cmp::Ordering,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Write,
     future::Future,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc,
+    },
 };
 
 /// Gets the first language server that is attached to a document which supports a specific feature.
@@ -62,43 +81,134 @@ macro_rules! language_server_with_feature {
     }};
 }
 
+/// Handles a failed request if the failure means the server exited (it closed
+/// the stream): the first such failure shows "<name> exited unexpectedly" and
+/// marks the server as crashed, so pending sibling requests stay quiet and
+/// feature queries skip the server until it is restarted. Returns `false` for
+/// ordinary request errors, which remain the caller's to report.
+fn handle_server_exit(
+    editor: &mut Editor,
+    language_server_id: LanguageServerId,
+    err: &helix_lsp::Error,
+) -> bool {
+    if !err.is_server_exit() {
+        return false;
+    }
+    let crashed_server = editor
+        .language_server_by_id(language_server_id)
+        .map(|ls| (ls.mark_crashed(), ls.name().to_string()));
+    if let Some((true, name)) = crashed_server {
+        log::error!("language server {name} exited during a request");
+        editor.set_error(format!("{name} exited unexpectedly (see :log)"));
+    }
+    true
+}
+
+/// Demotes one server's failed `request` to the log so that the surviving
+/// servers' results still show — an editor-wide error status would bury them.
+/// Server-exit cleanup ([`handle_server_exit`]) still applies. Returns the
+/// error message for the caller to surface if every server ends up failing.
+async fn discard_failed_server(
+    language_server_id: LanguageServerId,
+    err: helix_lsp::Error,
+    request: &'static str,
+) -> String {
+    let message = err.to_string();
+    log::warn!("{request} request failed, keeping the other servers' results: {err}");
+    crate::job::dispatch(move |editor, _| {
+        handle_server_exit(editor, language_server_id, &err);
+    })
+    .await;
+    message
+}
+
+fn format_location(location: &lsp::Location, cwdir: &Path) -> String {
+    // The preallocation here will overallocate a few characters since it will account for the
+    // URL's scheme, which is not used most of the time since that scheme will be "file://".
+    // Those extra chars will be used to avoid allocating when writing the line number (in the
+    // common case where it has 5 digits or less, which should be enough for a cast majority
+    // of usages).
+    let mut res = String::with_capacity(location.uri.as_str().len());
+
+    if location.uri.scheme() == "file" {
+        // `display_path` takes care of stripping the cwd prefix (and, on Windows,
+        // the verbatim prefix `to_file_path` may produce). Jumping still uses the
+        // original, un-normalized URI.
+        let mut write_path_to_res = || -> Option<()> {
+            let path = location.uri.to_file_path().ok()?;
+            let path = path.strip_prefix(cwdir).unwrap_or(&path);
+            res.push_str(&path::display_path(path));
+            Some(())
+        };
+        write_path_to_res();
+    } else {
+        // Never allocates since we declared the string with this capacity already.
+        res.push_str(location.uri.as_str());
+    }
+
+    // Most commonly, this will not allocate, especially on Unix systems where the root prefix
+    // is a simple `/` and not `C:\` (with whatever drive letter)
+    write!(&mut res, ":{}", location.range.start.line + 1)
+        .expect("Will only failed if allocating fail");
+    res
+}
+
 impl ui::menu::Item for lsp::Location {
     /// Current working directory.
     type Data = PathBuf;
 
     fn format(&self, cwdir: &Self::Data) -> Row {
-        // The preallocation here will overallocate a few characters since it will account for the
-        // URL's scheme, which is not used most of the time since that scheme will be "file://".
-        // Those extra chars will be used to avoid allocating when writing the line number (in the
-        // common case where it has 5 digits or less, which should be enough for a cast majority
-        // of usages).
-        let mut res = String::with_capacity(self.uri.as_str().len());
-
-        if self.uri.scheme() == "file" {
-            // With the preallocation above and UTF-8 paths already, this closure will do one (1)
-            // allocation, for `to_file_path`, else there will be two (2), with `to_string_lossy`.
-            let mut write_path_to_res = || -> Option<()> {
-                let path = self.uri.to_file_path().ok()?;
-                res.push_str(&path.strip_prefix(cwdir).unwrap_or(&path).to_string_lossy());
-                Some(())
-            };
-            write_path_to_res();
-        } else {
-            // Never allocates since we declared the string with this capacity already.
-            res.push_str(self.uri.as_str());
-        }
+        format_location(self, cwdir).into()
+    }
+}
+
+/// A goto target together with the server that reported it. When several
+/// servers answer the same request the results are merged, so every location
+/// keeps its own origin: servers may use different offset encodings, and a
+/// virtual document request must go back to the server that produced the uri.
+#[derive(Clone)]
+struct GotoLocationItem {
+    location: lsp::Location,
+    language_server_id: LanguageServerId,
+    offset_encoding: OffsetEncoding,
+    /// Names of every server that returned this location, in response order.
+    servers: Vec<String>,
+}
 
-        // Most commonly, this will not allocate, especially on Unix systems where the root prefix
-        // is a simple `/` and not `C:\` (with whatever drive letter)
-        write!(&mut res, ":{}", self.range.start.line + 1)
-            .expect("Will only failed if allocating fail");
+impl ui::menu::Item for GotoLocationItem {
+    /// Current working directory, plus whether to label each entry with the
+    /// servers that reported it (only done when the servers disagreed).
+    type Data = (PathBuf, bool);
+
+    fn format(&self, (cwdir, label_servers): &Self::Data) -> Row {
+        let mut res = format_location(&self.location, cwdir);
+        if *label_servers && !self.servers.is_empty() {
+            write!(&mut res, " [{}]", self.servers.join(","))
+                .expect("Will only failed if allocating fail");
+        }
         res.into()
     }
 }
 
+#[derive(Clone)]
 struct SymbolInformationItem {
     symbol: lsp::SymbolInformation,
     offset_encoding: OffsetEncoding,
+    /// Set for streamed workspace symbols whose server supports
+    /// `workspaceSymbol/resolve`: the container is filled in lazily while the
+    /// item is highlighted instead of weighing down the initial response.
+    lazy: Option<LazyWorkspaceSymbol>,
+}
+
+#[derive(Clone)]
+struct LazyWorkspaceSymbol {
+    language_server_id: LanguageServerId,
+    symbol: lsp::WorkspaceSymbol,
+    /// Shared between the clones nucleo hands out, so filling it once the
+    /// resolve request answers updates the highlighted row in place.
+    resolved: Arc<std::sync::OnceLock<lsp::WorkspaceSymbol>>,
+    /// Keeps the idle timer from re-requesting a resolve that is in flight.
+    requested: Arc<AtomicBool>,
 }
 
 impl ui::menu::Item for SymbolInformationItem {
@@ -106,42 +216,89 @@ impl ui::menu::Item for SymbolInformationItem {
     type Data = Option<lsp::Url>;
 
     fn format(&self, current_doc_path: &Self::Data) -> Row {
-        if current_doc_path.as_ref() == Some(&self.symbol.location.uri) {
-            self.symbol.name.as_str().into()
+        let mut name = self.symbol.name.clone();
+        // the container prefix also participates in fuzzy filtering, so
+        // `Foo new` finds `Foo::new` among several methods named `new`
+        let container = self
+            .lazy
+            .as_ref()
+            .and_then(|lazy| lazy.resolved.get())
+            .and_then(|resolved| resolved.container_name.as_deref())
+            .or(self.symbol.container_name.as_deref());
+        if let Some(container) = container.filter(|container| !container.is_empty()) {
+            name = format!("{container}::{name}");
+        }
+        let label = if current_doc_path.as_ref() == Some(&self.symbol.location.uri) {
+            name
         } else {
             match self.symbol.location.uri.to_file_path() {
-                Ok(path) => {
-                    let get_relative_path = path::get_relative_path(path.as_path());
-                    format!(
-                        "{} ({})",
-                        &self.symbol.name,
-                        get_relative_path.to_string_lossy()
-                    )
-                    .into()
-                }
-                Err(_) => format!("{} ({})", &self.symbol.name, &self.symbol.location.uri).into(),
+                Ok(path) => format!("{} ({})", name, path::display_path(&path)),
+                Err(_) => format!("{} ({})", name, &self.symbol.location.uri),
             }
+        };
+        if self.is_deprecated() {
+            // consistent treatment of deprecated symbols in the document and
+            // workspace symbol pickers
+            return Span::styled(
+                label,
+                Style::default().add_modifier(Modifier::DIM | Modifier::CROSSED_OUT),
+            )
+            .into();
         }
+        label.into()
     }
 }
 
-struct DiagnosticStyles {
+impl SymbolInformationItem {
+    fn is_deprecated(&self) -> bool {
+        #[allow(deprecated)]
+        let deprecated_field = self.symbol.deprecated.unwrap_or(false);
+        deprecated_field
+            || self
+                .symbol
+                .tags
+                .as_ref()
+                .is_some_and(|tags| tags.contains(&lsp::SymbolTag::DEPRECATED))
+    }
+}
+
+/// Renders an out-of-spec LSP enum value (e.g. an unknown `SymbolKind`) as its raw
+/// numeric value, like `kind(27)`. The inner value is private in `lsp-types` but all
+/// of these enums serialize transparently to a number.
+fn unknown_lsp_enum(label: &str, value: impl serde::Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(raw) => format!("{label}({raw})"),
+        Err(_) => format!("{label}(?)"),
+    }
+}
+
+pub(crate) struct DiagnosticStyles {
     hint: Style,
     info: Style,
     warning: Style,
     error: Style,
 }
 
-struct PickerDiagnostic {
+#[derive(Clone)]
+pub(crate) struct PickerDiagnostic {
     path: PathBuf,
     diag: lsp::Diagnostic,
     offset_encoding: OffsetEncoding,
+    /// The file was reloaded from disk after this diagnostic was published,
+    /// so its position may no longer match the text (see
+    /// [`Editor::stale_diagnostic_paths`]).
+    stale: bool,
+    /// Pointer to the first related location, shown after the message when
+    /// `lsp.diagnostic-related-suffix` is enabled (see
+    /// [`Document::related_location_suffix`]).
+    related_suffix: Option<String>,
 }
 
 impl ui::menu::Item for PickerDiagnostic {
     type Data = (DiagnosticStyles, DiagnosticsFormat);
 
     fn format(&self, (styles, format): &Self::Data) -> Row {
+        let mut unknown_severity = String::new();
         let mut style = self
             .diag
             .severity
@@ -150,12 +307,20 @@ impl ui::menu::Item for PickerDiagnostic {
                 DiagnosticSeverity::INFORMATION => styles.info,
                 DiagnosticSeverity::WARNING => styles.warning,
                 DiagnosticSeverity::ERROR => styles.error,
-                _ => Style::default(),
+                s => {
+                    // out-of-spec severity: it gets no style, so call out the raw
+                    // value instead of leaving the entry indistinguishable
+                    unknown_severity = format!(" [{}]", unknown_lsp_enum("severity", s));
+                    Style::default()
+                }
             })
             .unwrap_or_default();
 
         // remove background as it is distracting in the picker list
         style.bg = None;
+        if self.stale {
+            style = style.add_modifier(Modifier::DIM);
+        }
 
         let code = match self.diag.code.as_ref() {
             Some(NumberOrString::Number(n)) => format!(" ({n})"),
@@ -166,38 +331,354 @@ impl ui::menu::Item for PickerDiagnostic {
         let path = match format {
             DiagnosticsFormat::HideSourcePath => String::new(),
             DiagnosticsFormat::ShowSourcePath => {
-                let path = path::get_truncated_path(&self.path);
+                let path = path::get_truncated_path(path::simplified(&self.path));
                 format!("{}: ", path.to_string_lossy())
             }
         };
 
+        let stale = if self.stale { " (stale)" } else { "" };
+
         Spans::from(vec![
             Span::raw(path),
             Span::styled(&self.diag.message, style),
+            Span::styled(self.related_suffix.as_deref().unwrap_or(""), style),
             Span::styled(code, style),
+            Span::styled(stale, style),
+            Span::raw(unknown_severity),
         ])
         .into()
     }
 }
 
-fn location_to_file_location(location: &lsp::Location) -> FileLocation {
-    let path = location.uri.to_file_path().unwrap();
+fn location_to_file_location(location: &lsp::Location) -> Option<FileLocation> {
+    // virtual documents (e.g. `jdt://` URIs) have no previewable path
+    let path = location.uri.to_file_path().ok()?;
     let line = Some((
         location.range.start.line as usize,
         location.range.end.line as usize,
     ));
-    (path.into(), line)
+    Some((path.into(), line))
+}
+
+/// Custom "read content" requests used to materialize read-only documents for
+/// non-`file://` URI schemes served by some language servers.
+fn virtual_document_request(scheme: &str) -> Option<&'static str> {
+    match scheme {
+        // jdtls and the kotlin language server serve sources for library
+        // classes through these extension methods
+        "jdt" => Some("java/classFileContents"),
+        "kls" => Some("kotlin/jarClassContents"),
+        _ => None,
+    }
+}
+
+/// Opens the contents behind a non-`file://` location (e.g. a class inside a jar)
+/// as a read-only scratch buffer by asking the language server for the content
+/// through `method` (see [virtual_document_request]).
+fn jump_to_virtual_location(
+    editor: &mut Editor,
+    language_server_id: LanguageServerId,
+    method: &'static str,
+    location: &lsp::Location,
+    offset_encoding: OffsetEncoding,
+    action: Action,
+) {
+    let Some(language_server) = editor.language_server_by_id(language_server_id) else {
+        editor.set_error("Language Server disappeared");
+        return;
+    };
+    let request = language_server.custom_request(
+        method,
+        serde_json::json!({ "uri": location.uri.to_string() }),
+    );
+
+    let contents = match block_on(request)
+        .and_then(|json| serde_json::from_value::<Option<String>>(json).map_err(Into::into))
+    {
+        Ok(Some(contents)) => contents,
+        Ok(None) => {
+            editor.set_error(format!("No content for {}", location.uri));
+            return;
+        }
+        Err(err) => {
+            editor.set_error(format!("{method} failed: {err}"));
+            return;
+        }
+    };
+
+    let (view, doc) = current!(editor);
+    push_jump(view, doc);
+
+    open_readonly_scratch(editor, action, contents);
+
+    let (view, doc) = current!(editor);
+    if let Some(range) = lsp_range_to_range(doc.text(), location.range, offset_encoding) {
+        // we flip the range so that the cursor sits on the start of the symbol
+        doc.set_selection(view.id, Selection::single(range.head, range.anchor));
+        align_view(doc, view, Align::Center);
+    }
+}
+
+/// Opens `contents` in a fresh read-only scratch buffer.
+fn open_readonly_scratch(editor: &mut Editor, action: Action, contents: String) {
+    let doc_id = editor.new_file(action);
+    let doc = doc_mut!(editor, &doc_id);
+    let view = view_mut!(editor);
+    doc.ensure_view_init(view.id);
+    let transaction =
+        helix_core::Transaction::insert(doc.text(), doc.selection(view.id), contents.into())
+            .with_selection(Selection::point(0));
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    // the buffer has no backing file and there's nothing sensible to write back
+    doc.readonly = true;
+}
+
+/// What a [CustomRequest] sends as params, built from the focused document.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CustomRequestParams {
+    /// No parameters.
+    None,
+    /// `{ "textDocument": { "uri": … } }`
+    TextDocument,
+    /// The text document plus the primary cursor position.
+    TextDocumentPosition,
+}
+
+/// How the response of a [CustomRequest] is presented. A response that
+/// doesn't have the declared shape falls back to a pretty-printed JSON popup
+/// so it is still inspectable.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CustomRequestResponse {
+    /// Jump to a `Location`, to one of a `Location[]` through the goto
+    /// picker, or to a bare URI string.
+    Locations,
+    /// Open a string response as a read-only scratch buffer.
+    ScratchBuffer,
+    /// Show a string response in a popup.
+    Popup,
+}
+
+/// A server-specific extension request, described declaratively so that a
+/// new extension is a table entry instead of a hand-rolled command. Entries
+/// are exposed through the `:lsp-request` typable command.
+pub(crate) struct CustomRequest {
+    /// Name the request is invoked with, e.g. `:lsp-request expand-macro`.
+    pub name: &'static str,
+    /// The server the method belongs to; other servers are never queried.
+    pub server: &'static str,
+    /// The method sent over the wire.
+    pub method: &'static str,
+    pub params: CustomRequestParams,
+    /// JSON pointer applied to the raw response before interpretation, e.g.
+    /// `/expansion` pulls the text out of a `rust-analyzer/expandMacro`
+    /// response.
+    pub pointer: Option<&'static str>,
+    pub response: CustomRequestResponse,
+}
+
+/// The built-in extension requests.
+pub(crate) const CUSTOM_REQUESTS: &[CustomRequest] = &[
+    CustomRequest {
+        name: "expand-macro",
+        server: "rust-analyzer",
+        method: "rust-analyzer/expandMacro",
+        params: CustomRequestParams::TextDocumentPosition,
+        pointer: Some("/expansion"),
+        response: CustomRequestResponse::ScratchBuffer,
+    },
+    CustomRequest {
+        name: "reload-workspace",
+        server: "rust-analyzer",
+        method: "rust-analyzer/reloadWorkspace",
+        params: CustomRequestParams::None,
+        pointer: None,
+        response: CustomRequestResponse::Popup,
+    },
+    CustomRequest {
+        name: "switch-source-header",
+        server: "clangd",
+        method: "textDocument/switchSourceHeader",
+        params: CustomRequestParams::TextDocument,
+        pointer: None,
+        response: CustomRequestResponse::Locations,
+    },
+];
+
+/// Sends `request` to its server for the focused document and routes the
+/// response according to the declared response mode.
+pub(crate) fn custom_request(cx: &mut compositor::Context, request: &'static CustomRequest) {
+    let (view, doc) = current_ref!(cx.editor);
+    let Some(language_server) = doc
+        .language_servers()
+        .find(|ls| ls.name() == request.server)
+    else {
+        cx.editor.set_status(format!(
+            "{} is not attached to this document",
+            request.server
+        ));
+        return;
+    };
+    let offset_encoding = language_server.offset_encoding();
+    let language_server_id = language_server.id();
+
+    let params = match request.params {
+        CustomRequestParams::None => serde_json::Value::Null,
+        CustomRequestParams::TextDocument => serde_json::json!({
+            "textDocument": doc.identifier(),
+        }),
+        CustomRequestParams::TextDocumentPosition => serde_json::json!({
+            "textDocument": doc.identifier(),
+            "position": doc.position(view.id, offset_encoding),
+        }),
+    };
+
+    let future = language_server.custom_request(request.method, params);
+    cx.jobs.callback(async move {
+        let response = match future.await {
+            Ok(response) => response,
+            Err(err) => {
+                let call = move |editor: &mut Editor, _compositor: &mut Compositor| {
+                    if !handle_server_exit(editor, language_server_id, &err) {
+                        editor.set_error(format!("{} failed: {err}", request.method));
+                    }
+                };
+                return Ok(Callback::EditorCompositor(Box::new(call)));
+            }
+        };
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            custom_request_response(
+                editor,
+                compositor,
+                request,
+                language_server_id,
+                offset_encoding,
+                response,
+            );
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+fn custom_request_response(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    request: &'static CustomRequest,
+    language_server_id: LanguageServerId,
+    offset_encoding: OffsetEncoding,
+    response: serde_json::Value,
+) {
+    let value = match request.pointer {
+        Some(pointer) => match response.pointer(pointer) {
+            Some(value) => value.clone(),
+            None => return custom_request_fallback(editor, compositor, &response),
+        },
+        None => response,
+    };
+
+    // `null` is how servers signal "nothing to do" for most extensions, e.g.
+    // a header without a matching source for `switch-source-header`
+    if value.is_null() {
+        editor.set_status(format!("{} returned no results", request.name));
+        return;
+    }
+
+    match request.response {
+        CustomRequestResponse::Locations => {
+            if let Ok(location) = serde_json::from_value::<lsp::Location>(value.clone()) {
+                jump_to_location(editor, &location, offset_encoding, Action::Replace, request.name);
+            } else if let Ok(locations) = serde_json::from_value::<Vec<lsp::Location>>(value.clone())
+            {
+                let items = locations
+                    .into_iter()
+                    .map(|location| GotoLocationItem {
+                        location,
+                        language_server_id,
+                        offset_encoding,
+                        servers: vec![request.server.to_string()],
+                    })
+                    .collect::<Vec<_>>();
+                if items.is_empty() {
+                    editor.set_status(format!("{} returned no results", request.name));
+                } else {
+                    goto_impl(editor, compositor, items, request.name);
+                }
+            } else if let Ok(uri) = serde_json::from_value::<lsp::Url>(value.clone()) {
+                let location = lsp::Location::new(uri, lsp::Range::default());
+                jump_to_location(editor, &location, offset_encoding, Action::Replace, request.name);
+            } else {
+                custom_request_fallback(editor, compositor, &value);
+            }
+        }
+        CustomRequestResponse::ScratchBuffer => match value.as_str() {
+            Some(contents) => {
+                let (view, doc) = current!(editor);
+                push_jump(view, doc);
+                open_readonly_scratch(editor, Action::Replace, contents.to_string());
+            }
+            None => custom_request_fallback(editor, compositor, &value),
+        },
+        CustomRequestResponse::Popup => match value.as_str() {
+            Some(contents) => {
+                let contents = ui::Markdown::new(contents.to_string(), editor.syn_loader.clone());
+                let popup = Popup::new("lsp-request", contents).auto_close(true);
+                compositor.replace_or_push("lsp-request", popup);
+            }
+            None => custom_request_fallback(editor, compositor, &value),
+        },
+    }
+}
+
+/// Shows a response of unknown shape as pretty-printed JSON, so a
+/// misdeclared entry is still useful for inspecting what the server sent.
+fn custom_request_fallback(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    value: &serde_json::Value,
+) {
+    let json = serde_json::to_string_pretty(value).unwrap_or_default();
+    let contents = ui::Markdown::new(format!("```json\n{json}\n```"), editor.syn_loader.clone());
+    let popup = Popup::new("lsp-request", contents).auto_close(true);
+    compositor.replace_or_push("lsp-request", popup);
 }
 
-fn jump_to_location(
+pub(crate) fn jump_to_location(
     editor: &mut Editor,
     location: &lsp::Location,
     offset_encoding: OffsetEncoding,
     action: Action,
+    command: &'static str,
 ) {
     let (view, doc) = current!(editor);
     push_jump(view, doc);
+    jump_to_location_without_jumplist(editor, location, offset_encoding, action, command);
+}
+
+/// [jump_to_location] for a location confirmed in a picker: when
+/// `lsp.picker-jumps-in-jumplist` is off the origin is not pushed onto the
+/// jumplist, so browsing through many entries doesn't bury the position the
+/// picker was opened from.
+fn jump_to_location_from_picker(
+    editor: &mut Editor,
+    location: &lsp::Location,
+    offset_encoding: OffsetEncoding,
+    action: Action,
+    command: &'static str,
+) {
+    if editor.config().lsp.picker_jumps_in_jumplist {
+        let (view, doc) = current!(editor);
+        push_jump(view, doc);
+    }
+    jump_to_location_without_jumplist(editor, location, offset_encoding, action, command);
+}
 
+fn jump_to_location_without_jumplist(
+    editor: &mut Editor,
+    location: &lsp::Location,
+    offset_encoding: OffsetEncoding,
+    action: Action,
+    command: &'static str,
+) {
     let path = match location.uri.to_file_path() {
         Ok(path) => path,
         Err(_) => {
@@ -206,7 +687,7 @@ fn jump_to_location(
             return;
         }
     };
-    jump_to_position(editor, &path, location.range, offset_encoding, action);
+    jump_to_position(editor, &path, location.range, offset_encoding, action, command);
 }
 
 fn jump_to_position(
@@ -215,7 +696,13 @@ fn jump_to_position(
     range: lsp::Range,
     offset_encoding: OffsetEncoding,
     action: Action,
+    command: &'static str,
 ) {
+    let from = {
+        let (view, doc) = current_ref!(editor);
+        let cursor = doc.selection(view.id).primary().cursor(doc.text().slice(..));
+        (doc.id(), cursor)
+    };
     let doc = match editor.open(path, action) {
         Ok(id) => doc_mut!(editor, &id),
         Err(err) => {
@@ -224,6 +711,11 @@ fn jump_to_position(
             return;
         }
     };
+    // The jump targets exactly the view the `Action` chose: the focused view
+    // (for the split actions, the one just created and focused). When the same
+    // document is visible in other splits their selections and scroll
+    // deliberately stay put — a picker jump must not move the cursor in a view
+    // the user wasn't looking at.
     let view = view_mut!(editor);
     // TODO: convert inside server
     let new_range = if let Some(new_range) = lsp_range_to_range(doc.text(), range, offset_encoding)
@@ -236,61 +728,289 @@ fn jump_to_position(
     // we flip the range so that the cursor sits on the start of the symbol
     // (for example start of the function).
     doc.set_selection(view.id, Selection::single(new_range.head, new_range.anchor));
-    if action.align_view(view, doc.id()) {
+    let to = (doc.id(), new_range.anchor);
+    let doc_id = doc.id();
+    if action.align_view(view, doc_id) {
         align_view(doc, view, Align::Center);
     }
+    // otherwise (`Action::Load` into a view showing another document) the
+    // selection set above is centered by `Editor::replace_document_in_view`
+    // once the buffer is displayed in this view
+    editor.record_lsp_jump(LspJump { from, to, command });
 }
 
 type SymbolPicker = Picker<SymbolInformationItem>;
 
-fn sym_picker(symbols: Vec<SymbolInformationItem>, current_path: Option<lsp::Url>) -> SymbolPicker {
+fn sym_picker(
+    editor: &Editor,
+    symbols: Vec<SymbolInformationItem>,
+    current_path: Option<lsp::Url>,
+    command: &'static str,
+) -> SymbolPicker {
     // TODO: drop current_path comparison and instead use workspace: bool flag?
     Picker::new(symbols, current_path, move |cx, item, action| {
-        jump_to_location(
+        jump_to_location_from_picker(
             cx.editor,
             &item.symbol.location,
             item.offset_encoding,
             action,
+            command,
+        );
+    })
+    .with_preview(move |_editor, item| location_to_file_location(&item.symbol.location))
+    .with_alternate_action(move |cx, item| {
+        rename_symbol_at_location(
+            cx.editor,
+            &item.symbol.location,
+            item.offset_encoding,
+            item.symbol.name.clone(),
         );
     })
-    .with_preview(move |_editor, item| Some(location_to_file_location(&item.symbol.location)))
+    .with_raw_json(|item| serde_json::to_string_pretty(&item.symbol).ok())
+    .with_doc_preview(|cx, item| {
+        preview_symbol_docs(cx, &item.symbol.location, item.offset_encoding);
+    })
+    .with_confirm_action(editor.config().lsp.picker_actions.symbol)
     .truncate_start(false)
 }
 
+/// A shared predicate deciding whether a symbol is hidden by default in the
+/// symbol pickers, see [private_symbol_filter].
+type SymbolFilter = Arc<dyn Fn(&lsp::SymbolInformation) -> bool + Send + Sync>;
+
+/// The private-symbol heuristic of a document's language, `None` unless the
+/// language sets `hide-private-symbols`. The returned closure reports whether
+/// a symbol looks private: a match of the language's `private-symbol-regex`
+/// when one is configured, a leading underscore in the name otherwise.
+fn private_symbol_filter(doc: &Document) -> Option<SymbolFilter> {
+    let config = doc.language_config()?;
+    if !config.hide_private_symbols {
+        return None;
+    }
+    let regex = config.private_symbol_regex.clone();
+    Some(Arc::new(move |symbol: &lsp::SymbolInformation| {
+        match &regex {
+            Some(regex) => regex.is_match(&symbol.name),
+            None => symbol.name.starts_with('_'),
+        }
+    }))
+}
+
+/// Requests hover at the start of `location` and shows the result in a popup
+/// without jumping, so the symbol pickers can be used to skim documentation.
+/// Loads the file in the background if it isn't open yet so its language
+/// servers can serve the request.
+fn preview_symbol_docs(
+    cx: &mut compositor::Context,
+    location: &lsp::Location,
+    offset_encoding: OffsetEncoding,
+) {
+    let path = match location.uri.to_file_path() {
+        Ok(path) => path,
+        Err(_) => {
+            cx.editor
+                .set_error("Symbol documentation is only available for file uris");
+            return;
+        }
+    };
+    let doc_id = match cx.editor.open(&path, Action::Load) {
+        Ok(id) => id,
+        Err(err) => {
+            cx.editor
+                .set_error(format!("failed to open path: {:?}: {:?}", path, err));
+            return;
+        }
+    };
+    let doc = doc!(cx.editor, &doc_id);
+    let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::Hover)
+        .next()
+    else {
+        cx.editor
+            .set_status("No configured language server supports hover for this symbol");
+        return;
+    };
+    let language_server_id = language_server.id();
+    // the symbol position is in the coordinates of the server that reported
+    // it, which may not match the hover server's offset encoding
+    let Some(pos) = lsp_pos_to_pos(doc.text(), location.range.start, offset_encoding) else {
+        return;
+    };
+    let pos = pos_to_lsp_pos(doc.text(), pos, language_server.offset_encoding());
+    let Some(future) = language_server.text_document_hover(doc.identifier(), pos, None) else {
+        return;
+    };
+
+    cx.jobs.callback(async move {
+        let response: Option<lsp::Hover> = match future.await {
+            Ok(json) => serde_json::from_value(json)?,
+            Err(err) => {
+                let call = move |editor: &mut Editor, _compositor: &mut Compositor| {
+                    if !handle_server_exit(editor, language_server_id, &err) {
+                        editor.set_error(err.to_string());
+                    }
+                };
+                return Ok(Callback::EditorCompositor(Box::new(call)));
+            }
+        };
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            let contents = response
+                .map(|hover| hover_contents_to_markdown(hover.contents))
+                .unwrap_or_default();
+            if contents.is_empty() {
+                editor.set_status("No documentation available for this symbol");
+                return;
+            }
+            let contents = ui::Markdown::new(contents, editor.syn_loader.clone());
+            let popup = Popup::new("symbol-docs", contents).auto_close(true);
+            compositor.replace_or_push("symbol-docs", popup);
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
 #[derive(Copy, Clone, PartialEq)]
-enum DiagnosticsFormat {
+pub(crate) enum DiagnosticsFormat {
     ShowSourcePath,
     HideSourcePath,
 }
 
-fn diag_picker(
-    cx: &Context,
-    diagnostics: BTreeMap<PathBuf, Vec<(lsp::Diagnostic, LanguageServerId)>>,
-    format: DiagnosticsFormat,
-) -> Picker<PickerDiagnostic> {
-    // TODO: drop current_path comparison and instead use workspace: bool flag?
+/// Merges diagnostics that different language servers published for the same
+/// issue -- identical range and code, and a whitespace-normalized identical
+/// message -- into the first entry, whose `source` then lists every server
+/// that reported it, e.g. "ruff,pylsp". Enabled by
+/// `lsp.deduplicate-diagnostics`; differing ranges or codes never merge.
+/// Hides workspace-wide picker results (symbols, diagnostics) that live in
+/// ignored files, roughly matching what the file picker skips. The workspace's
+/// `.gitignore` and `.ignore` apply according to the `file-picker` config;
+/// the globs in `lsp.workspace-excludes` apply on top. `A-i` in the picker
+/// temporarily reveals the hidden results.
+struct WorkspaceFilter {
+    ignore: Gitignore,
+}
+
+impl WorkspaceFilter {
+    fn new(editor: &Editor) -> Self {
+        let config = editor.config();
+        let root = find_workspace().0;
+        let mut builder = GitignoreBuilder::new(&root);
+        if config.file_picker.ignore {
+            builder.add(root.join(".ignore"));
+        }
+        if config.file_picker.git_ignore {
+            builder.add(root.join(".gitignore"));
+        }
+        for glob in &config.lsp.workspace_excludes {
+            if let Err(err) = builder.add_line(None, glob) {
+                log::error!("Invalid lsp.workspace-excludes glob {glob:?}: {err}");
+            }
+        }
+        let ignore = builder.build().unwrap_or_else(|err| {
+            log::error!("Failed to build the workspace exclude matcher: {err}");
+            Gitignore::empty()
+        });
+        Self { ignore }
+    }
+
+    fn is_hidden(&self, path: &Path) -> bool {
+        self.ignore
+            .matched_path_or_any_parents(path, false)
+            .is_ignore()
+    }
+
+    fn is_hidden_uri(&self, uri: &lsp::Url) -> bool {
+        // non-file uris can't be matched against ignore files; leave them visible
+        uri.to_file_path()
+            .is_ok_and(|path| self.is_hidden(&path))
+    }
+}
+
+fn deduplicate_diagnostics(diags: &mut Vec<(lsp::Diagnostic, LanguageServerId)>) {
+    fn normalized(message: &str) -> String {
+        message.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    let mut deduped: Vec<(lsp::Diagnostic, LanguageServerId)> = Vec::with_capacity(diags.len());
+    for (diag, ls) in diags.drain(..) {
+        let duplicate = deduped.iter_mut().find(|(kept, kept_ls)| {
+            *kept_ls != ls
+                && kept.range == diag.range
+                && kept.code == diag.code
+                && normalized(&kept.message) == normalized(&diag.message)
+        });
+        match duplicate {
+            Some((kept, _)) => {
+                if let Some(source) = &diag.source {
+                    match &mut kept.source {
+                        Some(merged) if !merged.split(',').any(|s| s == source) => {
+                            merged.push(',');
+                            merged.push_str(source);
+                        }
+                        None => kept.source = Some(source.clone()),
+                        _ => (),
+                    }
+                }
+            }
+            None => deduped.push((diag, ls)),
+        }
+    }
+    *diags = deduped;
+}
 
-    // flatten the map to a vec of (url, diag) pairs
+/// Flattens the per-file diagnostics map to a vec of picker entries, applying
+/// the `lsp.deduplicate-diagnostics` merge pass per file.
+fn flatten_diagnostics(
+    editor: &Editor,
+    diagnostics: BTreeMap<PathBuf, Vec<(lsp::Diagnostic, LanguageServerId)>>,
+) -> Vec<PickerDiagnostic> {
     let mut flat_diag = Vec::new();
-    for (path, diags) in diagnostics {
+    for (path, mut diags) in diagnostics {
+        if editor.config().lsp.deduplicate_diagnostics {
+            deduplicate_diagnostics(&mut diags);
+        }
         flat_diag.reserve(diags.len());
+        let stale = editor.stale_diagnostic_paths.contains(&path);
 
         for (diag, ls) in diags {
-            if let Some(ls) = cx.editor.language_server_by_id(ls) {
+            if let Some(ls) = editor.language_server_by_id(ls) {
+                let related_suffix = editor
+                    .config()
+                    .lsp
+                    .diagnostic_related_suffix
+                    .then(|| Document::related_location_suffix(&diag))
+                    .flatten();
                 flat_diag.push(PickerDiagnostic {
                     path: path.clone(),
                     diag,
                     offset_encoding: ls.offset_encoding(),
+                    stale,
+                    related_suffix,
                 });
             }
         }
     }
+    flat_diag
+}
+
+fn diag_picker(
+    editor: &Editor,
+    flat_diag: Vec<PickerDiagnostic>,
+    format: DiagnosticsFormat,
+    command: &'static str,
+) -> Picker<PickerDiagnostic> {
+    // TODO: drop current_path comparison and instead use workspace: bool flag?
 
     let styles = DiagnosticStyles {
-        hint: cx.editor.theme.get("hint"),
-        info: cx.editor.theme.get("info"),
-        warning: cx.editor.theme.get("warning"),
-        error: cx.editor.theme.get("error"),
+        hint: editor.theme.get("hint"),
+        info: editor.theme.get("info"),
+        warning: editor.theme.get("warning"),
+        error: editor.theme.get("error"),
+    };
+
+    let picker_actions = editor.config().lsp.picker_actions;
+    let confirm_action = match command {
+        "workspace_diagnostics_picker" => picker_actions.workspace_diagnostics,
+        _ => picker_actions.diagnostics,
     };
 
     Picker::new(
@@ -301,85 +1021,139 @@ fn diag_picker(
                   path,
                   diag,
                   offset_encoding,
+                  stale,
+                  ..
               },
               action| {
-            jump_to_position(cx.editor, path, diag.range, *offset_encoding, action);
+            jump_to_position(cx.editor, path, diag.range, *offset_encoding, action, command);
             let (view, doc) = current!(cx.editor);
             view.diagnostics_handler
                 .immediately_show_diagnostic(doc, view.id);
+            if *stale {
+                cx.editor
+                    .set_status("The file changed on disk, this diagnostic may be out of date");
+            }
         },
     )
     .with_preview(move |_editor, PickerDiagnostic { path, diag, .. }| {
         let line = Some((diag.range.start.line as usize, diag.range.end.line as usize));
         Some((path.clone().into(), line))
     })
+    .with_raw_json(|item| serde_json::to_string_pretty(&item.diag).ok())
+    .with_detail(|item| (!item.diag.message.is_empty()).then(|| item.diag.message.clone()))
+    .with_confirm_action(confirm_action)
     .truncate_start(false)
 }
 
-pub fn symbol_picker(cx: &mut Context) {
-    fn nested_to_flat(
-        list: &mut Vec<SymbolInformationItem>,
-        file: &lsp::TextDocumentIdentifier,
-        symbol: lsp::DocumentSymbol,
-        offset_encoding: OffsetEncoding,
-    ) {
-        #[allow(deprecated)]
-        list.push(SymbolInformationItem {
-            symbol: lsp::SymbolInformation {
-                name: symbol.name,
-                kind: symbol.kind,
-                tags: symbol.tags,
-                deprecated: symbol.deprecated,
-                location: lsp::Location::new(file.uri.clone(), symbol.selection_range),
-                container_name: None,
-            },
-            offset_encoding,
-        });
-        for child in symbol.children.into_iter().flatten() {
-            nested_to_flat(list, file, child, offset_encoding);
-        }
+fn nested_to_flat(
+    list: &mut Vec<SymbolInformationItem>,
+    file: &lsp::TextDocumentIdentifier,
+    symbol: lsp::DocumentSymbol,
+    offset_encoding: OffsetEncoding,
+    container: Option<&str>,
+) {
+    #[allow(deprecated)]
+    list.push(SymbolInformationItem {
+        symbol: lsp::SymbolInformation {
+            name: symbol.name.clone(),
+            kind: symbol.kind,
+            tags: symbol.tags,
+            deprecated: symbol.deprecated,
+            location: lsp::Location::new(file.uri.clone(), symbol.selection_range),
+            // the parent symbol name, so that three methods all named `new`
+            // stay distinguishable (and fuzzy-matchable by `Foo new`)
+            container_name: container.map(str::to_owned),
+        },
+        offset_encoding,
+        lazy: None,
+    });
+    for child in symbol.children.into_iter().flatten() {
+        nested_to_flat(list, file, child, offset_encoding, Some(&symbol.name));
     }
-    let doc = doc!(cx.editor);
+}
 
+/// Builds the `textDocument/documentSymbol` request on every attached server
+/// that supports them, flattening the nested response variant; shared by
+/// [symbol_picker] and the `:goto-symbol` lookup ([goto_symbol_by_name]).
+fn document_symbol_requests(
+    doc: &Document,
+) -> FuturesOrdered<
+    impl Future<
+        Output = (
+            LanguageServerId,
+            Result<Vec<SymbolInformationItem>, helix_lsp::Error>,
+        ),
+    >,
+> {
     let mut seen_language_servers = HashSet::new();
 
-    let mut futures: FuturesOrdered<_> = doc
-        .language_servers_with_feature(LanguageServerFeature::DocumentSymbols)
+    doc.language_servers_with_feature(LanguageServerFeature::DocumentSymbols)
         .filter(|ls| seen_language_servers.insert(ls.id()))
         .map(|language_server| {
             let request = language_server.document_symbols(doc.identifier()).unwrap();
             let offset_encoding = language_server.offset_encoding();
+            let ls_id = language_server.id();
             let doc_id = doc.identifier();
 
             async move {
-                let json = request.await?;
-                let response: Option<lsp::DocumentSymbolResponse> = serde_json::from_value(json)?;
-                let symbols = match response {
-                    Some(symbols) => symbols,
-                    None => return anyhow::Ok(vec![]),
-                };
-                // lsp has two ways to represent symbols (flat/nested)
-                // convert the nested variant to flat, so that we have a homogeneous list
-                let symbols = match symbols {
-                    lsp::DocumentSymbolResponse::Flat(symbols) => symbols
-                        .into_iter()
-                        .map(|symbol| SymbolInformationItem {
-                            symbol,
-                            offset_encoding,
-                        })
-                        .collect(),
-                    lsp::DocumentSymbolResponse::Nested(symbols) => {
-                        let mut flat_symbols = Vec::new();
-                        for symbol in symbols {
-                            nested_to_flat(&mut flat_symbols, &doc_id, symbol, offset_encoding)
+                let result: Result<_, helix_lsp::Error> = async move {
+                    let json = request.await?;
+                    let response: Option<lsp::DocumentSymbolResponse> =
+                        serde_json::from_value(json)?;
+                    let symbols = match response {
+                        Some(symbols) => symbols,
+                        None => return Ok(vec![]),
+                    };
+                    // lsp has two ways to represent symbols (flat/nested)
+                    // convert the nested variant to flat, so that we have a homogeneous list
+                    let symbols = match symbols {
+                        lsp::DocumentSymbolResponse::Flat(symbols) => symbols
+                            .into_iter()
+                            .map(|mut symbol| {
+                                normalize_uri(&mut symbol.location.uri);
+                                SymbolInformationItem {
+                                    symbol,
+                                    offset_encoding,
+                                    lazy: None,
+                                }
+                            })
+                            .collect(),
+                        lsp::DocumentSymbolResponse::Nested(symbols) => {
+                            let mut flat_symbols = Vec::new();
+                            for symbol in symbols {
+                                nested_to_flat(
+                                    &mut flat_symbols,
+                                    &doc_id,
+                                    symbol,
+                                    offset_encoding,
+                                    None,
+                                )
+                            }
+                            flat_symbols
                         }
-                        flat_symbols
-                    }
-                };
-                Ok(symbols)
+                    };
+                    Ok(symbols)
+                }
+                .await;
+                (ls_id, result)
             }
         })
-        .collect();
+        .collect()
+}
+
+pub fn symbol_picker(cx: &mut Context) {
+    if !gate_on_server_ready(
+        cx,
+        LanguageServerFeature::DocumentSymbols,
+        "symbol_picker",
+    ) {
+        return;
+    }
+    let doc = doc!(cx.editor);
+    let private = private_symbol_filter(doc);
+    let doc_id = doc.id();
+
+    let mut futures = document_symbol_requests(doc);
     let current_url = doc.url();
 
     if futures.is_empty() {
@@ -388,14 +1162,72 @@ pub fn symbol_picker(cx: &mut Context) {
         return;
     }
 
+    // give immediate feedback, slow servers can leave this pending for a while
+    let total = futures.len();
+    cx.editor.set_status(format!(
+        "Requesting document symbols from {total} language server(s)…"
+    ));
+
     cx.jobs.callback(async move {
         let mut symbols = Vec::new();
-        // TODO if one symbol request errors, all other requests are discarded (even if they're valid)
-        while let Some(mut lsp_items) = futures.try_next().await? {
-            symbols.append(&mut lsp_items);
+        let mut responded = 0usize;
+        let mut last_error = None;
+        // a failing server (e.g. one that exited mid-request) only discards
+        // its own results, the surviving servers' symbols are still shown
+        while let Some((ls_id, result)) = futures.next().await {
+            match result {
+                Ok(mut lsp_items) => symbols.append(&mut lsp_items),
+                Err(err) => {
+                    last_error =
+                        Some(discard_failed_server(ls_id, err, "document symbols").await);
+                }
+            }
+            responded += 1;
+            if responded < total {
+                crate::job::dispatch(move |editor, _| {
+                    editor.set_status(format!(
+                        "{responded}/{total} language servers responded…"
+                    ));
+                })
+                .await;
+            }
         }
-        let call = move |_editor: &mut Editor, compositor: &mut Compositor| {
-            let picker = sym_picker(symbols, current_url);
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            if symbols.is_empty() {
+                // partial results are only worth showing when there are any;
+                // with every server failed, report the error instead
+                if let Some(error) = last_error {
+                    editor.set_error(error);
+                    return;
+                }
+            }
+            editor.clear_status();
+            // feeds the `:goto-symbol` argument completer
+            editor.cached_symbol_names = Some((
+                doc_id,
+                symbols.iter().map(|item| item.symbol.name.clone()).collect(),
+            ));
+            let picker = match private {
+                // private-looking symbols are hidden, not dropped: the
+                // picker's `A-i` toggle reveals them
+                Some(is_private) => {
+                    let (visible, hidden): (Vec<_>, Vec<_>) = symbols
+                        .into_iter()
+                        .partition(|item| !is_private(&item.symbol));
+                    sym_picker(editor, visible.clone(), current_url, "symbol_picker")
+                        .with_hidden_options(
+                            Arc::new(AtomicBool::new(false)),
+                            move |show_hidden| {
+                                let mut options = visible.clone();
+                                if show_hidden {
+                                    options.extend(hidden.iter().cloned());
+                                }
+                                options
+                            },
+                        )
+                }
+                None => sym_picker(editor, symbols, current_url, "symbol_picker"),
+            };
             compositor.push(Box::new(overlaid(picker)))
         };
 
@@ -403,6 +1235,87 @@ pub fn symbol_picker(cx: &mut Context) {
     });
 }
 
+/// Implementation of `:goto-symbol`: requests document symbols like
+/// [symbol_picker], matches `name` exactly — or as a prefix when nothing
+/// matches exactly — against the flattened symbol names and jumps straight
+/// to a single match; an ambiguous name falls back to a picker restricted
+/// to the candidates. The fetched names also refresh the cache feeding the
+/// command's argument completion.
+pub fn goto_symbol_by_name(cx: &mut compositor::Context, name: String) {
+    let doc = doc!(cx.editor);
+    let doc_id = doc.id();
+    let current_url = doc.url();
+
+    let mut futures = document_symbol_requests(doc);
+
+    if futures.is_empty() {
+        cx.editor
+            .set_error("No configured language server supports document symbols");
+        return;
+    }
+
+    cx.jobs.callback(async move {
+        let mut symbols = Vec::new();
+        let mut last_error = None;
+        // a failing server only discards its own results
+        while let Some((ls_id, result)) = futures.next().await {
+            match result {
+                Ok(mut lsp_items) => symbols.append(&mut lsp_items),
+                Err(err) => {
+                    last_error =
+                        Some(discard_failed_server(ls_id, err, "document symbols").await);
+                }
+            }
+        }
+
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            if symbols.is_empty() {
+                if let Some(error) = last_error {
+                    editor.set_error(error);
+                    return;
+                }
+            }
+            editor.cached_symbol_names = Some((
+                doc_id,
+                symbols.iter().map(|item| item.symbol.name.clone()).collect(),
+            ));
+
+            let mut candidates: Vec<_> = symbols
+                .iter()
+                .filter(|item| item.symbol.name == name)
+                .cloned()
+                .collect();
+            if candidates.is_empty() {
+                candidates = symbols
+                    .into_iter()
+                    .filter(|item| item.symbol.name.starts_with(&name))
+                    .collect();
+            }
+
+            match candidates.as_slice() {
+                [] => editor.set_error(format!("No symbol matching `{name}`")),
+                [item] => jump_to_location(
+                    editor,
+                    &item.symbol.location,
+                    item.offset_encoding,
+                    Action::Replace,
+                    "goto_symbol",
+                ),
+                _ => {
+                    editor.set_status(format!(
+                        "`{name}` is ambiguous, {} symbols match",
+                        candidates.len()
+                    ));
+                    let picker = sym_picker(editor, candidates, current_url, "goto_symbol");
+                    compositor.push(Box::new(overlaid(picker)));
+                }
+            }
+        };
+
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
 pub fn symbol_method_picker(cx: &mut Context) {
     fn nested_to_flat(
         list: &mut Vec<SymbolInformationItem>,
@@ -433,13 +1346,15 @@ pub fn symbol_method_picker(cx: &mut Context) {
 
         let node_name = format!("{prefix}{}{suffix}", symbol.name);
 
-        fn sbl_kind(sbl: lsp::SymbolKind) -> &'static str {
+        fn sbl_kind(sbl: lsp::SymbolKind) -> Cow<'static, str> {
             macro_rules! pair {
                 ( $($k:ident => $s:expr),+ ) => {
                     match sbl { $(
-                      lsp::SymbolKind::$k => concat!('[', $s, ']'),
+                      lsp::SymbolKind::$k => Cow::Borrowed(concat!('[', $s, ']')),
                     )+
-                    _ => "[??]" }
+                    // Servers do send out-of-spec kinds; show the raw number so
+                    // bug reports can tell us which one it was.
+                    _ => Cow::Owned(format!("[{}]", unknown_lsp_enum("kind", sbl))) }
                 }
             }
             pair! {
@@ -465,6 +1380,7 @@ pub fn symbol_method_picker(cx: &mut Context) {
                 container_name: None,
             },
             offset_encoding,
+            lazy: None,
         });
 
         for child in symbol.children.into_iter().flatten() {
@@ -481,34 +1397,50 @@ pub fn symbol_method_picker(cx: &mut Context) {
         .map(|language_server| {
             let request = language_server.document_symbols(doc.identifier()).unwrap();
             let offset_encoding = language_server.offset_encoding();
+            let ls_id = language_server.id();
             let doc_id = doc.identifier();
 
             async move {
-                let json = request.await?;
-                let response: Option<lsp::DocumentSymbolResponse> = serde_json::from_value(json)?;
-                let symbols = match response {
-                    Some(symbols) => symbols,
-                    None => return anyhow::Ok(vec![]),
-                };
-                // lsp has two ways to represent symbols (flat/nested)
-                // convert the nested variant to flat, so that we have a homogeneous list
-                let symbols = match symbols {
-                    lsp::DocumentSymbolResponse::Flat(symbols) => symbols
-                        .into_iter()
-                        .map(|symbol| SymbolInformationItem {
-                            symbol,
-                            offset_encoding,
-                        })
-                        .collect(),
-                    lsp::DocumentSymbolResponse::Nested(symbols) => {
-                        let mut flat_symbols = Vec::new();
-                        for symbol in symbols {
-                            nested_to_flat(&mut flat_symbols, &doc_id, symbol, offset_encoding, 0)
+                let result: Result<_, helix_lsp::Error> = async move {
+                    let json = request.await?;
+                    let response: Option<lsp::DocumentSymbolResponse> =
+                        serde_json::from_value(json)?;
+                    let symbols = match response {
+                        Some(symbols) => symbols,
+                        None => return Ok(vec![]),
+                    };
+                    // lsp has two ways to represent symbols (flat/nested)
+                    // convert the nested variant to flat, so that we have a homogeneous list
+                    let symbols = match symbols {
+                        lsp::DocumentSymbolResponse::Flat(symbols) => symbols
+                            .into_iter()
+                            .map(|mut symbol| {
+                                normalize_uri(&mut symbol.location.uri);
+                                SymbolInformationItem {
+                                    symbol,
+                                    offset_encoding,
+                                    lazy: None,
+                                }
+                            })
+                            .collect(),
+                        lsp::DocumentSymbolResponse::Nested(symbols) => {
+                            let mut flat_symbols = Vec::new();
+                            for symbol in symbols {
+                                nested_to_flat(
+                                    &mut flat_symbols,
+                                    &doc_id,
+                                    symbol,
+                                    offset_encoding,
+                                    0,
+                                )
+                            }
+                            flat_symbols
                         }
-                        flat_symbols
-                    }
-                };
-                Ok(symbols)
+                    };
+                    Ok(symbols)
+                }
+                .await;
+                (ls_id, result)
             }
         })
         .collect();
@@ -522,12 +1454,25 @@ pub fn symbol_method_picker(cx: &mut Context) {
 
     cx.jobs.callback(async move {
         let mut symbols = Vec::new();
-        // TODO if one symbol request errors, all other requests are discarded (even if they're valid)
-        while let Some(mut lsp_items) = futures.try_next().await? {
-            symbols.append(&mut lsp_items);
+        let mut last_error = None;
+        // a failing server only discards its own results, see [symbol_picker]
+        while let Some((ls_id, result)) = futures.next().await {
+            match result {
+                Ok(mut lsp_items) => symbols.append(&mut lsp_items),
+                Err(err) => {
+                    last_error =
+                        Some(discard_failed_server(ls_id, err, "document symbols").await);
+                }
+            }
         }
-        let call = move |_editor: &mut Editor, compositor: &mut Compositor| {
-            let picker = sym_picker(symbols, current_url);
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            if symbols.is_empty() {
+                if let Some(error) = last_error {
+                    editor.set_error(error);
+                    return;
+                }
+            }
+            let picker = sym_picker(editor, symbols, current_url, "symbol_method_picker");
             compositor.push(Box::new(overlaid(picker)))
         };
 
@@ -535,67 +1480,160 @@ pub fn symbol_method_picker(cx: &mut Context) {
     });
 }
 
-pub fn workspace_symbol_picker(cx: &mut Context) {
-    let doc = doc!(cx.editor);
-    if doc
-        .language_servers_with_feature(LanguageServerFeature::WorkspaceSymbols)
-        .count()
-        == 0
-    {
-        cx.editor
-            .set_error("No configured language server supports workspace symbols");
-        return;
-    }
+pub fn goto_nth_symbol_of_kind(cx: &mut Context) {
+    let count = cx.count();
+    cx.on_next_key(move |cx, event| {
+        let kinds: &'static [lsp::SymbolKind] = match event.char() {
+            Some('f') => &[lsp::SymbolKind::FUNCTION, lsp::SymbolKind::METHOD],
+            Some('s') => &[lsp::SymbolKind::STRUCT],
+            Some('c') => &[lsp::SymbolKind::CLASS],
+            Some('e') => &[lsp::SymbolKind::ENUM],
+            Some('i') => &[lsp::SymbolKind::INTERFACE],
+            Some('m') => &[lsp::SymbolKind::MODULE, lsp::SymbolKind::NAMESPACE],
+            Some('v') => &[lsp::SymbolKind::VARIABLE, lsp::SymbolKind::CONSTANT],
+            _ => {
+                cx.editor
+                    .set_error("Expected a symbol kind: f, s, c, e, i, m or v");
+                return;
+            }
+        };
 
-    let get_symbols = move |pattern: String, editor: &mut Editor| {
-        let doc = doc!(editor);
-        let mut seen_language_servers = HashSet::new();
-        let mut futures: FuturesOrdered<_> = doc
-            .language_servers_with_feature(LanguageServerFeature::WorkspaceSymbols)
-            .filter(|ls| seen_language_servers.insert(ls.id()))
-            .map(|language_server| {
-                let request = language_server.workspace_symbols(pattern.clone()).unwrap();
-                let offset_encoding = language_server.offset_encoding();
-                async move {
-                    let json = request.await?;
+        let (view, doc) = current!(cx.editor);
+        let cursor = doc.selection(view.id).primary().cursor(doc.text().slice(..));
 
-                    let response =
-                        serde_json::from_value::<Option<Vec<lsp::SymbolInformation>>>(json)?
-                            .unwrap_or_default()
-                            .into_iter()
-                            .map(|symbol| SymbolInformationItem {
-                                symbol,
-                                offset_encoding,
-                            })
-                            .collect();
+        let language_server =
+            language_server_with_feature!(cx.editor, doc, LanguageServerFeature::DocumentSymbols);
+        let offset_encoding = language_server.offset_encoding();
+        let future = language_server.document_symbols(doc.identifier()).unwrap();
 
-                    anyhow::Ok(response)
+        cx.callback(
+            future,
+            move |editor, _compositor, response: Option<lsp::DocumentSymbolResponse>| {
+                fn collect_nested(
+                    list: &mut Vec<(lsp::Position, lsp::SymbolKind)>,
+                    symbol: lsp::DocumentSymbol,
+                ) {
+                    list.push((symbol.selection_range.start, symbol.kind));
+                    for child in symbol.children.into_iter().flatten() {
+                        collect_nested(list, child);
+                    }
                 }
-            })
-            .collect();
 
-        if futures.is_empty() {
-            editor.set_error("No configured language server supports workspace symbols");
+                let symbols = match response {
+                    Some(lsp::DocumentSymbolResponse::Flat(symbols)) => symbols
+                        .into_iter()
+                        .map(|symbol| (symbol.location.range.start, symbol.kind))
+                        .collect(),
+                    Some(lsp::DocumentSymbolResponse::Nested(symbols)) => {
+                        let mut flat = Vec::new();
+                        for symbol in symbols {
+                            collect_nested(&mut flat, symbol);
+                        }
+                        flat
+                    }
+                    None => Vec::new(),
+                };
+
+                let text = doc!(editor).text();
+                // kind-filtered symbol starts in source order, so the count
+                // steps through "the nth function after the cursor"
+                let mut positions: Vec<usize> = symbols
+                    .into_iter()
+                    .filter(|(_, kind)| kinds.contains(kind))
+                    .filter_map(|(pos, _)| lsp_pos_to_pos(text, pos, offset_encoding))
+                    .collect();
+                positions.sort_unstable();
+                positions.dedup();
+
+                let Some(pos) = positions.into_iter().filter(|&pos| pos > cursor).nth(count - 1)
+                else {
+                    editor.set_error("No matching symbol after the cursor");
+                    return;
+                };
+
+                let (view, doc) = current!(editor);
+                push_jump(view, doc);
+                doc.set_selection(view.id, Selection::point(pos));
+                align_view(doc, view, Align::Center);
+            },
+        );
+    });
+}
+
+pub fn workspace_symbol_picker(cx: &mut Context) {
+    let supported = cx.editor.language_servers.iter_clients().any(|client| {
+        client.is_initialized()
+            && !client.is_crashed()
+            && client.supports_feature(LanguageServerFeature::WorkspaceSymbols)
+    });
+    if !supported {
+        // don't error out just because the focused file has no LSP, the
+        // workspace can still be indexed client-side
+        workspace_symbol_index_picker(cx);
+        return;
+    }
+    if !gate_on_server_ready(
+        cx,
+        LanguageServerFeature::WorkspaceSymbols,
+        "workspace_symbol_picker",
+    ) {
+        return;
+    }
+    let doc = doc!(cx.editor);
+
+    let filter = Arc::new(WorkspaceFilter::new(cx.editor));
+    let show_hidden = Arc::new(AtomicBool::new(false));
+    // judged by the focused document's language: per-file language lookups
+    // for every symbol would not be worth it in a workspace-wide list
+    let private = private_symbol_filter(doc);
+    let is_shown = {
+        let filter = Arc::clone(&filter);
+        let private = private.clone();
+        move |item: &SymbolInformationItem| {
+            !filter.is_hidden_uri(&item.symbol.location.uri)
+                && !private
+                    .as_ref()
+                    .is_some_and(|is_private| is_private(&item.symbol))
         }
+    };
 
-        async move {
-            let mut symbols = Vec::new();
-            // TODO if one symbol request errors, all other requests are discarded (even if they're valid)
-            while let Some(mut lsp_items) = futures.try_next().await? {
-                symbols.append(&mut lsp_items);
+    let get_symbols = {
+        let show_hidden = Arc::clone(&show_hidden);
+        let is_shown = is_shown.clone();
+        move |pattern: String, editor: &mut Editor| {
+            let symbols = fetch_workspace_symbols(pattern, editor);
+            let show_hidden = Arc::clone(&show_hidden);
+            let is_shown = is_shown.clone();
+            async move {
+                let mut symbols = symbols.await?;
+                if !show_hidden.load(atomic::Ordering::Relaxed) {
+                    symbols.retain(&is_shown);
+                }
+                anyhow::Ok(symbols)
             }
-            anyhow::Ok(symbols)
+            .boxed()
         }
-        .boxed()
     };
 
     let current_url = doc.url();
-    let initial_symbols = get_symbols("".to_owned(), cx.editor);
+    // the initial query is kept unfiltered so the `A-i` toggle has the hidden
+    // results at hand; later queries re-fetch and follow the shared flag
+    let initial_symbols = fetch_workspace_symbols("".to_owned(), cx.editor);
 
     cx.jobs.callback(async move {
         let symbols = initial_symbols.await?;
-        let call = move |_editor: &mut Editor, compositor: &mut Compositor| {
-            let picker = sym_picker(symbols, current_url);
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            let (visible, hidden): (Vec<_>, Vec<_>) =
+                symbols.into_iter().partition(|item| is_shown(item));
+            let picker = sym_picker(editor, visible.clone(), current_url, "workspace_symbol_picker")
+                .with_hidden_options(show_hidden, move |show_hidden| {
+                    let mut options = visible.clone();
+                    if show_hidden {
+                        options.extend(hidden.iter().cloned());
+                    }
+                    options
+                })
+                .with_idle_callback(resolve_highlighted_workspace_symbol);
             let dyn_picker = DynamicPicker::new(picker, Box::new(get_symbols));
             compositor.push(Box::new(overlaid(dyn_picker)))
         };
@@ -604,522 +1642,4387 @@ pub fn workspace_symbol_picker(cx: &mut Context) {
     });
 }
 
-pub fn diagnostics_picker(cx: &mut Context) {
-    let doc = doc!(cx.editor);
-    if let Some(current_path) = doc.path() {
-        let diagnostics = cx
-            .editor
-            .diagnostics
-            .get(current_path)
-            .cloned()
-            .unwrap_or_default();
-        let picker = diag_picker(
-            cx,
-            [(current_path.clone(), diagnostics)].into(),
-            DiagnosticsFormat::HideSourcePath,
-        );
-        cx.push_layer(Box::new(overlaid(picker)));
+/// Queries every running language server in the workspace that supports
+/// workspace symbols, the current document's first, and merges the responses,
+/// up to `lsp.workspace-symbol-limit` entries across all servers combined.
+fn fetch_workspace_symbols(
+    pattern: String,
+    editor: &mut Editor,
+) -> BoxFuture<'static, anyhow::Result<Vec<SymbolInformationItem>>> {
+    // The protocol has no way to ask servers for fewer results, so the
+    // limit is enforced client-side, across all servers combined.
+    let limit = editor.config().lsp.workspace_symbol_limit;
+    let doc = doc!(editor);
+    let mut seen_language_servers = HashSet::new();
+    // The current document's servers answer first, but in mixed repos the
+    // focused file may have no capable server at all while another running
+    // client in the workspace does, so those are queried too.
+    let mut futures: FuturesOrdered<_> = doc
+        .language_servers_with_feature(LanguageServerFeature::WorkspaceSymbols)
+        .chain(editor.language_servers.iter_clients().map(|client| &**client).filter(
+            |client| {
+                client.is_initialized()
+                    && !client.is_crashed()
+                    && client.supports_feature(LanguageServerFeature::WorkspaceSymbols)
+            },
+        ))
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+        .map(|language_server| {
+            let request = language_server.workspace_symbols(pattern.clone()).unwrap();
+            let offset_encoding = language_server.offset_encoding();
+            let ls_id = language_server.id();
+            let supports_resolve = matches!(
+                language_server.capabilities().workspace_symbol_provider,
+                Some(lsp::OneOf::Right(lsp::WorkspaceSymbolOptions {
+                    resolve_provider: Some(true),
+                    ..
+                }))
+            );
+            async move {
+                let result: Result<_, helix_lsp::Error> = async move {
+                    let json = request.await?;
+
+                    let response: Vec<_> =
+                        serde_json::from_value::<Option<lsp::WorkspaceSymbolResponse>>(json)?
+                            .map(|response| match response {
+                                lsp::WorkspaceSymbolResponse::Flat(symbols) => symbols
+                                    .into_iter()
+                                    .map(|mut symbol| {
+                                        normalize_uri(&mut symbol.location.uri);
+                                        SymbolInformationItem {
+                                            symbol,
+                                            offset_encoding,
+                                            lazy: None,
+                                        }
+                                    })
+                                    .collect(),
+                                lsp::WorkspaceSymbolResponse::Nested(symbols) => symbols
+                                    .into_iter()
+                                    .map(|symbol| {
+                                        workspace_symbol_to_item(
+                                            symbol,
+                                            ls_id,
+                                            offset_encoding,
+                                            supports_resolve,
+                                        )
+                                    })
+                                    .collect(),
+                            })
+                            .unwrap_or_default();
+
+                    Ok(response)
+                }
+                .await;
+                (ls_id, result)
+            }
+        })
+        .collect();
+
+    if futures.is_empty() {
+        editor.set_error("No configured language server supports workspace symbols");
     }
-}
 
-pub fn workspace_diagnostics_picker(cx: &mut Context) {
-    // TODO not yet filtered by LanguageServerFeature, need to do something similar as Document::shown_diagnostics here for all open documents
-    let diagnostics = cx.editor.diagnostics.clone();
-    let picker = diag_picker(cx, diagnostics, DiagnosticsFormat::ShowSourcePath);
-    cx.push_layer(Box::new(overlaid(picker)));
+    async move {
+        let mut symbols: Vec<SymbolInformationItem> = Vec::new();
+        let mut truncated = false;
+        // a failing server only discards its own results, see [symbol_picker]
+        while let Some((ls_id, result)) = futures.next().await {
+            let mut lsp_items = match result {
+                Ok(lsp_items) => lsp_items,
+                Err(err) => {
+                    crate::job::dispatch(move |editor, _| {
+                        if !handle_server_exit(editor, ls_id, &err) {
+                            editor.set_error(err.to_string());
+                        }
+                    })
+                    .await;
+                    continue;
+                }
+            };
+            let remaining = limit.saturating_sub(symbols.len());
+            if lsp_items.len() > remaining {
+                lsp_items.truncate(remaining);
+                truncated = true;
+            }
+            symbols.append(&mut lsp_items);
+            if truncated {
+                break;
+            }
+        }
+        if truncated {
+            crate::job::dispatch(|editor, _| {
+                editor.set_status("workspace symbol results truncated, refine your query");
+            })
+            .await;
+        }
+        anyhow::Ok(symbols)
+    }
+    .boxed()
 }
 
-struct CodeActionOrCommandItem {
-    lsp_item: lsp::CodeActionOrCommand,
+/// Converts a nested [lsp::WorkspaceSymbol] response entry into the picker
+/// item, keeping the raw symbol around for lazy resolution when the server
+/// supports `workspaceSymbol/resolve`.
+fn workspace_symbol_to_item(
+    mut symbol: lsp::WorkspaceSymbol,
     language_server_id: LanguageServerId,
+    offset_encoding: OffsetEncoding,
+    supports_resolve: bool,
+) -> SymbolInformationItem {
+    let location = match &mut symbol.location {
+        lsp::OneOf::Left(location) => {
+            normalize_uri(&mut location.uri);
+            location.clone()
+        }
+        // the range is one of the properties resolve may fill in lazily, an
+        // empty one jumps to the start of the file meanwhile
+        lsp::OneOf::Right(workspace_location) => {
+            let mut uri = workspace_location.uri.clone();
+            normalize_uri(&mut uri);
+            lsp::Location::new(uri, lsp::Range::default())
+        }
+    };
+    #[allow(deprecated)]
+    let information = lsp::SymbolInformation {
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        tags: symbol.tags.clone(),
+        deprecated: None,
+        location,
+        container_name: symbol.container_name.clone(),
+    };
+    SymbolInformationItem {
+        symbol: information,
+        offset_encoding,
+        lazy: supports_resolve.then(|| LazyWorkspaceSymbol {
+            language_server_id,
+            symbol,
+            resolved: Arc::new(std::sync::OnceLock::new()),
+            requested: Arc::new(AtomicBool::new(false)),
+        }),
+    }
 }
 
-impl ui::menu::Item for CodeActionOrCommandItem {
-    type Data = ();
-    fn format(&self, _data: &Self::Data) -> Row {
-        match &self.lsp_item {
-            lsp::CodeActionOrCommand::CodeAction(action) => action.title.as_str().into(),
-            lsp::CodeActionOrCommand::Command(command) => command.title.as_str().into(),
-        }
+/// Requests `workspaceSymbol/resolve` for the highlighted picker item once it
+/// has idled under the cursor; the answer fills the item's shared slot and
+/// the row's container shows up in place. The idle timer debounces this, so
+/// scrolling through a huge result set doesn't fire a request per row.
+fn resolve_highlighted_workspace_symbol(
+    cx: &mut compositor::Context,
+    item: &SymbolInformationItem,
+) {
+    let Some(lazy) = &item.lazy else {
+        return;
+    };
+    if lazy.resolved.get().is_some() || lazy.requested.swap(true, atomic::Ordering::Relaxed) {
+        return;
     }
+    let Some(future) = cx
+        .editor
+        .language_server_by_id(lazy.language_server_id)
+        .and_then(|language_server| language_server.resolve_workspace_symbol(lazy.symbol.clone()))
+    else {
+        return;
+    };
+    let resolved = Arc::clone(&lazy.resolved);
+    let requested = Arc::clone(&lazy.requested);
+    tokio::spawn(async move {
+        let response = future.await.and_then(|json| {
+            serde_json::from_value::<lsp::WorkspaceSymbol>(json).map_err(Into::into)
+        });
+        match response {
+            Ok(symbol) => {
+                let _ = resolved.set(symbol);
+                // wake the compositor so the row re-renders
+                crate::job::dispatch(|_, _| ()).await;
+            }
+            Err(err) => {
+                log::error!("workspaceSymbol/resolve: {err}");
+                // leave a retry to the next idle
+                requested.store(false, atomic::Ordering::Relaxed);
+            }
+        }
+    });
 }
 
-/// Determines the category of the `CodeAction` using the `CodeAction::kind` field.
-/// Returns a number that represent these categories.
-/// Categories with a lower number should be displayed first.
-///
-///
-/// While the `kind` field is defined as open ended in the LSP spec (any value may be used)
-/// in practice a closed set of common values (mostly suggested in the LSP spec) are used.
-/// VSCode displays each of these categories separately (separated by a heading in the codeactions picker)
-/// to make them easier to navigate. Helix does not display these  headings to the user.
-/// However it does sort code actions by their categories to achieve the same order as the VScode picker,
-/// just without the headings.
-///
-/// The order used here is modeled after the [vscode sourcecode](https://github.com/microsoft/vscode/blob/eaec601dd69aeb4abb63b9601a6f44308c8d8c6e/src/vs/editor/contrib/codeAction/browser/codeActionWidget.ts>)
-fn action_category(action: &CodeActionOrCommand) -> u32 {
-    if let CodeActionOrCommand::CodeAction(CodeAction {
-        kind: Some(kind), ..
-    }) = action
-    {
-        let mut components = kind.as_str().split('.');
-        match components.next() {
-            Some("quickfix") => 0,
-            Some("refactor") => match components.next() {
-                Some("extract") => 1,
-                Some("inline") => 2,
-                Some("rewrite") => 3,
-                Some("move") => 4,
-                Some("surround") => 5,
-                _ => 7,
-            },
-            Some("source") => 6,
-            _ => 7,
+/// Fallback for [workspace_symbol_picker] when no running language server
+/// supports workspace symbols: walks the workspace on a background thread,
+/// indexes function and type definitions with tree-sitter and streams them
+/// into the same picker. The walk respects ignore files and stops as soon as
+/// the picker is closed.
+fn workspace_symbol_index_picker(cx: &mut Context) {
+    use ignore::WalkBuilder;
+
+    let config = cx.editor.config();
+    let root = helix_stdx::env::current_working_dir();
+
+    let mut walk_builder = WalkBuilder::new(&root);
+    walk_builder
+        .hidden(config.file_picker.hidden)
+        .parents(config.file_picker.parents)
+        .ignore(config.file_picker.ignore)
+        .follow_links(config.file_picker.follow_symlinks)
+        .git_ignore(config.file_picker.git_ignore)
+        .git_global(config.file_picker.git_global)
+        .git_exclude(config.file_picker.git_exclude)
+        .max_depth(config.file_picker.max_depth);
+    walk_builder.add_custom_ignore_filename(helix_loader::config_dir().join("ignore"));
+    walk_builder.add_custom_ignore_filename(".helix/ignore");
+
+    let current_url = doc!(cx.editor).url();
+    let picker = sym_picker(cx.editor, Vec::new(), current_url, "workspace_symbol_picker")
+        .with_prompt_text("index (no LSP): ".into());
+    let injector = picker.injector();
+    let loader = cx.editor.syn_loader.clone();
+
+    cx.editor.set_status(
+        "No running language server supports workspace symbols, using the tree-sitter index",
+    );
+
+    std::thread::spawn(move || {
+        let files = walk_builder.build().filter_map(|entry| {
+            let entry = entry.ok()?;
+            entry.file_type()?.is_file().then(|| entry.into_path())
+        });
+        for path in files {
+            let Some(lang_config) = loader.load().language_config_for_file_name(&path) else {
+                continue;
+            };
+            for symbol in index_file_symbols(&path, &lang_config, &loader) {
+                if injector.push(symbol).is_err() {
+                    // the picker was closed, stop scanning
+                    return;
+                }
+            }
+        }
+    });
+
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// Parses `path` and extracts its `function.around` and `class.around`
+/// textobjects as workspace symbols. Files without a grammar or without
+/// textobject queries index as empty. The declaration line doubles as the
+/// symbol name, there is no server to ask for one.
+fn index_file_symbols(
+    path: &Path,
+    lang_config: &helix_core::syntax::LanguageConfiguration,
+    loader: &Arc<arc_swap::ArcSwap<helix_core::syntax::Loader>>,
+) -> Vec<SymbolInformationItem> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let text = helix_core::Rope::from(contents);
+    // initialized with the same scopes as [Document::detect_language], so a
+    // buffer opened later reuses the configuration
+    let Some(highlight_config) = lang_config.highlight_config(&loader.load().scopes()) else {
+        return Vec::new();
+    };
+    let Some(syntax) = helix_core::Syntax::new(text.slice(..), highlight_config, loader.clone())
+    else {
+        return Vec::new();
+    };
+    let Some(query) = lang_config.textobject_query() else {
+        return Vec::new();
+    };
+    let Ok(uri) = lsp::Url::from_file_path(path) else {
+        return Vec::new();
+    };
+
+    let slice = text.slice(..);
+    let root = syntax.tree().root_node();
+    let mut symbols = Vec::new();
+    for (capture, kind) in [
+        ("function.around", lsp::SymbolKind::FUNCTION),
+        ("class.around", lsp::SymbolKind::CLASS),
+    ] {
+        let mut cursor = helix_core::tree_sitter::QueryCursor::new();
+        let Some(nodes) = query.capture_nodes(capture, root, slice, &mut cursor) else {
+            continue;
+        };
+        for node in nodes {
+            let start = slice.byte_to_char(node.start_byte().min(slice.len_bytes()));
+            let line = slice.char_to_line(start);
+            let name = slice.line(line).to_string().trim().to_owned();
+            if name.is_empty() {
+                continue;
+            }
+            let position = lsp::Position::new(line as u32, 0);
+            #[allow(deprecated)]
+            let symbol = lsp::SymbolInformation {
+                name,
+                kind,
+                tags: None,
+                deprecated: None,
+                location: lsp::Location::new(uri.clone(), lsp::Range::new(position, position)),
+                container_name: Some("index (no LSP)".to_owned()),
+            };
+            symbols.push(SymbolInformationItem {
+                symbol,
+                // positions are plain line starts, so any encoding fits
+                offset_encoding: OffsetEncoding::Utf8,
+                lazy: None,
+            });
         }
-    } else {
-        7
     }
+    symbols
 }
 
-fn action_preferred(action: &CodeActionOrCommand) -> bool {
-    matches!(
-        action,
-        CodeActionOrCommand::CodeAction(CodeAction {
-            is_preferred: Some(true),
-            ..
-        })
-    )
+pub fn diagnostics_picker(cx: &mut Context) {
+    let doc = doc!(cx.editor);
+    if let Some(current_path) = doc.path().cloned() {
+        let picker = file_diagnostics_picker(cx.editor, current_path);
+        cx.push_layer(Box::new(overlaid(picker)));
+    }
 }
 
-fn action_fixes_diagnostics(action: &CodeActionOrCommand) -> bool {
-    matches!(
-        action,
-        CodeActionOrCommand::CodeAction(CodeAction {
-            diagnostics: Some(diagnostics),
-            ..
-        }) if !diagnostics.is_empty()
+/// Builds the single-file diagnostics picker for `path`, which does not have
+/// to belong to the focused document: `:diagnostics <path>` uses this for any
+/// file that currently has diagnostics.
+pub(crate) fn file_diagnostics_picker(editor: &Editor, path: PathBuf) -> Picker<PickerDiagnostic> {
+    let diagnostics = editor.diagnostics.get(&path).cloned().unwrap_or_default();
+    let flat_diag = flatten_diagnostics(editor, [(path.clone(), diagnostics)].into());
+    // [DiagnosticsFormat::HideSourcePath] drops the path from every row, so
+    // put the file name in the picker title instead
+    let title = path
+        .file_name()
+        .map(|name| format!("{}: ", name.to_string_lossy()))
+        .unwrap_or_default();
+    diag_picker(
+        editor,
+        flat_diag,
+        DiagnosticsFormat::HideSourcePath,
+        "diagnostics_picker",
     )
+    .with_prompt_text(title.into())
 }
 
-pub fn code_action(cx: &mut Context) {
+pub fn symbol_for_diagnostic(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
+    let text = doc.text();
+    let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+
+    let Some(diag) = doc
+        .diagnostics()
+        .iter()
+        .find(|diag| diag.range.start <= cursor && cursor <= diag.range.end)
+    else {
+        cx.editor.set_status("No diagnostic under cursor");
+        return;
+    };
+    let diag_start = diag.range.start;
+    let severity = match diag.severity {
+        Some(helix_core::diagnostic::Severity::Error) => "error",
+        Some(helix_core::diagnostic::Severity::Warning) => "warning",
+        Some(helix_core::diagnostic::Severity::Info) => "info",
+        Some(helix_core::diagnostic::Severity::Hint) => "hint",
+        None => "diagnostic",
+    };
 
-    let selection_range = doc.selection(view.id).primary();
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::DocumentSymbols);
+    let offset_encoding = language_server.offset_encoding();
+    let future = language_server.document_symbols(doc.identifier()).unwrap();
 
-    let mut seen_language_servers = HashSet::new();
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<lsp::DocumentSymbolResponse>| {
+            // Collect (full range, name) pairs; for nested symbols the `range`
+            // field covers the whole body, which is what containment needs.
+            fn collect_nested(list: &mut Vec<(lsp::Range, String)>, symbol: lsp::DocumentSymbol) {
+                list.push((symbol.range, symbol.name));
+                for child in symbol.children.into_iter().flatten() {
+                    collect_nested(list, child);
+                }
+            }
 
-    let mut futures: FuturesOrdered<_> = doc
-        .language_servers_with_feature(LanguageServerFeature::CodeAction)
-        .filter(|ls| seen_language_servers.insert(ls.id()))
-        // TODO this should probably already been filtered in something like "language_servers_with_feature"
-        .filter_map(|language_server| {
-            let offset_encoding = language_server.offset_encoding();
-            let language_server_id = language_server.id();
-            let range = range_to_lsp_range(doc.text(), selection_range, offset_encoding);
-            // Filter and convert overlapping diagnostics
-            let code_action_context = lsp::CodeActionContext {
-                diagnostics: doc
-                    .diagnostics()
-                    .iter()
-                    .filter(|&diag| {
-                        selection_range
-                            .overlaps(&helix_core::Range::new(diag.range.start, diag.range.end))
-                    })
-                    .map(|diag| diagnostic_to_lsp_diagnostic(doc.text(), diag, offset_encoding))
+            let symbols = match response {
+                Some(lsp::DocumentSymbolResponse::Flat(symbols)) => symbols
+                    .into_iter()
+                    .map(|symbol| (symbol.location.range, symbol.name))
                     .collect(),
-                only: None,
-                trigger_kind: Some(CodeActionTriggerKind::INVOKED),
-            };
-            let code_action_request =
-                language_server.code_actions(doc.identifier(), range, code_action_context)?;
-            Some((code_action_request, language_server_id))
-        })
-        .map(|(request, ls_id)| async move {
-            let json = request.await?;
-            let response: Option<lsp::CodeActionResponse> = serde_json::from_value(json)?;
-            let mut actions = match response {
-                Some(a) => a,
-                None => return anyhow::Ok(Vec::new()),
+                Some(lsp::DocumentSymbolResponse::Nested(symbols)) => {
+                    let mut flat = Vec::new();
+                    for symbol in symbols {
+                        collect_nested(&mut flat, symbol);
+                    }
+                    flat
+                }
+                None => Vec::new(),
             };
 
-            // remove disabled code actions
-            actions.retain(|action| {
-                matches!(
-                    action,
-                    CodeActionOrCommand::Command(_)
-                        | CodeActionOrCommand::CodeAction(CodeAction { disabled: None, .. })
-                )
-            });
-
-            // Sort codeactions into a useful order. This behaviour is only partially described in the LSP spec.
-            // Many details are modeled after vscode because language servers are usually tested against it.
-            // VScode sorts the codeaction two times:
-            //
-            // First the codeactions that fix some diagnostics are moved to the front.
-            // If both codeactions fix some diagnostics (or both fix none) the codeaction
-            // that is marked with `is_preferred` is shown first. The codeactions are then shown in separate
-            // submenus that only contain a certain category (see `action_category`) of actions.
-            //
-            // Below this done in in a single sorting step
-            actions.sort_by(|action1, action2| {
-                // sort actions by category
-                let order = action_category(action1).cmp(&action_category(action2));
-                if order != Ordering::Equal {
-                    return order;
-                }
-                // within the categories sort by relevancy.
-                // Modeled after the `codeActionsComparator` function in vscode:
-                // https://github.com/microsoft/vscode/blob/eaec601dd69aeb4abb63b9601a6f44308c8d8c6e/src/vs/editor/contrib/codeAction/browser/codeAction.ts
-
-                // if one code action fixes a diagnostic but the other one doesn't show it first
-                let order = action_fixes_diagnostics(action1)
-                    .cmp(&action_fixes_diagnostics(action2))
-                    .reverse();
-                if order != Ordering::Equal {
-                    return order;
-                }
-
-                // if one of the codeactions is marked as preferred show it first
-                // otherwise keep the original LSP sorting
-                action_preferred(action1)
-                    .cmp(&action_preferred(action2))
-                    .reverse()
-            });
-
-            Ok(actions
+            let doc = doc!(editor);
+            let text = doc.text();
+            // the innermost (smallest) containing symbol is the most useful answer
+            let containing = symbols
                 .into_iter()
-                .map(|lsp_item| CodeActionOrCommandItem {
-                    lsp_item,
-                    language_server_id: ls_id,
+                .filter_map(|(range, name)| {
+                    let range = lsp_range_to_range(text, range, offset_encoding)?;
+                    (range.from() <= diag_start && diag_start <= range.to())
+                        .then(|| (range.to() - range.from(), name))
                 })
-                .collect())
-        })
-        .collect();
-
-    if futures.is_empty() {
-        cx.editor
-            .set_error("No configured language server supports code actions");
-        return;
-    }
-
-    cx.jobs.callback(async move {
-        let mut actions = Vec::new();
-        // TODO if one code action request errors, all other requests are ignored (even if they're valid)
-        while let Some(mut lsp_items) = futures.try_next().await? {
-            actions.append(&mut lsp_items);
-        }
+                .min_by_key(|(len, _)| *len);
 
-        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
-            if actions.is_empty() {
-                editor.set_error("No code actions available");
-                return;
+            match containing {
+                Some((_, name)) => editor.set_status(format!("{severity} in {name}")),
+                None => editor.set_status(format!("{severity} is not inside any symbol")),
             }
-            let mut picker = ui::Menu::new(actions, (), move |editor, action, event| {
-                if event != PromptEvent::Validate {
-                    return;
-                }
+        },
+    );
+}
 
-                // always present here
-                let action = action.unwrap();
-                let Some(language_server) = editor.language_server_by_id(action.language_server_id)
-                else {
-                    editor.set_error("Language Server disappeared");
-                    return;
-                };
-                let offset_encoding = language_server.offset_encoding();
+pub fn pull_diagnostics(cx: &mut Context) {
+    let doc = doc!(cx.editor);
 
-                match &action.lsp_item {
-                    lsp::CodeActionOrCommand::Command(command) => {
-                        log::debug!("code action command: {:?}", command);
-                        execute_lsp_command(editor, action.language_server_id, command.clone());
-                    }
-                    lsp::CodeActionOrCommand::CodeAction(code_action) => {
-                        log::debug!("code action: {:?}", code_action);
-                        // we support lsp "codeAction/resolve" for `edit` and `command` fields
-                        let mut resolved_code_action = None;
-                        if code_action.edit.is_none() || code_action.command.is_none() {
-                            if let Some(future) =
-                                language_server.resolve_code_action(code_action.clone())
-                            {
-                                if let Ok(response) = helix_lsp::block_on(future) {
-                                    if let Ok(code_action) =
-                                        serde_json::from_value::<CodeAction>(response)
-                                    {
-                                        resolved_code_action = Some(code_action);
-                                    }
-                                }
-                            }
-                        }
-                        let resolved_code_action =
-                            resolved_code_action.as_ref().unwrap_or(code_action);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::PullDiagnostics);
+    let server_id = language_server.id();
+    let future = language_server
+        .text_document_diagnostic(doc.identifier(), None)
+        .unwrap();
 
-                        if let Some(ref workspace_edit) = resolved_code_action.edit {
-                            let _ = editor.apply_workspace_edit(offset_encoding, workspace_edit);
-                        }
+    let Some(path) = doc.path() else {
+        cx.editor
+            .set_error("Cannot pull diagnostics for a scratch buffer");
+        return;
+    };
+    let path = path::normalize(path);
 
-                        // if code action provides both edit and command first the edit
-                        // should be applied and then the command
-                        if let Some(command) = &code_action.command {
-                            execute_lsp_command(editor, action.language_server_id, command.clone());
-                        }
-                    }
+    cx.callback(
+        future,
+        move |editor, _compositor, response: lsp::DocumentDiagnosticReportResult| {
+            let report = match response {
+                lsp::DocumentDiagnosticReportResult::Report(report) => report,
+                // We don't set `partial_result_params` in the request so servers
+                // shouldn't answer with a partial result.
+                lsp::DocumentDiagnosticReportResult::Partial(_) => {
+                    log::warn!("ignoring unexpected partial diagnostic report");
+                    return;
                 }
-            });
-            picker.move_down(); // pre-select the first item
-
-            let popup = Popup::new("code-action", picker).with_scrollbar(false);
-
-            compositor.replace_or_push("code-action", popup);
-        };
-
-        Ok(Callback::EditorCompositor(Box::new(call)))
-    });
-}
+            };
+            match report {
+                lsp::DocumentDiagnosticReport::Full(report) => {
+                    merge_pulled_diagnostics(
+                        editor,
+                        server_id,
+                        path,
+                        report.full_document_diagnostic_report.items,
+                    );
+                    merge_related_diagnostic_reports(editor, server_id, report.related_documents);
+                }
+                lsp::DocumentDiagnosticReport::Unchanged(report) => {
+                    // the requested document is unchanged, but the related
+                    // reports can still carry news
+                    merge_related_diagnostic_reports(editor, server_id, report.related_documents);
+                    editor.set_status("Diagnostics unchanged");
+                }
+            }
 
-impl ui::menu::Item for lsp::Command {
-    type Data = ();
-    fn format(&self, _data: &Self::Data) -> Row {
-        self.title.as_str().into()
-    }
+            editor.recompute_workspace_diagnostic_counts();
+        },
+    );
 }
 
-pub fn execute_lsp_command(
+/// Merges diagnostics pulled from `server_id` for one document into the store
+/// the same way published diagnostics are, leaving entries of other language
+/// servers alone. The caller recomputes the workspace counts once all reports
+/// of a response are merged.
+fn merge_pulled_diagnostics(
     editor: &mut Editor,
-    language_server_id: LanguageServerId,
-    cmd: lsp::Command,
+    server_id: LanguageServerId,
+    path: PathBuf,
+    items: Vec<lsp::Diagnostic>,
 ) {
-    // the command is executed on the server and communicated back
-    // to the client asynchronously using workspace edits
-    let future = match editor
-        .language_server_by_id(language_server_id)
-        .and_then(|language_server| language_server.command(cmd))
-    {
-        Some(future) => future,
-        None => {
-            editor.set_error("Language server does not support executing commands");
-            return;
+    let doc_id = editor.document_by_path(&path).map(|doc| doc.id());
+    let diagnostics = items.into_iter().map(|d| (d, server_id));
+    editor.stale_diagnostic_paths.remove(&path);
+    editor.restored_diagnostics.remove(&path);
+    let diagnostics = match editor.diagnostics.entry(path) {
+        std::collections::btree_map::Entry::Occupied(o) => {
+            let current_diagnostics = o.into_mut();
+            current_diagnostics.retain(|(_, lsp_id)| *lsp_id != server_id);
+            current_diagnostics.extend(diagnostics);
+            current_diagnostics
         }
+        std::collections::btree_map::Entry::Vacant(v) => v.insert(diagnostics.collect()),
     };
-
-    tokio::spawn(async move {
-        let res = future.await;
-
-        if let Err(e) = res {
-            log::error!("execute LSP command: {}", e);
-        }
+    // Sort diagnostics first by severity and then by line numbers,
+    // breaking ties between servers by their priority in the
+    // language configuration instead of arrival order.
+    let doc = doc_id.and_then(|doc_id| editor.documents.get(&doc_id));
+    diagnostics.sort_by_cached_key(|(d, server_id)| {
+        let priority = doc.map_or(usize::MAX, |doc| doc.language_server_priority(*server_id));
+        Editor::diagnostic_sort_key(d, priority, *server_id)
     });
-}
 
-#[derive(Debug)]
-pub struct ApplyEditError {
-    pub kind: ApplyEditErrorKind,
-    pub failed_change_idx: usize,
-}
-
-#[derive(Debug)]
-pub enum ApplyEditErrorKind {
-    DocumentChanged,
-    FileNotFound,
-    UnknownURISchema,
-    IoError(std::io::Error),
-    // TODO: check edits before applying and propagate failure
-    // InvalidEdit,
-}
+    let Some(doc_id) = doc_id else { return };
+    if let Some(doc) = editor.documents.get_mut(&doc_id) {
+        let diagnostics =
+            Editor::doc_diagnostics(&editor.language_servers, &editor.diagnostics, doc);
+        doc.replace_diagnostics(diagnostics, &[], Some(server_id));
 
-impl ToString for ApplyEditErrorKind {
-    fn to_string(&self) -> String {
-        match self {
-            ApplyEditErrorKind::DocumentChanged => "document has changed".to_string(),
-            ApplyEditErrorKind::FileNotFound => "file not found".to_string(),
-            ApplyEditErrorKind::UnknownURISchema => "URI schema not supported".to_string(),
-            ApplyEditErrorKind::IoError(err) => err.to_string(),
-        }
+        helix_event::dispatch(helix_view::events::DiagnosticsDidChange {
+            editor,
+            doc: doc_id,
+        });
     }
 }
 
-/// Precondition: `locations` should be non-empty.
-fn goto_impl(
+/// Merges the `relatedDocuments` part of a pull-diagnostics report. Servers
+/// may report diagnostics for documents affected by the requested one — e.g.
+/// sources including an edited header — keyed by the reported URI.
+fn merge_related_diagnostic_reports(
     editor: &mut Editor,
-    compositor: &mut Compositor,
-    locations: Vec<lsp::Location>,
-    offset_encoding: OffsetEncoding,
+    server_id: LanguageServerId,
+    related: Option<HashMap<lsp::Url, lsp::DocumentDiagnosticReportKind>>,
 ) {
-    let cwdir = helix_stdx::env::current_working_dir();
-
-    match locations.as_slice() {
-        [location] => {
-            jump_to_location(editor, location, offset_encoding, Action::Replace);
-        }
-        [] => unreachable!("`locations` should be non-empty for `goto_impl`"),
-        _locations => {
-            let picker = Picker::new(locations, cwdir, move |cx, location, action| {
-                jump_to_location(cx.editor, location, offset_encoding, action)
-            })
-            .with_preview(move |_editor, location| Some(location_to_file_location(location)));
-            compositor.push(Box::new(overlaid(picker)));
+    for (mut uri, report) in related.into_iter().flatten() {
+        normalize_uri(&mut uri);
+        let Ok(path) = uri.to_file_path() else {
+            log::warn!("discarding related diagnostic report for unsupported URI: {uri}");
+            continue;
+        };
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                merge_pulled_diagnostics(editor, server_id, path::normalize(path), report.items);
+            }
+            // nothing is stored from previous pulls to resolve it against
+            lsp::DocumentDiagnosticReportKind::Unchanged(_) => {}
         }
     }
 }
 
-fn to_locations(definitions: Option<lsp::GotoDefinitionResponse>) -> Vec<lsp::Location> {
-    match definitions {
-        Some(lsp::GotoDefinitionResponse::Scalar(location)) => vec![location],
-        Some(lsp::GotoDefinitionResponse::Array(locations)) => locations,
-        Some(lsp::GotoDefinitionResponse::Link(locations)) => locations
-            .into_iter()
-            .map(|location_link| lsp::Location {
-                uri: location_link.target_uri,
-                range: location_link.target_range,
+pub fn workspace_diagnostics_picker(cx: &mut Context) {
+    let picker = workspace_diagnostics_picker_for(cx.editor, None);
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// Builds the workspace diagnostics picker, optionally narrowed to the
+/// diagnostics published by a single language server.
+fn workspace_diagnostics_picker_for(
+    editor: &Editor,
+    server: Option<LanguageServerId>,
+) -> Picker<PickerDiagnostic> {
+    // TODO not yet filtered by LanguageServerFeature, need to do something similar as Document::shown_diagnostics here for all open documents
+    let filter = WorkspaceFilter::new(editor);
+    let diagnostics: BTreeMap<_, _> = match server {
+        Some(server) => editor
+            .diagnostics
+            .iter()
+            .filter_map(|(path, diags)| {
+                let diags: Vec<_> = diags
+                    .iter()
+                    .filter(|(_, language_server_id)| *language_server_id == server)
+                    .cloned()
+                    .collect();
+                (!diags.is_empty()).then(|| (path.clone(), diags))
             })
             .collect(),
-        None => Vec::new(),
+        None => editor.diagnostics.clone(),
+    };
+    let (visible, hidden): (BTreeMap<_, _>, BTreeMap<_, _>) = diagnostics
+        .into_iter()
+        .partition(|(path, _)| !filter.is_hidden(path));
+    let mut visible = flatten_diagnostics(editor, visible);
+    let mut hidden = flatten_diagnostics(editor, hidden);
+    // diagnostics restored from the previous session fill in for files the
+    // servers have not re-checked yet (`lsp.persist-diagnostics`); they have
+    // no live server, so the per-server narrowing cannot include them
+    if server.is_none() {
+        let related_suffix = editor.config().lsp.diagnostic_related_suffix;
+        for (path, diags) in &editor.restored_diagnostics {
+            if editor.diagnostics.contains_key(path) {
+                continue;
+            }
+            let target = if filter.is_hidden(path) {
+                &mut hidden
+            } else {
+                &mut visible
+            };
+            target.extend(diags.iter().map(|(diag, offset_encoding)| {
+                PickerDiagnostic {
+                    path: path.clone(),
+                    diag: diag.clone(),
+                    offset_encoding: *offset_encoding,
+                    stale: true,
+                    related_suffix: related_suffix
+                        .then(|| Document::related_location_suffix(diag))
+                        .flatten(),
+                }
+            }));
+        }
     }
-}
-
-fn goto_single_impl<P, F>(cx: &mut Context, feature: LanguageServerFeature, request_provider: P)
-where
+    diag_picker(
+        editor,
+        visible.clone(),
+        DiagnosticsFormat::ShowSourcePath,
+        "workspace_diagnostics_picker",
+    )
+    .with_hidden_options(Arc::new(AtomicBool::new(false)), move |show_hidden| {
+        let mut options = visible.clone();
+        if show_hidden {
+            options.extend(hidden.iter().cloned());
+        }
+        options
+    })
+}
+
+/// Shows the TODO/FIXME-style diagnostics of the whole workspace in a
+/// picker. Some servers publish informational diagnostics for such comments,
+/// which makes the diagnostics store a ready-made task list; a diagnostic
+/// qualifies when its source or message contains one of `lsp.todo-patterns`.
+pub fn todo_diagnostics_picker(cx: &mut Context) {
+    let patterns = cx.editor.config().lsp.todo_patterns.clone();
+    let matches_pattern = |diag: &lsp::Diagnostic| {
+        patterns.iter().any(|pattern| {
+            diag.message.contains(pattern.as_str())
+                || diag
+                    .source
+                    .as_ref()
+                    .map_or(false, |source| source.contains(pattern.as_str()))
+        })
+    };
+    let diagnostics: BTreeMap<_, _> = cx
+        .editor
+        .diagnostics
+        .iter()
+        .filter_map(|(path, diags)| {
+            let diags: Vec<_> = diags
+                .iter()
+                .filter(|(diag, _)| matches_pattern(diag))
+                .cloned()
+                .collect();
+            (!diags.is_empty()).then(|| (path.clone(), diags))
+        })
+        .collect();
+    let flat_diag = flatten_diagnostics(cx.editor, diagnostics);
+    let picker = diag_picker(
+        cx.editor,
+        flat_diag,
+        DiagnosticsFormat::ShowSourcePath,
+        "todo_diagnostics_picker",
+    );
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// Shows every diagnostic overlapping the cursor in full in a popup:
+/// severity, source, code with its description link and the complete message,
+/// without the end-of-line truncation. `Alt-n`/`Alt-p` cycle when several
+/// diagnostics overlap and the `1`-`9` keys jump to the matching
+/// related-information entry, through the same path the diagnostics picker
+/// uses.
+pub fn show_diagnostics_under_cursor(cx: &mut Context) {
+    let entries = {
+        let (view, doc) = current_ref!(cx.editor);
+        let text = doc.text();
+        let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+        let diagnostics = doc
+            .path()
+            .and_then(|path| cx.editor.diagnostics.get(path))
+            .map(|diagnostics| diagnostics.as_slice())
+            .unwrap_or_default();
+
+        let mut entries = Vec::new();
+        for (diag, server_id) in diagnostics {
+            let Some(language_server) = cx.editor.language_server_by_id(*server_id) else {
+                continue;
+            };
+            let offset_encoding = language_server.offset_encoding();
+            let Some(range) = lsp_range_to_range(text, diag.range, offset_encoding) else {
+                continue;
+            };
+            if cursor < range.from() || cursor > range.to() {
+                continue;
+            }
+            entries.push(diagnostic_entry(diag, language_server.name(), offset_encoding));
+        }
+        entries
+    };
+
+    if entries.is_empty() {
+        cx.editor.set_status("No diagnostics under the cursor");
+        return;
+    }
+
+    cx.jobs.callback(async move {
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            let info = ui::lsp::DiagnosticInfo::new(entries, editor.syn_loader.clone());
+            let popup = Popup::new(ui::lsp::DiagnosticInfo::ID, info).auto_close(true);
+            compositor.replace_or_push(ui::lsp::DiagnosticInfo::ID, popup);
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+/// Formats one diagnostic as the markdown rendered by
+/// [show_diagnostics_under_cursor].
+fn diagnostic_entry(
+    diag: &lsp::Diagnostic,
+    server_name: &str,
+    offset_encoding: OffsetEncoding,
+) -> ui::lsp::DiagnosticEntry {
+    let severity = match diag.severity {
+        Some(DiagnosticSeverity::ERROR) => "Error",
+        Some(DiagnosticSeverity::WARNING) => "Warning",
+        Some(DiagnosticSeverity::INFORMATION) => "Info",
+        Some(DiagnosticSeverity::HINT) => "Hint",
+        _ => "Diagnostic",
+    };
+    let mut contents = format!("**{severity}**");
+    let source = diag.source.as_deref().unwrap_or(server_name);
+    write!(contents, " from {source}").unwrap();
+    if let Some(code) = &diag.code {
+        let code = match code {
+            NumberOrString::Number(code) => code.to_string(),
+            NumberOrString::String(code) => code.clone(),
+        };
+        match &diag.code_description {
+            Some(description) => {
+                write!(contents, " \\[[{code}]({})\\]", description.href).unwrap()
+            }
+            None => write!(contents, " \\[{code}\\]").unwrap(),
+        }
+    }
+    write!(contents, "\n\n{}", diag.message).unwrap();
+
+    let related = diag.related_information.clone().unwrap_or_default();
+    if !related.is_empty() {
+        contents.push_str("\n\nRelated:\n\n");
+        for (index, info) in related.iter().enumerate() {
+            let position = info.location.range.start;
+            let path = match info.location.uri.to_file_path() {
+                Ok(path) => path::get_relative_path(&path).to_string_lossy().into_owned(),
+                Err(_) => info.location.uri.to_string(),
+            };
+            writeln!(
+                contents,
+                "{}. `{}:{}:{}` {}",
+                index + 1,
+                path,
+                position.line + 1,
+                position.character + 1,
+                info.message
+            )
+            .unwrap();
+        }
+    }
+
+    ui::lsp::DiagnosticEntry {
+        contents,
+        related: related.into_iter().map(|info| info.location).collect(),
+        offset_encoding,
+    }
+}
+
+/// Lists the related-information entries ("moved here", "borrowed here", …)
+/// of the diagnostics under the cursor in a picker and jumps to the chosen
+/// one — the breadcrumb trail of a complex borrow-check error laid out at
+/// once instead of one `1`-`9` jump at a time.
+pub fn diagnostic_related_picker(cx: &mut Context) {
+    #[derive(Clone)]
+    struct RelatedInformationItem {
+        info: lsp::DiagnosticRelatedInformation,
+        offset_encoding: OffsetEncoding,
+    }
+
+    impl ui::menu::Item for RelatedInformationItem {
+        /// Current working directory.
+        type Data = PathBuf;
+
+        fn format(&self, cwdir: &Self::Data) -> Row {
+            format!(
+                "{}: {}",
+                format_location(&self.info.location, cwdir),
+                self.info.message
+            )
+            .into()
+        }
+    }
+
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text();
+    let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+    let diagnostics = doc
+        .path()
+        .and_then(|path| cx.editor.diagnostics.get(path))
+        .map(|diagnostics| diagnostics.as_slice())
+        .unwrap_or_default();
+
+    let mut items = Vec::new();
+    for (diag, server_id) in diagnostics {
+        let Some(language_server) = cx.editor.language_server_by_id(*server_id) else {
+            continue;
+        };
+        let offset_encoding = language_server.offset_encoding();
+        let Some(range) = lsp_range_to_range(text, diag.range, offset_encoding) else {
+            continue;
+        };
+        if cursor < range.from() || cursor > range.to() {
+            continue;
+        }
+        items.extend(
+            diag.related_information
+                .iter()
+                .flatten()
+                .map(|info| RelatedInformationItem {
+                    info: info.clone(),
+                    offset_encoding,
+                }),
+        );
+    }
+
+    if items.is_empty() {
+        cx.editor
+            .set_status("No related information for the diagnostics under the cursor");
+        return;
+    }
+
+    let picker = Picker::new(
+        items,
+        helix_stdx::env::current_working_dir(),
+        |cx, item, action| {
+            let Ok(path) = item.info.location.uri.to_file_path() else {
+                return;
+            };
+            jump_to_position(
+                cx.editor,
+                &path,
+                item.info.location.range,
+                item.offset_encoding,
+                action,
+                "diagnostic_related_picker",
+            );
+        },
+    )
+    .with_preview(|_editor, item| location_to_file_location(&item.info.location))
+    .truncate_start(false);
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// Jumps straight to the first related-information location of the
+/// diagnostic under the cursor — for rustc borrow errors the "borrow later
+/// used here" spot — without going through [diagnostic_related_picker].
+/// Entries whose URI does not resolve to a file path are skipped.
+pub fn goto_first_related(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let text = doc.text();
+    let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+    let diagnostics = doc
+        .path()
+        .and_then(|path| cx.editor.diagnostics.get(path))
+        .map(|diagnostics| diagnostics.as_slice())
+        .unwrap_or_default();
+
+    let mut target = None;
+    'diagnostics: for (diag, server_id) in diagnostics {
+        let Some(language_server) = cx.editor.language_server_by_id(*server_id) else {
+            continue;
+        };
+        let offset_encoding = language_server.offset_encoding();
+        let Some(range) = lsp_range_to_range(text, diag.range, offset_encoding) else {
+            continue;
+        };
+        if cursor < range.from() || cursor > range.to() {
+            continue;
+        }
+        for info in diag.related_information.iter().flatten() {
+            if let Ok(path) = info.location.uri.to_file_path() {
+                target = Some((path, info.location.range, offset_encoding));
+                break 'diagnostics;
+            }
+        }
+    }
+
+    let Some((path, range, offset_encoding)) = target else {
+        cx.editor
+            .set_status("No related information for the diagnostic under the cursor");
+        return;
+    };
+    jump_to_position(
+        cx.editor,
+        &path,
+        range,
+        offset_encoding,
+        Action::Replace,
+        "goto_first_related",
+    );
+}
+
+/// Narrows the workspace diagnostics picker to one language server, chosen
+/// from a picker of the active servers. Useful when several servers publish
+/// overlapping diagnostics and only one tool's output matters.
+pub fn server_diagnostics_picker(cx: &mut Context) {
+    struct ServerItem {
+        id: LanguageServerId,
+        name: String,
+    }
+
+    impl ui::menu::Item for ServerItem {
+        type Data = ();
+        fn format(&self, _data: &Self::Data) -> Row {
+            self.name.clone().into()
+        }
+    }
+
+    let servers: Vec<ServerItem> = cx
+        .editor
+        .language_servers
+        .iter_clients()
+        .filter(|client| client.is_initialized())
+        .map(|client| ServerItem {
+            id: client.id(),
+            name: client.name().to_string(),
+        })
+        .collect();
+
+    match servers.as_slice() {
+        [] => cx.editor.set_status("No active language servers"),
+        [server] => {
+            let picker = workspace_diagnostics_picker_for(cx.editor, Some(server.id));
+            cx.push_layer(Box::new(overlaid(picker)));
+        }
+        _ => {
+            let picker = Picker::new(servers, (), |cx, server, _action| {
+                let server = server.id;
+                cx.jobs.callback(async move {
+                    let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+                        let picker = workspace_diagnostics_picker_for(editor, Some(server));
+                        compositor.push(Box::new(overlaid(picker)));
+                    };
+                    Ok(Callback::EditorCompositor(Box::new(call)))
+                });
+            });
+            cx.push_layer(Box::new(overlaid(picker)));
+        }
+    }
+}
+
+struct DiagnosticCodeItem {
+    source: Option<String>,
+    code: String,
+    count: usize,
+}
+
+impl ui::menu::Item for DiagnosticCodeItem {
+    type Data = ();
+    fn format(&self, _data: &Self::Data) -> Row {
+        format!("{}: {}", self.code, self.count).into()
+    }
+}
+
+fn diagnostic_code_string(code: &lsp::NumberOrString) -> String {
+    match code {
+        lsp::NumberOrString::Number(n) => n.to_string(),
+        lsp::NumberOrString::String(s) => s.clone(),
+    }
+}
+
+pub fn diagnostics_by_code(cx: &mut Context) {
+    // Group the workspace diagnostics by (source, code) so one lint can be
+    // cleaned up at a time; diagnostics without a code aren't addressable
+    // this way and are skipped.
+    let mut counts: BTreeMap<(Option<String>, String), usize> = BTreeMap::new();
+    for diags in cx.editor.diagnostics.values() {
+        for (diag, _) in diags {
+            let Some(code) = &diag.code else { continue };
+            *counts
+                .entry((diag.source.clone(), diagnostic_code_string(code)))
+                .or_default() += 1;
+        }
+    }
+
+    if counts.is_empty() {
+        cx.editor
+            .set_status("No diagnostics with a code in the workspace");
+        return;
+    }
+
+    let items: Vec<_> = counts
+        .into_iter()
+        .map(|((source, code), count)| DiagnosticCodeItem {
+            source,
+            code,
+            count,
+        })
+        .collect();
+
+    let picker = Picker::new(items, (), |cx, item, _action| {
+        let diagnostics: BTreeMap<_, _> = cx
+            .editor
+            .diagnostics
+            .iter()
+            .filter_map(|(path, diags)| {
+                let diags: Vec<_> = diags
+                    .iter()
+                    .filter(|(diag, _)| {
+                        diag.source == item.source
+                            && diag
+                                .code
+                                .as_ref()
+                                .is_some_and(|code| diagnostic_code_string(code) == item.code)
+                    })
+                    .cloned()
+                    .collect();
+                (!diags.is_empty()).then(|| (path.clone(), diags))
+            })
+            .collect();
+        crate::job::dispatch_blocking(move |editor, compositor| {
+            let flat_diag = flatten_diagnostics(editor, diagnostics);
+            let picker = diag_picker(
+                editor,
+                flat_diag,
+                DiagnosticsFormat::ShowSourcePath,
+                "diagnostics_by_code",
+            );
+            compositor.push(Box::new(overlaid(picker)));
+        });
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+struct CodeActionOrCommandItem {
+    /// The action exactly as the server sent it. `codeAction/resolve` (and
+    /// command execution) must receive this value unmodified: servers key the
+    /// resolve off the opaque `data` field and can mis-resolve or reject an
+    /// action that was altered client-side.
+    lsp_item: lsp::CodeActionOrCommand,
+    language_server_id: LanguageServerId,
+    /// The menu label, precomputed so that display tweaks never have to touch
+    /// [`Self::lsp_item`].
+    title: String,
+}
+
+impl CodeActionOrCommandItem {
+    fn new(lsp_item: lsp::CodeActionOrCommand, language_server_id: LanguageServerId) -> Self {
+        let title = match &lsp_item {
+            lsp::CodeActionOrCommand::CodeAction(action) => action.title.clone(),
+            lsp::CodeActionOrCommand::Command(command) => command.title.clone(),
+        };
+        Self {
+            lsp_item,
+            language_server_id,
+            title,
+        }
+    }
+}
+
+impl ui::menu::Item for CodeActionOrCommandItem {
+    type Data = ();
+    fn format(&self, _data: &Self::Data) -> Row {
+        // point out which diagnostic a quick fix addresses — its code when
+        // there is one, the first words of its message otherwise — so that
+        // similar-titled fixes for different errors can be told apart
+        if let CodeActionOrCommand::CodeAction(CodeAction {
+            diagnostics: Some(diagnostics),
+            ..
+        }) = &self.lsp_item
+        {
+            if let Some(diagnostic) = diagnostics.first() {
+                let label = match diagnostic.code.as_ref() {
+                    Some(NumberOrString::Number(code)) => code.to_string(),
+                    Some(NumberOrString::String(code)) => code.clone(),
+                    None => {
+                        let mut words = diagnostic.message.split_whitespace();
+                        let label = words.by_ref().take(4).collect::<Vec<_>>().join(" ");
+                        if words.next().is_some() {
+                            format!("{label}…")
+                        } else {
+                            label
+                        }
+                    }
+                };
+                if !label.is_empty() {
+                    return Spans::from(vec![
+                        Span::raw(self.title.as_str()),
+                        Span::styled(
+                            format!(" ({label})"),
+                            Style::default().add_modifier(Modifier::DIM),
+                        ),
+                    ])
+                    .into();
+                }
+            }
+        }
+        self.title.as_str().into()
+    }
+}
+
+/// Determines the category of the `CodeAction` using the `CodeAction::kind` field.
+/// Returns a number that represent these categories.
+/// Categories with a lower number should be displayed first.
+///
+///
+/// While the `kind` field is defined as open ended in the LSP spec (any value may be used)
+/// in practice a closed set of common values (mostly suggested in the LSP spec) are used.
+/// VSCode displays each of these categories separately (separated by a heading in the codeactions picker)
+/// to make them easier to navigate. Helix does not display these  headings to the user.
+/// However it does sort code actions by their categories to achieve the same order as the VScode picker,
+/// just without the headings.
+///
+/// The order used here is modeled after the [vscode sourcecode](https://github.com/microsoft/vscode/blob/eaec601dd69aeb4abb63b9601a6f44308c8d8c6e/src/vs/editor/contrib/codeAction/browser/codeActionWidget.ts>)
+fn action_category(action: &CodeActionOrCommand) -> u32 {
+    if let CodeActionOrCommand::CodeAction(CodeAction {
+        kind: Some(kind), ..
+    }) = action
+    {
+        let mut components = kind.as_str().split('.');
+        match components.next() {
+            Some("quickfix") => 0,
+            Some("refactor") => match components.next() {
+                Some("extract") => 1,
+                Some("inline") => 2,
+                Some("rewrite") => 3,
+                Some("move") => 4,
+                Some("surround") => 5,
+                _ => 7,
+            },
+            Some("source") => 6,
+            _ => 7,
+        }
+    } else {
+        7
+    }
+}
+
+fn action_preferred(action: &CodeActionOrCommand) -> bool {
+    matches!(
+        action,
+        CodeActionOrCommand::CodeAction(CodeAction {
+            is_preferred: Some(true),
+            ..
+        })
+    )
+}
+
+fn action_fixes_diagnostics(action: &CodeActionOrCommand) -> bool {
+    matches!(
+        action,
+        CodeActionOrCommand::CodeAction(CodeAction {
+            diagnostics: Some(diagnostics),
+            ..
+        }) if !diagnostics.is_empty()
+    )
+}
+
+/// Whether a pin from `lsp.code-action-favorites` matches an action: the kinds
+/// must be equal (a pin without one matches any kind) and the pin's title must
+/// be a prefix of the action's title, so parameterized titles ("Import `foo`
+/// from module …") still match a pin taken from another instance.
+fn pin_matches(pin: &CodeActionPin, item: &CodeActionOrCommandItem) -> bool {
+    if let Some(pin_kind) = &pin.kind {
+        let kind = match &item.lsp_item {
+            CodeActionOrCommand::CodeAction(action) => {
+                action.kind.as_ref().map(|kind| kind.as_str())
+            }
+            CodeActionOrCommand::Command(_) => None,
+        };
+        if kind != Some(pin_kind.as_str()) {
+            return false;
+        }
+    }
+    item.title.starts_with(&pin.title)
+}
+
+/// Derives the pin for an action. The title pattern is cut off before the
+/// first quoted fragment so that pinning "Import `foo` from module bar" covers
+/// every import action, not just the one for `foo`.
+fn pin_for_action(item: &CodeActionOrCommandItem) -> CodeActionPin {
+    let kind = match &item.lsp_item {
+        CodeActionOrCommand::CodeAction(action) => {
+            action.kind.as_ref().map(|kind| kind.as_str().to_string())
+        }
+        CodeActionOrCommand::Command(_) => None,
+    };
+    let title = match item.title.find(['`', '"', '\'']) {
+        Some(index) => item.title[..index].to_string(),
+        None => item.title.clone(),
+    };
+    CodeActionPin { kind, title }
+}
+
+/// Adds a pin for the highlighted action to `lsp.code-action-favorites`, or
+/// removes the pin that currently matches it. The updated list is applied to
+/// the running session and written to the project's `.helix/config.toml` so
+/// the favorites stay scoped to the workspace.
+fn toggle_code_action_pin(editor: &mut Editor, item: &CodeActionOrCommandItem) {
+    let mut config = editor.config().clone();
+    let favorites = &mut config.lsp.code_action_favorites;
+    match favorites.iter().position(|pin| pin_matches(pin, item)) {
+        Some(index) => {
+            let pin = favorites.remove(index);
+            editor.set_status(format!("Unpinned \"{}\"", pin.title.trim_end()));
+        }
+        None => {
+            let pin = pin_for_action(item);
+            editor.set_status(format!("Pinned \"{}\"", pin.title.trim_end()));
+            favorites.push(pin);
+        }
+    }
+    if let Err(err) = persist_code_action_favorites(&config.lsp.code_action_favorites) {
+        editor.set_error(format!("Could not update the workspace config: {err}"));
+        return;
+    }
+    if editor
+        .config_events
+        .0
+        .send(ConfigEvent::Update(Box::new(config)))
+        .is_err()
+    {
+        editor.set_error("Could not apply the updated config");
+    }
+}
+
+/// Writes the favorites back to the workspace `.helix/config.toml`, leaving
+/// everything else in the file untouched.
+fn persist_code_action_favorites(favorites: &[CodeActionPin]) -> anyhow::Result<()> {
+    let path = helix_loader::workspace_config_file();
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    let mut root: toml::Table = text.parse()?;
+    let lsp = root
+        .entry("editor")
+        .or_insert_with(|| toml::Table::new().into())
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("`editor` is not a table"))?
+        .entry("lsp")
+        .or_insert_with(|| toml::Table::new().into())
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("`editor.lsp` is not a table"))?;
+    lsp.insert(
+        "code-action-favorites".to_string(),
+        toml::Value::try_from(favorites)?,
+    );
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, toml::to_string(&root)?)?;
+    Ok(())
+}
+
+pub fn code_action(cx: &mut Context) {
+    if !gate_on_server_ready(cx, LanguageServerFeature::CodeAction, "code_action") {
+        return;
+    }
+    code_action_impl(cx, false)
+}
+
+/// Like [code_action] but pre-filters the menu to the actions matching a pin
+/// in `lsp.code-action-favorites`, falling back to the full menu when none
+/// match.
+pub fn code_action_favorites(cx: &mut Context) {
+    if !gate_on_server_ready(cx, LanguageServerFeature::CodeAction, "code_action_favorites") {
+        return;
+    }
+    code_action_impl(cx, true)
+}
+
+/// Builds the `textDocument/codeAction` request for `selection_range` on every
+/// attached server that supports them, including the response filtering and
+/// sorting; shared by [code_action] and the debounced selection-change query
+/// ([query_code_actions]).
+fn code_action_requests(
+    doc: &Document,
+    selection_range: helix_core::Range,
+    sort: CodeActionSort,
+) -> FuturesOrdered<
+    impl Future<
+        Output = (
+            LanguageServerId,
+            Result<Vec<CodeActionOrCommandItem>, helix_lsp::Error>,
+        ),
+    >,
+> {
+    let mut seen_language_servers = HashSet::new();
+
+    doc
+        .language_servers_with_feature(LanguageServerFeature::CodeAction)
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+        // TODO this should probably already been filtered in something like "language_servers_with_feature"
+        .filter_map(|language_server| {
+            let offset_encoding = language_server.offset_encoding();
+            let language_server_id = language_server.id();
+            let range = lsp_position::range_to_lsp_range(doc, selection_range, language_server);
+            // Filter and convert overlapping diagnostics
+            let code_action_context = lsp::CodeActionContext {
+                diagnostics: doc
+                    .diagnostics()
+                    .iter()
+                    .filter(|&diag| {
+                        selection_range
+                            .overlaps(&helix_core::Range::new(diag.range.start, diag.range.end))
+                    })
+                    .map(|diag| diagnostic_to_lsp_diagnostic(doc.text(), diag, offset_encoding))
+                    .collect(),
+                only: None,
+                trigger_kind: Some(CodeActionTriggerKind::INVOKED),
+            };
+            let code_action_request =
+                language_server.code_actions(doc.identifier(), range, code_action_context)?;
+            Some((code_action_request, language_server_id))
+        })
+        .map(|(request, ls_id)| async move {
+            let result: Result<_, helix_lsp::Error> = async move {
+                let json = request.await?;
+                let response: Option<lsp::CodeActionResponse> = serde_json::from_value(json)?;
+                let mut actions = match response {
+                    Some(a) => a,
+                    None => return Ok(Vec::new()),
+                };
+
+                // remove disabled code actions
+                actions.retain(|action| {
+                    matches!(
+                        action,
+                        CodeActionOrCommand::Command(_)
+                            | CodeActionOrCommand::CodeAction(CodeAction { disabled: None, .. })
+                    )
+                });
+
+                // Sort codeactions into a useful order. This behaviour is only partially described in the LSP spec.
+                // Many details are modeled after vscode because language servers are usually tested against it.
+                // VScode sorts the codeaction two times:
+                //
+                // First the codeactions that fix some diagnostics are moved to the front.
+                // If both codeactions fix some diagnostics (or both fix none) the codeaction
+                // that is marked with `is_preferred` is shown first. The codeactions are then shown in separate
+                // submenus that only contain a certain category (see `action_category`) of actions.
+                //
+                // Below this done in in a single sorting step
+                //
+                // `lsp.code-action-sort = "server"` skips the sort for
+                // servers whose own ordering is more meaningful than the
+                // heuristic (e.g. eslint)
+                if sort == CodeActionSort::Helix {
+                    actions.sort_by(|action1, action2| {
+                        // sort actions by category
+                        let order = action_category(action1).cmp(&action_category(action2));
+                        if order != Ordering::Equal {
+                            return order;
+                        }
+                        // within the categories sort by relevancy.
+                        // Modeled after the `codeActionsComparator` function in vscode:
+                        // https://github.com/microsoft/vscode/blob/eaec601dd69aeb4abb63b9601a6f44308c8d8c6e/src/vs/editor/contrib/codeAction/browser/codeAction.ts
+
+                        // if one code action fixes a diagnostic but the other one doesn't show it first
+                        let order = action_fixes_diagnostics(action1)
+                            .cmp(&action_fixes_diagnostics(action2))
+                            .reverse();
+                        if order != Ordering::Equal {
+                            return order;
+                        }
+
+                        // if one of the codeactions is marked as preferred show it first
+                        // otherwise keep the original LSP sorting
+                        action_preferred(action1)
+                            .cmp(&action_preferred(action2))
+                            .reverse()
+                    });
+                }
+
+                Ok(actions
+                    .into_iter()
+                    .map(|lsp_item| CodeActionOrCommandItem::new(lsp_item, ls_id))
+                    .collect())
+            }
+            .await;
+            (ls_id, result)
+        })
+        .collect()
+}
+
+/// Runs the query triggered by `Handlers::trigger_code_actions` once the
+/// debounce has settled and stores the result in `Editor::cached_code_actions`.
+/// Failures are not surfaced: the query is speculative, nothing the user asked
+/// for is lost.
+pub(crate) fn query_code_actions(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    view_id: ViewId,
+    range: helix_core::Range,
+) {
+    let sort = editor.config().lsp.code_action_sort;
+    let Some(doc) = editor.documents.get(&doc_id) else {
+        return;
+    };
+    let version = doc.version();
+    if editor.cached_code_actions.as_ref().map_or(false, |cached| {
+        cached.doc == doc_id && cached.version == version && cached.range == range
+    }) {
+        return;
+    }
+    let mut futures = code_action_requests(doc, range, sort);
+    if futures.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut actions = Vec::new();
+        while let Some((_, result)) = futures.next().await {
+            // a failing server only loses its own results
+            match result {
+                Ok(mut items) => actions.append(&mut items),
+                Err(err) => log::warn!("code action request failed: {err}"),
+            }
+        }
+        crate::job::dispatch(move |editor, _| {
+            // drop a result the selection has outgrown while the request was
+            // in flight; the next selection change re-queries
+            let current = editor
+                .documents
+                .get(&doc_id)
+                .filter(|doc| doc.version() == version)
+                .and_then(|doc| doc.selections().get(&view_id))
+                .map_or(false, |selection| selection.primary() == range);
+            if !current {
+                return;
+            }
+            editor.cached_code_actions = Some(CachedCodeActions {
+                doc: doc_id,
+                version,
+                range,
+                actions: actions
+                    .into_iter()
+                    .map(|item| (item.lsp_item, item.language_server_id))
+                    .collect(),
+            });
+        })
+        .await;
+    });
+}
+
+fn code_action_impl(cx: &mut Context, favorites_only: bool) {
+    let sort = cx.editor.config().lsp.code_action_sort;
+    let (view, doc) = current!(cx.editor);
+
+    let doc_id = doc.id();
+    let version = doc.version();
+    let selection_range = doc.selection(view.id).primary();
+
+    // remember where the first diagnostic included in the request context
+    // sits, so the menu can open next to it instead of over it
+    let diagnostic_anchor = doc
+        .diagnostics()
+        .iter()
+        .find(|diag| {
+            selection_range.overlaps(&helix_core::Range::new(diag.range.start, diag.range.end))
+        })
+        .map(|diag| diag.range.start);
+
+    let mut futures = code_action_requests(doc, selection_range, sort);
+
+    if futures.is_empty() {
+        cx.editor
+            .set_error("No configured language server supports code actions");
+        return;
+    }
+
+    // give immediate feedback, slow servers can leave this pending for a while
+    let total = futures.len();
+    cx.editor.set_status(format!(
+        "Requesting code actions from {total} language server(s)…"
+    ));
+
+    cx.jobs.callback(async move {
+        let mut actions = Vec::new();
+        let mut responded = 0usize;
+        let mut last_error = None;
+        // a failing server (e.g. one that exited mid-request) only discards
+        // its own results, the surviving servers' actions are still shown
+        while let Some((ls_id, result)) = futures.next().await {
+            match result {
+                Ok(mut lsp_items) => actions.append(&mut lsp_items),
+                Err(err) => {
+                    last_error = Some(discard_failed_server(ls_id, err, "code action").await);
+                }
+            }
+            responded += 1;
+            if responded < total {
+                crate::job::dispatch(move |editor, _| {
+                    editor.set_status(format!(
+                        "{responded}/{total} language servers responded…"
+                    ));
+                })
+                .await;
+            }
+        }
+
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            editor.clear_status();
+            if actions.is_empty() {
+                match last_error {
+                    // every server failed: report that instead of the
+                    // misleading "no actions"
+                    Some(error) => editor.set_error(error),
+                    None => editor.set_error("No code actions available"),
+                }
+                return;
+            }
+            let mut actions = actions;
+            if favorites_only {
+                let favorites = editor.config().lsp.code_action_favorites.clone();
+                if actions
+                    .iter()
+                    .any(|item| favorites.iter().any(|pin| pin_matches(pin, item)))
+                {
+                    actions.retain(|item| favorites.iter().any(|pin| pin_matches(pin, item)));
+                } else if favorites.is_empty() {
+                    editor.set_status("No code actions pinned yet (`A-p` in the menu), showing all");
+                } else {
+                    editor.set_status("No pinned code action matched, showing all");
+                }
+            }
+            if actions.len() == 1 && editor.config().lsp.code_action_auto_apply_single {
+                if document_changed_since(editor, doc_id, version) {
+                    editor.set_error("Document changed since the code actions were requested");
+                    return;
+                }
+                apply_code_action(editor, &actions[0]);
+                return;
+            }
+            // The popup's anchor is the screen position of that diagnostic;
+            // Popup itself flips above/below the anchor as space allows, so
+            // the diagnostic's line stays visible either way. Falls back to
+            // the cursor when configured to, when the diagnostic scrolled out
+            // of view or when the focus moved to another document meanwhile.
+            let anchor = match editor.config().lsp.code_action_anchor {
+                CodeActionAnchor::Diagnostic => {
+                    let (view, doc) = current_ref!(editor);
+                    diagnostic_anchor
+                        .filter(|_| doc.id() == doc_id)
+                        .and_then(|pos| {
+                            let inner = view.inner_area(doc);
+                            let pos = view.screen_coords_at_pos(
+                                doc,
+                                doc.text().slice(..),
+                                pos.min(doc.text().len_chars()),
+                            )?;
+                            Some(helix_core::Position::new(
+                                pos.row + inner.y as usize,
+                                pos.col + inner.x as usize,
+                            ))
+                        })
+                }
+                CodeActionAnchor::Cursor => None,
+            };
+            compositor.replace_or_push(
+                "code-action",
+                code_action_menu(actions, doc_id, version).position(anchor),
+            );
+        };
+
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+fn code_action_menu(
+    actions: Vec<CodeActionOrCommandItem>,
+    doc_id: DocumentId,
+    version: i32,
+) -> Popup<ui::Menu<CodeActionOrCommandItem>> {
+    let mut picker = ui::Menu::new(actions, (), move |editor, action, event| {
+        if event != PromptEvent::Validate {
+            return;
+        }
+
+        // The positions in the edits and command arguments reference the
+        // document as it was when the actions were requested; executing them
+        // against a newer revision would act on the wrong spots.
+        if document_changed_since(editor, doc_id, version) {
+            editor.set_error("Document changed since the code actions were requested");
+            return;
+        }
+
+        // always present here
+        apply_code_action(editor, action.unwrap());
+    })
+    .with_key(alt!('p'), |editor, action| {
+        if let Some(action) = action {
+            toggle_code_action_pin(editor, action);
+        }
+    });
+    picker.move_down(); // pre-select the first item
+
+    Popup::new("code-action", picker).with_scrollbar(false)
+}
+
+/// See [code_action_menu]: code actions (and their command arguments) are tied
+/// to the document version they were requested at.
+fn document_changed_since(editor: &Editor, doc_id: DocumentId, version: i32) -> bool {
+    editor
+        .documents
+        .get(&doc_id)
+        .map_or(true, |doc| doc.version() != version)
+}
+
+fn apply_code_action(editor: &mut Editor, action: &CodeActionOrCommandItem) -> bool {
+    let Some(language_server) = editor.language_server_by_id(action.language_server_id) else {
+        editor.set_error("Language Server disappeared");
+        return false;
+    };
+    let offset_encoding = language_server.offset_encoding();
+
+    match &action.lsp_item {
+        lsp::CodeActionOrCommand::Command(command) => {
+            log::debug!("code action command: {:?}", command);
+            execute_lsp_command(editor, action.language_server_id, command.clone());
+            true
+        }
+        lsp::CodeActionOrCommand::CodeAction(code_action) => {
+            log::debug!("code action: {:?}", code_action);
+            // we support lsp "codeAction/resolve" for `edit` and `command` fields.
+            // The request must carry `code_action` exactly as received, see
+            // [`CodeActionOrCommandItem::lsp_item`].
+            let mut resolved_code_action = None;
+            if code_action.edit.is_none() || code_action.command.is_none() {
+                if let Some(future) = language_server.resolve_code_action(code_action.clone()) {
+                    if let Ok(response) = helix_lsp::block_on(future) {
+                        if let Ok(code_action) = serde_json::from_value::<CodeAction>(response) {
+                            resolved_code_action = Some(code_action);
+                        }
+                    }
+                }
+            }
+            let resolved_code_action = resolved_code_action.as_ref().unwrap_or(code_action);
+
+            let mut applied = true;
+            if let Some(ref workspace_edit) = resolved_code_action.edit {
+                applied = editor
+                    .apply_workspace_edit(offset_encoding, workspace_edit)
+                    .is_ok();
+            }
+
+            // if code action provides both edit and command first the edit
+            // should be applied and then the command
+            if let Some(command) = &code_action.command {
+                execute_lsp_command(editor, action.language_server_id, command.clone());
+            }
+            applied
+        }
+    }
+}
+
+pub fn quick_fix_under_cursor(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let doc_id = doc.id();
+    let version = doc.version();
+    let text = doc.text();
+    let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+    let Some(diag) = doc
+        .diagnostics()
+        .iter()
+        .find(|diag| diag.range.contains(cursor) || diag.range.end == cursor)
+        .cloned()
+    else {
+        cx.editor.set_error("No diagnostic under cursor");
+        return;
+    };
+
+    // scope the request to the server that produced the diagnostic, other
+    // servers cannot know how to fix it
+    let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::CodeAction)
+        .find(|language_server| language_server.id() == diag.provider)
+    else {
+        cx.editor
+            .set_error("The diagnostic's language server does not support code actions");
+        return;
+    };
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+    let range = range_to_lsp_range(
+        text,
+        helix_core::Range::new(diag.range.start, diag.range.end),
+        offset_encoding,
+    );
+    let code_action_context = lsp::CodeActionContext {
+        diagnostics: vec![diagnostic_to_lsp_diagnostic(text, &diag, offset_encoding)],
+        only: Some(vec![lsp::CodeActionKind::QUICKFIX]),
+        trigger_kind: Some(CodeActionTriggerKind::INVOKED),
+    };
+    let future = language_server
+        .code_actions(doc.identifier(), range, code_action_context)
+        .unwrap();
+
+    cx.callback(
+        future,
+        move |editor, compositor, response: Option<lsp::CodeActionResponse>| {
+            let mut actions: Vec<_> = response
+                .unwrap_or_default()
+                .into_iter()
+                // Not every server honors `only`, so additionally keep only
+                // enabled actions that fix some diagnostic.
+                .filter(|action| {
+                    matches!(
+                        action,
+                        CodeActionOrCommand::Command(_)
+                            | CodeActionOrCommand::CodeAction(CodeAction { disabled: None, .. })
+                    ) && action_fixes_diagnostics(action)
+                })
+                .map(|lsp_item| CodeActionOrCommandItem::new(lsp_item, language_server_id))
+                .collect();
+
+            if document_changed_since(editor, doc_id, version) {
+                editor.set_error("Document changed since the code actions were requested");
+                return;
+            }
+
+            match actions.len() {
+                0 => editor.set_error("No quick fix available for this diagnostic"),
+                // a single quick fix is applied without prompting
+                1 => {
+                    apply_code_action(editor, &actions.remove(0));
+                }
+                _ => compositor
+                    .replace_or_push("code-action", code_action_menu(actions, doc_id, version)),
+            }
+        },
+    );
+}
+
+/// Returns the edits of `action` if it is a plain replacement of text inside
+/// `range` in the document `uri`: a code action without a command whose
+/// workspace edit only touches that document and stays within the diagnostic.
+/// Such a fix can be applied directly, skipping `codeAction/resolve`.
+fn diagnostic_replacement_edits(
+    action: &CodeActionOrCommand,
+    uri: &lsp::Url,
+    range: lsp::Range,
+) -> Option<Vec<lsp::TextEdit>> {
+    let CodeActionOrCommand::CodeAction(CodeAction {
+        edit: Some(workspace_edit),
+        command: None,
+        ..
+    }) = action
+    else {
+        return None;
+    };
+
+    let edits: Vec<_> = match (&workspace_edit.changes, &workspace_edit.document_changes) {
+        (Some(changes), None) if changes.len() == 1 => {
+            let (edit_uri, edits) = changes.iter().next().unwrap();
+            if edit_uri != uri {
+                return None;
+            }
+            edits.clone()
+        }
+        (None, Some(lsp::DocumentChanges::Edits(document_edits))) => {
+            let [document_edit] = document_edits.as_slice() else {
+                return None;
+            };
+            if document_edit.text_document.uri != *uri {
+                return None;
+            }
+            document_edit
+                .edits
+                .iter()
+                .map(|edit| match edit {
+                    lsp::OneOf::Left(edit) => Some(edit.clone()),
+                    // annotated edits may ask for confirmation, don't shortcut them
+                    lsp::OneOf::Right(_) => None,
+                })
+                .collect::<Option<_>>()?
+        }
+        _ => return None,
+    };
+
+    (!edits.is_empty()
+        && edits
+            .iter()
+            .all(|edit| edit.range.start >= range.start && edit.range.end <= range.end))
+    .then_some(edits)
+}
+
+/// Like [`quick_fix_under_cursor`], but optimized for "replace `==` with
+/// `===`"-style fixes: when the server offers a single quick fix that is a
+/// pure text edit within the diagnostic's range, its edits are applied
+/// directly without a menu or a `codeAction/resolve` round-trip. Anything
+/// more involved falls back to the regular code action path.
+pub fn apply_diagnostic_replacement(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let doc_id = doc.id();
+    let version = doc.version();
+    let uri = doc.identifier().uri;
+    let text = doc.text();
+    let cursor = doc.selection(view.id).primary().cursor(text.slice(..));
+    let Some(diag) = doc
+        .diagnostics()
+        .iter()
+        .find(|diag| diag.range.contains(cursor) || diag.range.end == cursor)
+        .cloned()
+    else {
+        cx.editor.set_error("No diagnostic under cursor");
+        return;
+    };
+
+    let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::CodeAction)
+        .find(|language_server| language_server.id() == diag.provider)
+    else {
+        cx.editor
+            .set_error("The diagnostic's language server does not support code actions");
+        return;
+    };
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+    let range = range_to_lsp_range(
+        text,
+        helix_core::Range::new(diag.range.start, diag.range.end),
+        offset_encoding,
+    );
+    let code_action_context = lsp::CodeActionContext {
+        diagnostics: vec![diagnostic_to_lsp_diagnostic(text, &diag, offset_encoding)],
+        only: Some(vec![lsp::CodeActionKind::QUICKFIX]),
+        trigger_kind: Some(CodeActionTriggerKind::INVOKED),
+    };
+    let future = language_server
+        .code_actions(doc.identifier(), range, code_action_context)
+        .unwrap();
+
+    cx.callback(
+        future,
+        move |editor, compositor, response: Option<lsp::CodeActionResponse>| {
+            let mut actions: Vec<_> = response
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|action| {
+                    matches!(
+                        action,
+                        CodeActionOrCommand::Command(_)
+                            | CodeActionOrCommand::CodeAction(CodeAction { disabled: None, .. })
+                    ) && action_fixes_diagnostics(action)
+                })
+                .map(|lsp_item| CodeActionOrCommandItem::new(lsp_item, language_server_id))
+                .collect();
+
+            if document_changed_since(editor, doc_id, version) {
+                editor.set_error("Document changed since the code actions were requested");
+                return;
+            }
+
+            match actions.len() {
+                0 => editor.set_error("No quick fix available for this diagnostic"),
+                1 => {
+                    let action = actions.remove(0);
+                    if let Some(edits) =
+                        diagnostic_replacement_edits(&action.lsp_item, &uri, range)
+                    {
+                        let workspace_edit = lsp::WorkspaceEdit {
+                            changes: Some([(uri, edits)].into_iter().collect()),
+                            ..Default::default()
+                        };
+                        let _ = editor.apply_workspace_edit(offset_encoding, &workspace_edit);
+                    } else {
+                        apply_code_action(editor, &action);
+                    }
+                }
+                _ => compositor
+                    .replace_or_push("code-action", code_action_menu(actions, doc_id, version)),
+            }
+        },
+    );
+}
+
+/// Collects the text edits of `workspace_edit` that target `doc`, converted
+/// to document ranges and sorted by position. The ranges refer to the
+/// document as it is *before* the edit is applied.
+fn collect_workspace_edit_ranges(
+    doc: &Document,
+    workspace_edit: &lsp::WorkspaceEdit,
+    offset_encoding: OffsetEncoding,
+) -> Option<Vec<(Range, String)>> {
+    let uri = doc.url()?;
+    let text = doc.text();
+
+    let mut edits: Vec<(Range, String)> = Vec::new();
+    let mut collect = |text_edit: &lsp::TextEdit| {
+        if let Some(range) = lsp_range_to_range(text, text_edit.range, offset_encoding) {
+            edits.push((range, text_edit.new_text.clone()));
+        }
+    };
+    match (&workspace_edit.changes, &workspace_edit.document_changes) {
+        (Some(changes), None) => {
+            for text_edit in changes.get(&uri)? {
+                collect(text_edit);
+            }
+        }
+        (_, Some(lsp::DocumentChanges::Edits(document_edits))) => {
+            for document_edit in document_edits
+                .iter()
+                .filter(|document_edit| document_edit.text_document.uri == uri)
+            {
+                for text_edit in &document_edit.edits {
+                    let text_edit = match text_edit {
+                        lsp::OneOf::Left(text_edit) => text_edit,
+                        lsp::OneOf::Right(annotated) => &annotated.text_edit,
+                    };
+                    collect(text_edit);
+                }
+            }
+        }
+        _ => return None,
+    }
+    edits.sort_unstable_by_key(|(range, _)| range.from());
+    Some(edits)
+}
+
+/// Returns the byte offset and text of the first identifier in `text`.
+fn first_identifier(text: &str) -> Option<(usize, &str)> {
+    let start = text.find(|c: char| c.is_alphabetic() || c == '_')?;
+    let len = text[start..]
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(text.len() - start);
+    Some((start, &text[start..start + len]))
+}
+
+/// Predicts the position (in the document as it will be *after* the edit is
+/// applied) and the text of the identifier a `refactor.extract` action
+/// introduces in `doc`. Extract actions insert the definition in one edit and
+/// the use site in another; the use site is heuristically the shortest
+/// inserted text containing an identifier — typically just the generated name
+/// or a call to it — and its first identifier is the generated name.
+fn locate_extracted_identifier(
+    doc: &Document,
+    workspace_edit: &lsp::WorkspaceEdit,
+    offset_encoding: OffsetEncoding,
+) -> Option<(usize, String)> {
+    let edits = collect_workspace_edit_ranges(doc, workspace_edit, offset_encoding)?;
+
+    let (index, offset, identifier) = edits
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, new_text))| {
+            first_identifier(new_text)
+                .map(|(offset, identifier)| (index, offset, identifier, new_text.len()))
+        })
+        .min_by_key(|&(.., len)| len)
+        .map(|(index, offset, identifier, _)| (index, offset, identifier.to_string()))?;
+
+    // shift the use site by the size change of every edit before it
+    let mut pos = edits[index].0.from() as isize;
+    for (range, new_text) in &edits[..index] {
+        pos += new_text.chars().count() as isize - (range.to() - range.from()) as isize;
+    }
+    let pos = usize::try_from(pos).ok()? + edits[index].1[..offset].chars().count();
+    Some((pos, identifier))
+}
+
+/// Applies an extract action and opens the rename prompt on the identifier it
+/// introduced, prefilled with the server-generated name.
+fn apply_extract_action(editor: &mut Editor, action: &CodeActionOrCommandItem) {
+    let lsp::CodeActionOrCommand::CodeAction(code_action) = &action.lsp_item else {
+        // a bare command applies server-side, there is no edit to inspect
+        apply_code_action(editor, action);
+        return;
+    };
+    let Some(language_server) = editor.language_server_by_id(action.language_server_id) else {
+        editor.set_error("Language Server disappeared");
+        return;
+    };
+    let offset_encoding = language_server.offset_encoding();
+
+    // resolve the edit up front, [apply_code_action] would do the same
+    let mut resolved_code_action = None;
+    if code_action.edit.is_none() || code_action.command.is_none() {
+        if let Some(future) = language_server.resolve_code_action(code_action.clone()) {
+            if let Ok(response) = helix_lsp::block_on(future) {
+                if let Ok(code_action) = serde_json::from_value::<CodeAction>(response) {
+                    resolved_code_action = Some(code_action);
+                }
+            }
+        }
+    }
+    let resolved_code_action = resolved_code_action.as_ref().unwrap_or(code_action);
+    let Some(workspace_edit) = &resolved_code_action.edit else {
+        editor.set_error("Extract action did not provide an edit");
+        return;
+    };
+
+    // figure out where the identifier will land before the text changes
+    let target = locate_extracted_identifier(doc!(editor), workspace_edit, offset_encoding);
+
+    if editor
+        .apply_workspace_edit(offset_encoding, workspace_edit)
+        .is_err()
+    {
+        return;
+    }
+    if let Some(command) = &resolved_code_action.command {
+        execute_lsp_command(editor, action.language_server_id, command.clone());
+    }
+
+    let Some((pos, identifier)) = target else {
+        return;
+    };
+    let (view, doc) = current!(editor);
+    let end = pos + identifier.chars().count();
+    if end > doc.text().len_chars() {
+        return;
+    }
+    // select the generated name so the prompt renames exactly it
+    doc.set_selection(view.id, Selection::single(pos, end));
+    crate::job::dispatch_blocking(move |editor, compositor| {
+        let prompt = create_rename_prompt(editor, identifier, None, None);
+        compositor.push(prompt);
+    });
+}
+
+/// Runs an extract refactor (`refactor.extract`, e.g. "Extract into variable")
+/// on the primary selection and immediately prompts to rename the identifier
+/// the server made up, so extracting and naming the result is one flow.
+pub fn extract_and_rename(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let doc_id = doc.id();
+    let version = doc.version();
+    let selection_range = doc.selection(view.id).primary();
+
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::CodeAction);
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+    let range = range_to_lsp_range(doc.text(), selection_range, offset_encoding);
+    let code_action_context = lsp::CodeActionContext {
+        diagnostics: Vec::new(),
+        only: Some(vec![lsp::CodeActionKind::REFACTOR_EXTRACT]),
+        trigger_kind: Some(CodeActionTriggerKind::INVOKED),
+    };
+    let future = language_server
+        .code_actions(doc.identifier(), range, code_action_context)
+        .unwrap();
+
+    cx.callback(
+        future,
+        move |editor, compositor, response: Option<lsp::CodeActionResponse>| {
+            let mut actions: Vec<_> = response
+                .unwrap_or_default()
+                .into_iter()
+                // not every server honors `only`
+                .filter(|action| {
+                    matches!(
+                        action,
+                        CodeActionOrCommand::CodeAction(CodeAction {
+                            disabled: None,
+                            kind: Some(kind),
+                            ..
+                        }) if kind.as_str().starts_with("refactor.extract")
+                    )
+                })
+                .map(|lsp_item| CodeActionOrCommandItem::new(lsp_item, language_server_id))
+                .collect();
+
+            if document_changed_since(editor, doc_id, version) {
+                editor.set_error("Document changed since the code actions were requested");
+                return;
+            }
+
+            match actions.len() {
+                0 => editor.set_error("No extract refactor available for this selection"),
+                1 => apply_extract_action(editor, &actions.remove(0)),
+                _ => {
+                    let mut menu = ui::Menu::new(actions, (), move |editor, action, event| {
+                        if event != PromptEvent::Validate {
+                            return;
+                        }
+                        if document_changed_since(editor, doc_id, version) {
+                            editor
+                                .set_error("Document changed since the code actions were requested");
+                            return;
+                        }
+                        // always present here
+                        apply_extract_action(editor, action.unwrap());
+                    });
+                    menu.move_down(); // pre-select the first item
+                    compositor.replace_or_push(
+                        "code-action",
+                        Popup::new("code-action", menu).with_scrollbar(false),
+                    );
+                }
+            }
+        },
+    );
+}
+
+pub fn apply_code_actions_kind_all_buffers(cx: &mut Context) {
+    let prompt = ui::Prompt::new(
+        "code-action-kind:".into(),
+        None,
+        ui::completers::none,
+        move |cx, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate || input.is_empty() {
+                return;
+            }
+            apply_code_actions_of_kind(cx.editor, lsp::CodeActionKind::from(input.to_string()));
+        },
+    );
+    cx.push_layer(Box::new(prompt));
+}
+
+/// Requests code actions of `kind` (e.g. `source.organizeImports`) for every
+/// open document and applies all of them, reporting a per-file summary.
+fn apply_code_actions_of_kind(editor: &mut Editor, kind: lsp::CodeActionKind) {
+    let mut requests = Vec::new();
+    for doc in editor.documents() {
+        let full_range = helix_core::Range::new(0, doc.text().len_chars());
+        let mut seen_language_servers = HashSet::new();
+        for language_server in doc.language_servers_with_feature(LanguageServerFeature::CodeAction)
+        {
+            if !seen_language_servers.insert(language_server.id()) {
+                continue;
+            }
+            // skip servers that declare a kind list not covering the request
+            if let Some(lsp::CodeActionProviderCapability::Options(options)) =
+                &language_server.capabilities().code_action_provider
+            {
+                if let Some(kinds) = &options.code_action_kinds {
+                    if !kinds
+                        .iter()
+                        .any(|supported| kind.as_str().starts_with(supported.as_str()))
+                    {
+                        continue;
+                    }
+                }
+            }
+            let offset_encoding = language_server.offset_encoding();
+            let range = range_to_lsp_range(doc.text(), full_range, offset_encoding);
+            let context = lsp::CodeActionContext {
+                diagnostics: Vec::new(),
+                only: Some(vec![kind.clone()]),
+                trigger_kind: Some(CodeActionTriggerKind::INVOKED),
+            };
+            let Some(request) = language_server.code_actions(doc.identifier(), range, context)
+            else {
+                continue;
+            };
+            requests.push((
+                doc.display_name().to_string(),
+                language_server.id(),
+                request,
+            ));
+        }
+    }
+
+    if requests.is_empty() {
+        editor.set_error("No open document has a language server supporting this code action kind");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut responses = Vec::with_capacity(requests.len());
+        for (name, language_server_id, request) in requests {
+            let res = async {
+                let json = request.await?;
+                let response: Option<lsp::CodeActionResponse> = serde_json::from_value(json)?;
+                anyhow::Ok(response.unwrap_or_default())
+            }
+            .await;
+            responses.push((name, language_server_id, res));
+        }
+
+        crate::job::dispatch(move |editor, _| {
+            let mut applied = 0usize;
+            let mut files = HashSet::new();
+            let mut failures = Vec::new();
+            for (name, language_server_id, res) in responses {
+                match res {
+                    Ok(actions) => {
+                        for lsp_item in actions {
+                            // disabled actions can't be applied
+                            if matches!(
+                                &lsp_item,
+                                CodeActionOrCommand::CodeAction(CodeAction {
+                                    disabled: Some(_),
+                                    ..
+                                })
+                            ) {
+                                continue;
+                            }
+                            let item =
+                                CodeActionOrCommandItem::new(lsp_item, language_server_id);
+                            if apply_code_action(editor, &item) {
+                                applied += 1;
+                                files.insert(name.clone());
+                            } else {
+                                failures.push(name.clone());
+                            }
+                        }
+                    }
+                    Err(err) => failures.push(format!("{name}: {err}")),
+                }
+            }
+            if failures.is_empty() {
+                editor.set_status(format!(
+                    "Applied {applied} code action(s) in {} file(s)",
+                    files.len()
+                ));
+            } else {
+                failures.sort();
+                failures.dedup();
+                editor.set_error(format!(
+                    "Applied {applied} code action(s); failed in: {}",
+                    failures.join(", ")
+                ));
+            }
+        })
+        .await;
+    });
+}
+
+impl ui::menu::Item for lsp::Command {
+    type Data = ();
+    fn format(&self, _data: &Self::Data) -> Row {
+        self.title.as_str().into()
+    }
+}
+
+/// Best-effort staleness check for the opaque arguments of a server-provided
+/// command. Servers commonly embed the documented version/position patterns
+/// (`VersionedTextDocumentIdentifier`, `Position`, `Range`) in there; by the
+/// time a menu entry is confirmed the document may have moved on, and
+/// executing anyway makes the server fail opaquely (or worse, edit the wrong
+/// spot). Recognized patterns are validated against the open documents;
+/// anything else is left alone.
+fn stale_command_argument(editor: &Editor, argument: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    let Some(object) = argument.as_object() else {
+        return argument
+            .as_array()
+            .is_some_and(|values| values.iter().any(|v| stale_command_argument(editor, v)));
+    };
+
+    let doc = object
+        .get("uri")
+        .and_then(Value::as_str)
+        .and_then(|uri| lsp::Url::parse(uri).ok())
+        .and_then(|url| url.to_file_path().ok())
+        .and_then(|path| editor.document_by_path(path));
+    if let Some(doc) = doc {
+        // {"uri": ..., "version": ...}, i.e. a VersionedTextDocumentIdentifier
+        if let Some(version) = object.get("version").and_then(Value::as_i64) {
+            if version != doc.version() as i64 {
+                return true;
+            }
+        }
+        // sibling positions/ranges that now point past the end of the file
+        let len_lines = doc.text().len_lines() as u64;
+        let out_of_bounds = |position: &Value| {
+            position
+                .get("line")
+                .and_then(Value::as_u64)
+                .is_some_and(|line| line >= len_lines)
+        };
+        if object.get("position").is_some_and(out_of_bounds) {
+            return true;
+        }
+        if object.get("range").is_some_and(|range| {
+            ["start", "end"]
+                .iter()
+                .any(|key| range.get(key).is_some_and(out_of_bounds))
+        }) {
+            return true;
+        }
+    }
+
+    object
+        .values()
+        .any(|value| stale_command_argument(editor, value))
+}
+
+pub fn execute_lsp_command(
+    editor: &mut Editor,
+    language_server_id: LanguageServerId,
+    cmd: lsp::Command,
+) {
+    let title = if cmd.title.is_empty() {
+        cmd.command.clone()
+    } else {
+        cmd.title.clone()
+    };
+
+    log::debug!(
+        "executing LSP command {:?} with arguments {}",
+        cmd.command,
+        serde_json::to_string(&cmd.arguments).unwrap_or_default()
+    );
+
+    if cmd
+        .arguments
+        .iter()
+        .flatten()
+        .any(|argument| stale_command_argument(editor, argument))
+    {
+        editor.set_error("Command arguments are stale, please retry");
+        return;
+    }
+
+    // the command is executed on the server and communicated back
+    // to the client asynchronously using workspace edits
+    let future = match editor
+        .language_server_by_id(language_server_id)
+        .and_then(|language_server| language_server.command(cmd))
+    {
+        Some(future) => future,
+        None => {
+            editor.set_error("Language server does not support executing commands");
+            return;
+        }
+    };
+
+    // route the server's log and progress output into the command output
+    // buffer while the command is pending (see `lsp_command_output`)
+    editor.lsp_command_capture = Some(language_server_id);
+    editor.append_lsp_command_output(&format!("── {title} ──"));
+
+    tokio::spawn(async move {
+        let res = future.await;
+
+        if let Err(ref e) = res {
+            log::error!("execute LSP command: {}", e);
+        }
+
+        crate::job::dispatch(move |editor, _| {
+            if editor.lsp_command_capture == Some(language_server_id) {
+                editor.lsp_command_capture = None;
+            }
+            match res {
+                Ok(_) => editor.append_lsp_command_output(&format!("── {title} finished ──")),
+                Err(err) => {
+                    editor.append_lsp_command_output(&format!("── {title} failed: {err} ──"))
+                }
+            }
+        })
+        .await;
+    });
+}
+
+/// Toggles or focuses the buffer that collects server output while LSP
+/// commands run.
+pub fn lsp_command_output(cx: &mut Context) {
+    let editor = &mut *cx.editor;
+    let doc_id = editor.lsp_command_output_document();
+    let current = view!(editor).id;
+    let shown = editor
+        .tree
+        .views()
+        .find_map(|(view, _)| (view.doc == doc_id).then_some(view.id));
+    match shown {
+        // toggle the split closed, unless it is the only view left
+        Some(view_id) if view_id == current => {
+            if editor.tree.views().count() > 1 {
+                editor.close(view_id);
+            } else {
+                editor.set_status("LSP command output is the only open view");
+            }
+        }
+        Some(view_id) => editor.focus(view_id),
+        None => editor.switch(doc_id, Action::HorizontalSplit),
+    }
+}
+
+#[derive(Debug)]
+pub struct ApplyEditError {
+    pub kind: ApplyEditErrorKind,
+    pub failed_change_idx: usize,
+}
+
+#[derive(Debug)]
+pub enum ApplyEditErrorKind {
+    DocumentChanged,
+    FileNotFound,
+    UnknownURISchema,
+    IoError(std::io::Error),
+    // TODO: check edits before applying and propagate failure
+    // InvalidEdit,
+}
+
+impl ToString for ApplyEditErrorKind {
+    fn to_string(&self) -> String {
+        match self {
+            ApplyEditErrorKind::DocumentChanged => "document has changed".to_string(),
+            ApplyEditErrorKind::FileNotFound => "file not found".to_string(),
+            ApplyEditErrorKind::UnknownURISchema => "URI schema not supported".to_string(),
+            ApplyEditErrorKind::IoError(err) => err.to_string(),
+        }
+    }
+}
+
+/// Routes a goto jump to either the regular file based [jump_to_location] or,
+/// for URI schemes the language server can serve content for, the virtual
+/// document pathway. `from_picker` marks jumps confirmed in a picker, which
+/// honor `lsp.picker-jumps-in-jumplist`.
+fn goto_location(
+    editor: &mut Editor,
+    language_server_id: LanguageServerId,
+    location: &lsp::Location,
+    offset_encoding: OffsetEncoding,
+    action: Action,
+    command: &'static str,
+    from_picker: bool,
+) {
+    match virtual_document_request(location.uri.scheme()) {
+        Some(method) if location.uri.to_file_path().is_err() => jump_to_virtual_location(
+            editor,
+            language_server_id,
+            method,
+            location,
+            offset_encoding,
+            action,
+        ),
+        _ if from_picker => {
+            jump_to_location_from_picker(editor, location, offset_encoding, action, command)
+        }
+        _ => jump_to_location(editor, location, offset_encoding, action, command),
+    }
+}
+
+/// Ask for confirmation before batch-opening more files than this.
+const OPEN_BUFFERS_CONFIRM_THRESHOLD: usize = 50;
+
+/// Opens the file behind every location as a background buffer, deduplicated
+/// by path, leaving the focused view (and its jumplist) untouched. Above
+/// [OPEN_BUFFERS_CONFIRM_THRESHOLD] files a confirmation prompt is shown
+/// first so a stray keypress can't load an entire monorepo.
+fn open_locations_in_background(editor: &mut Editor, locations: &[lsp::Location]) {
+    let mut seen = HashSet::new();
+    let paths: Vec<PathBuf> = locations
+        .iter()
+        .filter_map(|location| location.uri.to_file_path().ok())
+        .filter(|path| seen.insert(path.clone()))
+        .collect();
+
+    if paths.len() > OPEN_BUFFERS_CONFIRM_THRESHOLD {
+        crate::job::dispatch_blocking(move |_editor, compositor| {
+            let prompt = ui::Prompt::new(
+                format!("open {} buffers? (y/n):", paths.len()).into(),
+                None,
+                ui::completers::none,
+                move |cx: &mut compositor::Context, input: &str, event: PromptEvent| {
+                    if event != PromptEvent::Validate || !matches!(input, "y" | "yes") {
+                        return;
+                    }
+                    open_paths_in_background(cx.editor, &paths);
+                },
+            );
+            compositor.push(Box::new(prompt));
+        });
+    } else {
+        open_paths_in_background(editor, &paths);
+    }
+}
+
+fn open_paths_in_background(editor: &mut Editor, paths: &[PathBuf]) {
+    let mut opened = 0usize;
+    for path in paths {
+        match editor.open(path, Action::Load) {
+            Ok(_) => opened += 1,
+            Err(err) => log::error!("failed to open {}: {err}", path.display()),
+        }
+    }
+    editor.set_status(format!("opened {opened} buffers"));
+}
+
+/// Precondition: `locations` should be non-empty.
+/// Reports an empty LSP result: when an `lsp_or` binding armed a fallback
+/// command for this request it is triggered instead of the error message
+/// (the application loop runs it with a full command context, which job
+/// callbacks lack).
+fn empty_result_fallback(editor: &mut Editor, message: &'static str) {
+    match editor.lsp_fallback.take() {
+        Some(LspFallback::Armed(fallback)) => {
+            editor.lsp_fallback = Some(LspFallback::Triggered(fallback));
+        }
+        _ => editor.set_error(message),
+    }
+}
+
+fn goto_impl(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    locations: Vec<GotoLocationItem>,
+    command: &'static str,
+) {
+    let cwdir = helix_stdx::env::current_working_dir();
+
+    // `lsp.goto-workspace-only` suppresses locations outside the workspace
+    // root (the stdlib, `node_modules`, registry sources, ...); they stay
+    // reachable through the picker's reveal toggle. When every location is
+    // external hiding them all would leave nothing to go to, so the filter
+    // does not apply.
+    let (locations, external) = if editor.config().lsp.goto_workspace_only {
+        let root = find_workspace().0;
+        let (inside, outside): (Vec<_>, Vec<_>) = locations.into_iter().partition(|item| {
+            item.location
+                .uri
+                .to_file_path()
+                .is_ok_and(|path| path.starts_with(&root))
+        });
+        if inside.is_empty() {
+            (outside, Vec::new())
+        } else {
+            (inside, outside)
+        }
+    } else {
+        (locations, Vec::new())
+    };
+
+    match locations.as_slice() {
+        [item] if external.is_empty() => {
+            goto_location(
+                editor,
+                item.language_server_id,
+                &item.location,
+                item.offset_encoding,
+                Action::Replace,
+                command,
+                false,
+            );
+        }
+        [] => unreachable!("`locations` should be non-empty for `goto_impl`"),
+        _locations => {
+            // label entries by server only when the servers disagreed; when
+            // every entry was reported by the same servers there is nothing
+            // to tell apart
+            let label_servers = locations
+                .iter()
+                .any(|item| item.servers != locations[0].servers);
+            let all_locations: Vec<_> = locations
+                .iter()
+                .chain(external.iter())
+                .map(|item| item.location.clone())
+                .collect();
+            let visible = locations;
+            let picker = Picker::new(
+                visible.clone(),
+                (cwdir, label_servers),
+                move |cx, item, action| {
+                    goto_location(
+                        cx.editor,
+                        item.language_server_id,
+                        &item.location,
+                        item.offset_encoding,
+                        action,
+                        command,
+                        true,
+                    )
+                },
+            )
+            .with_preview(move |_editor, item| location_to_file_location(&item.location))
+            .with_alternate_action(move |cx, _item| {
+                open_locations_in_background(cx.editor, &all_locations);
+            })
+            .with_raw_json(|item| serde_json::to_string_pretty(&item.location).ok())
+            .with_confirm_action(match command {
+                "goto_reference" => editor.config().lsp.picker_actions.reference,
+                _ => editor.config().lsp.picker_actions.goto,
+            });
+            let picker = if external.is_empty() {
+                picker
+            } else {
+                picker.with_hidden_options(Arc::new(AtomicBool::new(false)), move |show_hidden| {
+                    let mut options = visible.clone();
+                    if show_hidden {
+                        options.extend(external.iter().cloned());
+                    }
+                    options
+                })
+            };
+            compositor.push(Box::new(overlaid(picker)));
+        }
+    }
+}
+
+fn to_locations(definitions: Option<lsp::GotoDefinitionResponse>) -> Vec<lsp::Location> {
+    let mut locations = match definitions {
+        Some(lsp::GotoDefinitionResponse::Scalar(location)) => vec![location],
+        Some(lsp::GotoDefinitionResponse::Array(locations)) => locations,
+        Some(lsp::GotoDefinitionResponse::Link(locations)) => locations
+            .into_iter()
+            .map(|location_link| lsp::Location {
+                uri: location_link.target_uri,
+                range: location_link.target_range,
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    for location in &mut locations {
+        normalize_uri(&mut location.uri);
+    }
+    locations
+}
+
+/// Whether `server_id` can answer user-invoked requests: the `initialize`
+/// handshake is done and it is not reporting work-done progress (language
+/// servers report their start-up indexing this way).
+fn server_ready(editor: &Editor, server_id: LanguageServerId) -> bool {
+    editor
+        .language_server_by_id(server_id)
+        .map_or(false, |ls| ls.is_initialized())
+        && !editor.lsp_busy.contains_key(&server_id)
+}
+
+/// Gates a user-invoked command on the readiness of the servers providing
+/// `feature` for the current document, per `lsp.server-not-ready`. Returns
+/// `true` when at least one of them is ready — partial answers beat none —
+/// or when none is configured at all (the caller reports that as usual).
+/// Otherwise the command either fails with a message naming the busy server,
+/// or is parked in `Editor::pending_lsp_command` and re-run by the
+/// application once the server reports ready; Escape cancels it. The slot
+/// holds a single command, so queued requests cannot pile up.
+fn gate_on_server_ready(
+    cx: &mut Context,
+    feature: LanguageServerFeature,
+    command: &'static str,
+) -> bool {
+    let doc = doc!(cx.editor);
+    let servers: Vec<_> = doc
+        .language_servers_with_feature(feature)
+        .map(|ls| ls.id())
+        .collect();
+    if servers.is_empty() || servers.iter().any(|&id| server_ready(cx.editor, id)) {
+        return true;
+    }
+    let server_id = servers[0];
+    let name = cx
+        .editor
+        .language_server_by_id(server_id)
+        .map_or("language server", |ls| ls.name())
+        .to_string();
+    let message = match cx.editor.lsp_busy.get(&server_id) {
+        Some(progress) => format!("Waiting for {name} ({progress})"),
+        None => format!("Waiting for {name} (initializing)"),
+    };
+    match cx.editor.config().lsp.server_not_ready {
+        ServerNotReady::Wait => {
+            cx.editor.pending_lsp_command = Some(PendingLspCommand {
+                server: server_id,
+                command,
+            });
+            cx.editor
+                .set_status(format!("{message} — press Escape to cancel"));
+        }
+        ServerNotReady::Fail => cx.editor.set_error(message),
+    }
+    false
+}
+
+fn goto_single_impl<P, F>(
+    cx: &mut Context,
+    feature: LanguageServerFeature,
+    request_provider: P,
+    command: &'static str,
+) where
     P: Fn(&Client, lsp::Position, lsp::TextDocumentIdentifier) -> Option<F>,
     F: Future<Output = helix_lsp::Result<serde_json::Value>> + 'static + Send,
 {
+    if !gate_on_server_ready(cx, feature, command) {
+        return;
+    }
+    let (view, doc) = current!(cx.editor);
+    let view_id = view.id;
+
+    let mut seen_language_servers = HashSet::new();
+    let mut futures: FuturesOrdered<_> = doc
+        .language_servers_with_feature(feature)
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+        .map(|language_server| {
+            let ls_id = language_server.id();
+            let name = language_server.name().to_string();
+            let offset_encoding = language_server.offset_encoding();
+            let pos = lsp_position::cursor_to_lsp_pos(doc, view_id, language_server);
+            let identifier = doc.identifier();
+            // make sure a didChange still in flight cannot be overtaken by
+            // the request, which would make the server answer for stale text
+            let barrier =
+                language_server.document_sync_barrier(identifier.uri.clone(), doc.version());
+            let future = request_provider(language_server, pos, identifier).unwrap();
+            async move {
+                barrier.await;
+                let result: Result<Option<lsp::GotoDefinitionResponse>, helix_lsp::Error> =
+                    async move { Ok(serde_json::from_value(future.await?)?) }.await;
+                (ls_id, name, offset_encoding, result)
+            }
+        })
+        .collect();
+
+    if futures.is_empty() {
+        cx.editor
+            .set_status(format!("No configured language server supports {feature}"));
+        return;
+    }
+
+    cx.jobs.callback(async move {
+        let mut locations: Vec<GotoLocationItem> = Vec::new();
+        let mut errors = Vec::new();
+        while let Some((ls_id, name, offset_encoding, result)) = futures.next().await {
+            let response = match result {
+                Ok(response) => response,
+                Err(err) => {
+                    errors.push((ls_id, err));
+                    continue;
+                }
+            };
+            for location in to_locations(response) {
+                // servers that agree on a location (same uri and range)
+                // collapse into a single entry
+                match locations.iter_mut().find(|item| item.location == location) {
+                    Some(item) => item.servers.push(name.clone()),
+                    None => locations.push(GotoLocationItem {
+                        location,
+                        language_server_id: ls_id,
+                        offset_encoding,
+                        servers: vec![name.clone()],
+                    }),
+                }
+            }
+        }
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            for (ls_id, err) in &errors {
+                if !handle_server_exit(editor, *ls_id, err) {
+                    editor.set_error(err.to_string());
+                }
+            }
+            if locations.is_empty() {
+                if errors.is_empty() {
+                    empty_result_fallback(editor, "No definition found.");
+                }
+            } else {
+                goto_impl(editor, compositor, locations, command);
+            }
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+pub fn goto_declaration(cx: &mut Context) {
+    goto_single_impl(
+        cx,
+        LanguageServerFeature::GotoDeclaration,
+        |ls, pos, doc_id| ls.goto_declaration(doc_id, pos, None),
+        "goto_declaration",
+    );
+}
+
+pub fn goto_definition(cx: &mut Context) {
+    goto_single_impl(
+        cx,
+        LanguageServerFeature::GotoDefinition,
+        |ls, pos, doc_id| ls.goto_definition(doc_id, pos, None),
+        "goto_definition",
+    );
+}
+
+pub fn goto_type_definition(cx: &mut Context) {
+    goto_single_impl(
+        cx,
+        LanguageServerFeature::GotoTypeDefinition,
+        |ls, pos, doc_id| ls.goto_type_definition(doc_id, pos, None),
+        "goto_type_definition",
+    );
+}
+
+/// Runs goto-definition and goto-type-definition concurrently and merges the
+/// results into one picker: for a variable this lists both where it is
+/// defined and where its type is defined. The query that produced each entry
+/// rides in the [`GotoLocationItem::servers`] label column, and a location
+/// both queries agree on collapses into a single `[definition,type]` entry.
+pub fn goto_definition_and_type(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let view_id = view.id;
+
+    let mut requests = Vec::new();
+    for (feature, query) in [
+        (LanguageServerFeature::GotoDefinition, "definition"),
+        (LanguageServerFeature::GotoTypeDefinition, "type"),
+    ] {
+        let mut seen_language_servers = HashSet::new();
+        for language_server in doc
+            .language_servers_with_feature(feature)
+            .filter(|ls| seen_language_servers.insert(ls.id()))
+        {
+            let ls_id = language_server.id();
+            let offset_encoding = language_server.offset_encoding();
+            let pos = doc.position(view_id, offset_encoding);
+            // the two requests return distinct future types, box to mix them
+            let future = match feature {
+                LanguageServerFeature::GotoTypeDefinition => language_server
+                    .goto_type_definition(doc.identifier(), pos, None)
+                    .map(|future| future.boxed()),
+                _ => language_server
+                    .goto_definition(doc.identifier(), pos, None)
+                    .map(|future| future.boxed()),
+            }
+            .unwrap();
+            let barrier =
+                language_server.document_sync_barrier(doc.identifier().uri, doc.version());
+            requests.push(async move {
+                barrier.await;
+                let result: Result<Option<lsp::GotoDefinitionResponse>, helix_lsp::Error> =
+                    async move { Ok(serde_json::from_value(future.await?)?) }.await;
+                (ls_id, query, offset_encoding, result)
+            });
+        }
+    }
+    let mut futures: FuturesOrdered<_> = requests.into_iter().collect();
+
+    if futures.is_empty() {
+        cx.editor.set_status(
+            "No configured language server supports goto-definition or goto-type-definition",
+        );
+        return;
+    }
+
+    cx.jobs.callback(async move {
+        let mut locations: Vec<GotoLocationItem> = Vec::new();
+        let mut errors = Vec::new();
+        while let Some((ls_id, query, offset_encoding, result)) = futures.next().await {
+            let response = match result {
+                Ok(response) => response,
+                Err(err) => {
+                    errors.push((ls_id, err));
+                    continue;
+                }
+            };
+            for location in to_locations(response) {
+                // a location answering both queries (or one query on several
+                // servers) collapses into a single entry
+                match locations.iter_mut().find(|item| item.location == location) {
+                    Some(item) => {
+                        if !item.servers.iter().any(|label| label == query) {
+                            item.servers.push(query.to_string());
+                        }
+                    }
+                    None => locations.push(GotoLocationItem {
+                        location,
+                        language_server_id: ls_id,
+                        offset_encoding,
+                        servers: vec![query.to_string()],
+                    }),
+                }
+            }
+        }
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            for (ls_id, err) in &errors {
+                if !handle_server_exit(editor, *ls_id, err) {
+                    editor.set_error(err.to_string());
+                }
+            }
+            if locations.is_empty() {
+                if errors.is_empty() {
+                    editor.set_error("No definition or type definition found.");
+                }
+            } else {
+                goto_impl(editor, compositor, locations, "goto_definition_and_type");
+            }
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+/// Goto the definition of the type of the expression under the cursor. The
+/// typeDefinition request is issued at the cursor first; when nothing comes
+/// back — or every location just points back at the requested position, as
+/// some servers answer on a method call — it is retried at the start of the
+/// enclosing expression, found via tree-sitter node boundaries. The status
+/// line reports which position finally produced the result.
+pub fn goto_expression_type_definition(cx: &mut Context) {
+    fn merge_location(
+        locations: &mut Vec<GotoLocationItem>,
+        ls_id: LanguageServerId,
+        name: &str,
+        offset_encoding: OffsetEncoding,
+        location: lsp::Location,
+    ) {
+        match locations.iter_mut().find(|item| item.location == location) {
+            Some(item) => item.servers.push(name.to_string()),
+            None => locations.push(GotoLocationItem {
+                location,
+                language_server_id: ls_id,
+                offset_encoding,
+                servers: vec![name.to_string()],
+            }),
+        }
+    }
+
+    /// Collects one batch of responses, dropping locations that only point
+    /// back at the position the request was made for.
+    async fn drain<F>(
+        futures: &mut FuturesOrdered<F>,
+        doc_uri: Option<&lsp::Url>,
+        locations: &mut Vec<GotoLocationItem>,
+        errors: &mut Vec<(LanguageServerId, helix_lsp::Error)>,
+        self_referential: &mut usize,
+    ) where
+        F: Future<
+            Output = (
+                LanguageServerId,
+                String,
+                OffsetEncoding,
+                lsp::Position,
+                helix_lsp::Result<Option<lsp::GotoDefinitionResponse>>,
+            ),
+        >,
+    {
+        while let Some((ls_id, name, offset_encoding, pos, result)) = futures.next().await {
+            let response = match result {
+                Ok(response) => response,
+                Err(err) => {
+                    errors.push((ls_id, err));
+                    continue;
+                }
+            };
+            for location in to_locations(response) {
+                if doc_uri == Some(&location.uri)
+                    && location.range.start <= pos
+                    && pos <= location.range.end
+                {
+                    *self_referential += 1;
+                    continue;
+                }
+                merge_location(locations, ls_id, &name, offset_encoding, location);
+            }
+        }
+    }
+
+    let (view, doc) = current!(cx.editor);
+    let view_id = view.id;
+    let text = doc.text();
+    let cursor = doc.selection(view_id).primary().cursor(text.slice(..));
+
+    // start of the enclosing expression: the first ancestor of the node under
+    // the cursor that starts before it, e.g. the receiver of a method call
+    let expression_start = doc.syntax().and_then(|syntax| {
+        let byte = text.char_to_byte(cursor);
+        let node = syntax
+            .tree()
+            .root_node()
+            .named_descendant_for_byte_range(byte, byte)?;
+        let mut ancestor = node.parent()?;
+        while ancestor.start_byte() == node.start_byte() {
+            ancestor = ancestor.parent()?;
+        }
+        Some(text.byte_to_char(ancestor.start_byte()))
+    });
+    let expression_start = expression_start.filter(|&start| start != cursor);
+    let expression_line = expression_start.map_or(0, |start| text.char_to_line(start) + 1);
+
+    let doc_uri = doc.url();
+    let mut seen_language_servers = HashSet::new();
+    let mut cursor_requests = Vec::new();
+    let mut expression_requests = Vec::new();
+    for language_server in doc
+        .language_servers_with_feature(LanguageServerFeature::GotoTypeDefinition)
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+    {
+        let ls_id = language_server.id();
+        let name = language_server.name().to_string();
+        let offset_encoding = language_server.offset_encoding();
+        // requests only go out when the future is polled, so the fallback
+        // batch costs nothing unless the cursor batch comes back empty
+        for (pos, requests) in [
+            (Some(cursor), &mut cursor_requests),
+            (expression_start, &mut expression_requests),
+        ] {
+            let Some(pos) = pos else { continue };
+            let pos = pos_to_lsp_pos(text, pos, offset_encoding);
+            let future = language_server
+                .goto_type_definition(doc.identifier(), pos, None)
+                .unwrap();
+            let name = name.clone();
+            requests.push(async move {
+                let result: helix_lsp::Result<Option<lsp::GotoDefinitionResponse>> =
+                    async move { Ok(serde_json::from_value(future.await?)?) }.await;
+                (ls_id, name, offset_encoding, pos, result)
+            });
+        }
+    }
+    let mut cursor_futures: FuturesOrdered<_> = cursor_requests.into_iter().collect();
+    let mut expression_futures: FuturesOrdered<_> = expression_requests.into_iter().collect();
+
+    if cursor_futures.is_empty() {
+        cx.editor
+            .set_status("No configured language server supports goto-type-definition");
+        return;
+    }
+
+    cx.jobs.callback(async move {
+        let mut locations = Vec::new();
+        let mut errors = Vec::new();
+        let mut self_referential = 0usize;
+        drain(
+            &mut cursor_futures,
+            doc_uri.as_ref(),
+            &mut locations,
+            &mut errors,
+            &mut self_referential,
+        )
+        .await;
+        let mut used_expression = false;
+        if locations.is_empty() {
+            drain(
+                &mut expression_futures,
+                doc_uri.as_ref(),
+                &mut locations,
+                &mut errors,
+                &mut self_referential,
+            )
+            .await;
+            used_expression = !locations.is_empty();
+        }
+
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            for (ls_id, err) in &errors {
+                if !handle_server_exit(editor, *ls_id, err) {
+                    editor.set_error(err.to_string());
+                }
+            }
+            if locations.is_empty() {
+                if errors.is_empty() {
+                    if self_referential > 0 {
+                        editor.set_error(
+                            "Type definition only points back at the requested position",
+                        );
+                    } else {
+                        editor.set_error("No type definition found.");
+                    }
+                }
+                return;
+            }
+            goto_impl(
+                editor,
+                compositor,
+                locations,
+                "goto_expression_type_definition",
+            );
+            if used_expression {
+                editor.set_status(format!(
+                    "typeDefinition at the enclosing expression (line {expression_line}) produced the result"
+                ));
+            } else {
+                editor.set_status("typeDefinition at the cursor produced the result");
+            }
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+pub fn goto_implementation(cx: &mut Context) {
+    goto_single_impl(
+        cx,
+        LanguageServerFeature::GotoImplementation,
+        |ls, pos, doc_id| ls.goto_implementation(doc_id, pos, None),
+        "goto_implementation",
+    );
+}
+
+/// Like [goto_implementation] but only reports how many implementations exist
+/// instead of jumping. The locations are cached on the editor so
+/// [implementations_picker] can open a picker over them afterwards without a
+/// second request.
+pub fn count_implementations(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::GotoImplementation);
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+    let pos = doc.position(view.id, offset_encoding);
+    let future = language_server
+        .goto_implementation(doc.identifier(), pos, None)
+        .unwrap();
+
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<lsp::GotoDefinitionResponse>| {
+            let locations = to_locations(response);
+            match locations.len() {
+                0 => editor.set_status("No implementations found"),
+                1 => editor.set_status("1 implementation"),
+                n => editor.set_status(format!("{n} implementations")),
+            }
+            editor.cached_implementations =
+                (!locations.is_empty()).then_some(helix_view::editor::CachedImplementations {
+                    language_server_id,
+                    offset_encoding,
+                    locations,
+                });
+        },
+    );
+}
+
+/// Opens a picker over the locations cached by [count_implementations].
+pub fn implementations_picker(cx: &mut Context) {
+    let Some(cached) = cx.editor.cached_implementations.clone() else {
+        cx.editor
+            .set_error("No cached implementations, run count_implementations first");
+        return;
+    };
+
+    if let [location] = cached.locations.as_slice() {
+        goto_location(
+            cx.editor,
+            cached.language_server_id,
+            location,
+            cached.offset_encoding,
+            Action::Replace,
+            "implementations_picker",
+            false,
+        );
+        return;
+    }
+
+    let cwdir = helix_stdx::env::current_working_dir();
+    let language_server_id = cached.language_server_id;
+    let offset_encoding = cached.offset_encoding;
+    let picker = Picker::new(cached.locations, cwdir, move |cx, location, action| {
+        goto_location(
+            cx.editor,
+            language_server_id,
+            location,
+            offset_encoding,
+            action,
+            "implementations_picker",
+            true,
+        )
+    })
+    .with_preview(move |_editor, location| location_to_file_location(location))
+    .with_raw_json(|location| serde_json::to_string_pretty(location).ok());
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// Opens a picker over the jumps recorded by LSP navigation commands, newest
+/// first. Unlike the view-local jumplist this retains which command caused
+/// each jump.
+pub fn lsp_jump_picker(cx: &mut Context) {
+    struct LspJumpItem {
+        jump: LspJump,
+        from_path: Option<PathBuf>,
+        from_line: usize,
+        to_path: Option<PathBuf>,
+        to_line: usize,
+    }
+
+    impl ui::menu::Item for LspJumpItem {
+        type Data = ();
+
+        fn format(&self, _data: &Self::Data) -> Row {
+            let display = |path: &Option<PathBuf>, line: usize| {
+                let path = path.as_deref().map(helix_stdx::path::get_relative_path);
+                match path.as_deref().and_then(Path::to_str) {
+                    Some(path) => format!("{}:{}", path, line + 1),
+                    None => format!("{}:{}", SCRATCH_BUFFER_NAME, line + 1),
+                }
+            };
+            format!(
+                "{} {} -> {}",
+                self.jump.command,
+                display(&self.from_path, self.from_line),
+                display(&self.to_path, self.to_line),
+            )
+            .into()
+        }
+    }
+
+    // drop entries whose documents were closed or whose positions fell out of
+    // range after edits
+    let mut history = std::mem::take(&mut cx.editor.lsp_jump_history);
+    history.retain(|jump| {
+        [jump.from, jump.to].into_iter().all(|(doc_id, pos)| {
+            cx.editor
+                .documents
+                .get(&doc_id)
+                .is_some_and(|doc| pos <= doc.text().len_chars())
+        })
+    });
+    cx.editor.lsp_jump_history = history;
+
+    let items: Vec<_> = cx
+        .editor
+        .lsp_jump_history
+        .iter()
+        .rev()
+        .map(|jump| {
+            let line_of = |(doc_id, pos): (DocumentId, usize)| {
+                let doc = &cx.editor.documents[&doc_id];
+                (doc.path().cloned(), doc.text().char_to_line(pos))
+            };
+            let (from_path, from_line) = line_of(jump.from);
+            let (to_path, to_line) = line_of(jump.to);
+            LspJumpItem {
+                jump: jump.clone(),
+                from_path,
+                from_line,
+                to_path,
+                to_line,
+            }
+        })
+        .collect();
+
+    if items.is_empty() {
+        cx.editor.set_error("No LSP jumps recorded");
+        return;
+    }
+
+    let picker = Picker::new(items, (), |cx, item, action| {
+        let (doc_id, pos) = item.jump.to;
+        if !cx.editor.documents.contains_key(&doc_id) {
+            cx.editor.set_error("Document was closed");
+            return;
+        }
+        cx.editor.switch(doc_id, action);
+        let config = cx.editor.config();
+        let (view, doc) = (view_mut!(cx.editor), doc_mut!(cx.editor, &doc_id));
+        doc.set_selection(view.id, Selection::point(pos.min(doc.text().len_chars())));
+        if action.align_view(view, doc.id()) {
+            view.ensure_cursor_in_view_center(doc, config.scrolloff);
+        }
+    })
+    .with_preview(|editor, item| {
+        let (doc_id, pos) = item.jump.to;
+        let doc = editor.documents.get(&doc_id)?;
+        let line = doc.text().char_to_line(pos.min(doc.text().len_chars()));
+        Some((doc_id.into(), Some((line, line))))
+    });
+    cx.push_layer(Box::new(overlaid(picker)));
+}
+
+/// The effective include-declaration flag for goto-reference requests: the
+/// session override set by [`toggle_goto_reference_declaration`] if there is
+/// one, the config value otherwise.
+fn goto_reference_include_declaration(editor: &Editor) -> bool {
+    editor
+        .goto_reference_declaration_override
+        .unwrap_or(editor.config().lsp.goto_reference_include_declaration)
+}
+
+/// Flips a session-level override of `lsp.goto-reference-include-declaration`
+/// and reports the new state. Subsequent `goto_reference` calls honor the
+/// override until it is toggled back or the session ends; the persisted
+/// config is untouched.
+pub fn toggle_goto_reference_declaration(cx: &mut Context) {
+    let include = !goto_reference_include_declaration(cx.editor);
+    cx.editor.goto_reference_declaration_override = Some(include);
+    cx.editor.set_status(if include {
+        "Goto reference includes the declaration"
+    } else {
+        "Goto reference excludes the declaration"
+    });
+}
+
+pub fn goto_reference(cx: &mut Context) {
+    if !gate_on_server_ready(cx, LanguageServerFeature::GotoReference, "goto_reference") {
+        return;
+    }
+    let include_declaration = goto_reference_include_declaration(cx.editor);
+    let (view, doc) = current!(cx.editor);
+
+    // TODO could probably support multiple language servers,
+    // not sure if there's a real practical use case for this though
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::GotoReference);
+    let language_server_id = language_server.id();
+    let language_server_name = language_server.name().to_string();
+    let offset_encoding = language_server.offset_encoding();
+    let pos = doc.position(view.id, offset_encoding);
+    let barrier = language_server.document_sync_barrier(doc.identifier().uri, doc.version());
+    let future = language_server
+        .goto_reference(doc.identifier(), pos, include_declaration, None)
+        .unwrap();
+    let future = async move {
+        barrier.await;
+        future.await
+    };
+
+    cx.callback(
+        future,
+        move |editor, compositor, response: Option<Vec<lsp::Location>>| {
+            let items: Vec<_> = response
+                .unwrap_or_default()
+                .into_iter()
+                .map(|mut location| {
+                    normalize_uri(&mut location.uri);
+                    location
+                })
+                .map(|location| GotoLocationItem {
+                    location,
+                    language_server_id,
+                    offset_encoding,
+                    servers: vec![language_server_name.clone()],
+                })
+                .collect();
+            if items.is_empty() {
+                empty_result_fallback(editor, "No references found.");
+            } else {
+                goto_impl(editor, compositor, items, "goto_reference");
+            }
+        },
+    );
+}
+
+/// Opens the references picker with locations the `symbol_info` popup
+/// already fetched instead of issuing a fresh request.
+pub(crate) fn show_fetched_references(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    references: ui::lsp::SymbolInfoLocations,
+) {
+    let items: Vec<_> = references
+        .locations
+        .into_iter()
+        .map(|location| GotoLocationItem {
+            location,
+            language_server_id: references.language_server_id,
+            offset_encoding: references.offset_encoding,
+            servers: vec![references.server_name.clone()],
+        })
+        .collect();
+    goto_impl(editor, compositor, items, "goto_reference");
+}
+
+/// Fires hover, goto-definition and goto-reference concurrently and shows
+/// the answers in one popup: the hover contents, the definition locations
+/// (`d` or `1`-`9` jumps) and the reference count (`r` opens the references
+/// picker with the already-fetched locations). Whatever succeeded is
+/// rendered, with a note about any query that failed.
+pub fn symbol_info(cx: &mut Context) {
+    if !gate_on_server_ready(cx, LanguageServerFeature::Hover, "symbol_info") {
+        return;
+    }
+    let include_declaration = goto_reference_include_declaration(cx.editor);
+    let (view, doc) = current!(cx.editor);
+    let view_id = view.id;
+
+    enum Response {
+        Hover(Option<lsp::Hover>),
+        Definition(Option<lsp::GotoDefinitionResponse>),
+        References(Option<Vec<lsp::Location>>),
+    }
+
+    type QueryResult = (
+        &'static str,
+        LanguageServerId,
+        String,
+        OffsetEncoding,
+        Result<Response, helix_lsp::Error>,
+    );
+
+    // one query each to the first server supporting the feature
+    let mut futures: FuturesOrdered<BoxFuture<'static, QueryResult>> = FuturesOrdered::new();
+    if let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::Hover)
+        .next()
+    {
+        let (ls_id, name) = (language_server.id(), language_server.name().to_string());
+        let offset_encoding = language_server.offset_encoding();
+        let pos = doc.position(view_id, offset_encoding);
+        let barrier =
+            language_server.document_sync_barrier(doc.identifier().uri, doc.version());
+        let future = language_server
+            .text_document_hover(doc.identifier(), pos, None)
+            .unwrap();
+        futures.push_back(
+            async move {
+                barrier.await;
+                let result =
+                    async move { Ok(Response::Hover(serde_json::from_value(future.await?)?)) }
+                        .await;
+                ("hover", ls_id, name, offset_encoding, result)
+            }
+            .boxed(),
+        );
+    }
+    if let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::GotoDefinition)
+        .next()
+    {
+        let (ls_id, name) = (language_server.id(), language_server.name().to_string());
+        let offset_encoding = language_server.offset_encoding();
+        let pos = doc.position(view_id, offset_encoding);
+        let barrier =
+            language_server.document_sync_barrier(doc.identifier().uri, doc.version());
+        let future = language_server
+            .goto_definition(doc.identifier(), pos, None)
+            .unwrap();
+        futures.push_back(
+            async move {
+                barrier.await;
+                let result = async move {
+                    Ok(Response::Definition(serde_json::from_value(future.await?)?))
+                }
+                .await;
+                ("definition", ls_id, name, offset_encoding, result)
+            }
+            .boxed(),
+        );
+    }
+    if let Some(language_server) = doc
+        .language_servers_with_feature(LanguageServerFeature::GotoReference)
+        .next()
+    {
+        let (ls_id, name) = (language_server.id(), language_server.name().to_string());
+        let offset_encoding = language_server.offset_encoding();
+        let pos = doc.position(view_id, offset_encoding);
+        let barrier =
+            language_server.document_sync_barrier(doc.identifier().uri, doc.version());
+        let future = language_server
+            .goto_reference(doc.identifier(), pos, include_declaration, None)
+            .unwrap();
+        futures.push_back(
+            async move {
+                barrier.await;
+                let result = async move {
+                    Ok(Response::References(serde_json::from_value(future.await?)?))
+                }
+                .await;
+                ("references", ls_id, name, offset_encoding, result)
+            }
+            .boxed(),
+        );
+    }
+
+    if futures.is_empty() {
+        cx.editor.set_status(
+            "No configured language server supports hover, goto-definition or goto-reference",
+        );
+        return;
+    }
+
+    cx.jobs.callback(async move {
+        let mut hover = None;
+        let mut definitions: Option<ui::lsp::SymbolInfoLocations> = None;
+        let mut references: Option<ui::lsp::SymbolInfoLocations> = None;
+        let mut failures = Vec::new();
+        let mut errors = Vec::new();
+        while let Some((query, ls_id, name, offset_encoding, result)) = futures.next().await {
+            match result {
+                Ok(Response::Hover(response)) => {
+                    hover = response
+                        .map(|hover| hover_contents_to_markdown(hover.contents))
+                        .filter(|contents| !contents.is_empty());
+                }
+                Ok(Response::Definition(response)) => {
+                    let locations = to_locations(response);
+                    if !locations.is_empty() {
+                        definitions = Some(ui::lsp::SymbolInfoLocations {
+                            locations,
+                            language_server_id: ls_id,
+                            server_name: name,
+                            offset_encoding,
+                        });
+                    }
+                }
+                Ok(Response::References(response)) => {
+                    let locations = response.unwrap_or_default();
+                    if !locations.is_empty() {
+                        references = Some(ui::lsp::SymbolInfoLocations {
+                            locations,
+                            language_server_id: ls_id,
+                            server_name: name,
+                            offset_encoding,
+                        });
+                    }
+                }
+                Err(err) => {
+                    failures.push(query);
+                    errors.push((ls_id, err));
+                }
+            }
+        }
+
+        let cwdir = helix_stdx::env::current_working_dir();
+        let mut contents = String::new();
+        contents.push_str(hover.as_deref().unwrap_or("_No hover information_"));
+        contents.push_str("\n\n---\n\n**Definition**\n\n");
+        match &definitions {
+            Some(definitions) => {
+                for (i, location) in definitions.locations.iter().take(9).enumerate() {
+                    writeln!(contents, "{}. `{}`", i + 1, format_location(location, &cwdir))
+                        .unwrap();
+                }
+                contents.push_str("\nPress `d` (or the number) to jump.\n");
+            }
+            None => contents.push_str("_No definition found_\n"),
+        }
+        contents.push_str("\n**References**\n\n");
+        match &references {
+            Some(references) => {
+                let count = references.locations.len();
+                writeln!(
+                    contents,
+                    "{count} reference{}, press `r` to open the picker.",
+                    if count == 1 { "" } else { "s" }
+                )
+                .unwrap();
+            }
+            None => contents.push_str("_No references found_\n"),
+        }
+        if !failures.is_empty() {
+            writeln!(contents, "\n_Failed: {}_", failures.join(", ")).unwrap();
+        }
+
+        let has_any = hover.is_some() || definitions.is_some() || references.is_some();
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            for (ls_id, err) in &errors {
+                if !handle_server_exit(editor, *ls_id, err) {
+                    editor.set_error(err.to_string());
+                }
+            }
+            if !has_any && !failures.is_empty() {
+                // nothing at all came back, the errors above say why
+                return;
+            }
+            let contents =
+                ui::lsp::SymbolInfo::new(contents, definitions, references, editor.syn_loader.clone());
+            let popup = Popup::new(ui::lsp::SymbolInfo::ID, contents).auto_close(true);
+            compositor.replace_or_push(ui::lsp::SymbolInfo::ID, popup);
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+/// Renders the locations of `references` into the references view buffer as
+/// grep-style `path:line: snippet` lines, grouped by file, creating the
+/// buffer (in a horizontal split) if necessary, and records the query behind
+/// it. Snippets come from the open document when there is one and from disk
+/// otherwise.
+fn show_references_view(editor: &mut Editor, mut references: ReferencesView) {
+    references.locations.sort_unstable_by(|a, b| {
+        a.uri
+            .as_str()
+            .cmp(b.uri.as_str())
+            .then(a.range.start.cmp(&b.range.start))
+    });
+
+    let mut text = format!(
+        "{} reference{} to {}",
+        references.locations.len(),
+        if references.locations.len() == 1 {
+            ""
+        } else {
+            "s"
+        },
+        references.symbol
+    );
+    let mut last_path: Option<PathBuf> = None;
+    // files are contiguous after sorting, read each at most once
+    let mut file_contents: Option<(PathBuf, String)> = None;
+    for location in &references.locations {
+        let Ok(path) = location.uri.to_file_path() else {
+            continue;
+        };
+        if last_path.as_ref() != Some(&path) {
+            text.push('\n');
+        }
+        let line = location.range.start.line as usize;
+        let snippet = match editor.document_by_path(&path) {
+            Some(doc) if line < doc.text().len_lines() => doc.text().line(line).to_string(),
+            Some(_) => String::new(),
+            None => {
+                if file_contents.as_ref().map(|(p, _)| p) != Some(&path) {
+                    let contents = std::fs::read_to_string(&path).unwrap_or_default();
+                    file_contents = Some((path.clone(), contents));
+                }
+                let (_, contents) = file_contents.as_ref().unwrap();
+                contents.lines().nth(line).unwrap_or_default().to_string()
+            }
+        };
+        write!(
+            text,
+            "\n{}:{}: {}",
+            path::get_relative_path(&path).display(),
+            line + 1,
+            snippet.trim_end()
+        )
+        .unwrap();
+        last_path = Some(path);
+    }
+    text.push('\n');
+
+    let doc_id = references
+        .doc
+        .filter(|id| editor.documents.contains_key(id));
+    let doc_id = match doc_id {
+        Some(id) => {
+            // focus the split that already shows the view, or reopen one
+            let shown = editor
+                .tree
+                .views()
+                .find_map(|(view, _)| (view.doc == id).then_some(view.id));
+            match shown {
+                Some(view_id) => editor.focus(view_id),
+                None => editor.switch(id, Action::HorizontalSplit),
+            }
+            id
+        }
+        None => editor.new_file(Action::HorizontalSplit),
+    };
+    references.doc = Some(doc_id);
+
+    let view_id = editor.get_synced_view_id(doc_id);
+    let doc = doc_mut!(editor, &doc_id);
+    let end = doc.text().len_chars();
+    let transaction = Transaction::change(doc.text(), [(0, end, Some(text.into()))].into_iter());
+    let view = view_mut!(editor, view_id);
+    doc.apply(&transaction, view.id);
+    doc.append_changes_to_history(view);
+    doc.set_selection(view.id, Selection::point(0));
+    doc.readonly = true;
+    editor.references_view = Some(references);
+}
+
+/// Like `goto_reference`, but pins the results into a persistent scratch
+/// buffer of grep-style `path:line: snippet` lines instead of a transient
+/// picker, so the list can be revisited and narrowed during longer refactors.
+/// `ret` on a line jumps to it and `references_view_refresh` re-runs the
+/// query.
+pub fn goto_reference_view(cx: &mut Context) {
+    if !gate_on_server_ready(
+        cx,
+        LanguageServerFeature::GotoReference,
+        "goto_reference_view",
+    ) {
+        return;
+    }
+    let include_declaration = goto_reference_include_declaration(cx.editor);
     let (view, doc) = current!(cx.editor);
 
-    let language_server = language_server_with_feature!(cx.editor, doc, feature);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::GotoReference);
+    let language_server_id = language_server.id();
     let offset_encoding = language_server.offset_encoding();
-    let pos = doc.position(view.id, offset_encoding);
-    let future = request_provider(language_server, pos, doc.identifier()).unwrap();
+    let text_document = doc.identifier();
+    let position = doc.position(view.id, offset_encoding);
+    let barrier = language_server.document_sync_barrier(text_document.uri.clone(), doc.version());
+    let future = language_server
+        .goto_reference(text_document.clone(), position, include_declaration, None)
+        .unwrap();
+    let future = async move {
+        barrier.await;
+        future.await
+    };
+
+    // label the view with the symbol under the cursor (or the selection)
+    let text = doc.text().slice(..);
+    let primary_selection = doc.selection(view.id).primary();
+    let symbol: String = if primary_selection.len() > 1 {
+        primary_selection
+    } else {
+        use helix_core::textobject::{textobject_word, TextObject};
+        textobject_word(text, primary_selection, TextObject::Inside, 1, false)
+    }
+    .fragment(text)
+    .into();
 
     cx.callback(
         future,
-        move |editor, compositor, response: Option<lsp::GotoDefinitionResponse>| {
-            let items = to_locations(response);
-            if items.is_empty() {
-                editor.set_error("No definition found.");
-            } else {
-                goto_impl(editor, compositor, items, offset_encoding);
+        move |editor, _compositor, response: Option<Vec<lsp::Location>>| {
+            let locations = response.unwrap_or_default();
+            if locations.is_empty() {
+                editor.set_error("No references found.");
+                return;
             }
+            show_references_view(
+                editor,
+                ReferencesView {
+                    doc: None, // assigned by show_references_view
+                    symbol,
+                    language_server_id,
+                    offset_encoding,
+                    text_document,
+                    position,
+                    locations,
+                },
+            );
         },
     );
 }
 
-pub fn goto_declaration(cx: &mut Context) {
-    goto_single_impl(
-        cx,
-        LanguageServerFeature::GotoDeclaration,
-        |ls, pos, doc_id| ls.goto_declaration(doc_id, pos, None),
+/// Re-runs the query behind the references view and re-renders it in place,
+/// e.g. after edits added or removed references.
+pub fn references_view_refresh(cx: &mut Context) {
+    let Some(references) = cx.editor.references_view.clone() else {
+        cx.editor.set_error("No references view to refresh");
+        return;
+    };
+    let Some(language_server) = cx.editor.language_server_by_id(references.language_server_id)
+    else {
+        cx.editor.set_error("Language Server disappeared");
+        return;
+    };
+    let Some(future) = language_server.goto_reference(
+        references.text_document.clone(),
+        references.position,
+        goto_reference_include_declaration(cx.editor),
+        None,
+    ) else {
+        cx.editor
+            .set_error("Language server does not support goto-reference");
+        return;
+    };
+
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<Vec<lsp::Location>>| {
+            let locations = response.unwrap_or_default();
+            if locations.is_empty() {
+                editor.set_error("No references found.");
+                return;
+            }
+            show_references_view(
+                editor,
+                ReferencesView {
+                    locations,
+                    ..references
+                },
+            );
+        },
     );
 }
 
-pub fn goto_definition(cx: &mut Context) {
-    goto_single_impl(
-        cx,
-        LanguageServerFeature::GotoDefinition,
-        |ls, pos, doc_id| ls.goto_definition(doc_id, pos, None),
-    );
+/// Parses the grep-style `path:line:` prefix of a references view line.
+fn parse_references_view_line(line: &str) -> Option<(&str, usize)> {
+    // the path itself may contain colons (e.g. a Windows drive), so take the
+    // first colon that is followed by a line number and another colon
+    for (i, _) in line.match_indices(':') {
+        let rest = &line[i + 1..];
+        let Some(end) = rest.find(':') else { continue };
+        if let Ok(number) = rest[..end].parse::<usize>() {
+            return Some((&line[..i], number.checked_sub(1)?));
+        }
+    }
+    None
 }
 
-pub fn goto_type_definition(cx: &mut Context) {
-    goto_single_impl(
-        cx,
-        LanguageServerFeature::GotoTypeDefinition,
-        |ls, pos, doc_id| ls.goto_type_definition(doc_id, pos, None),
-    );
+/// Jumps to the location on the current line of the references view, matching
+/// the line against the recorded locations so the server's exact range is
+/// selected. Lines that no longer match one — because the buffer was narrowed
+/// by editing — still jump to the parsed path and line. Outside of the
+/// references view this is a no-op, which keeps the default `ret` binding
+/// inert in other buffers.
+pub fn references_view_jump(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let Some(references) = cx
+        .editor
+        .references_view
+        .as_ref()
+        .filter(|references| references.doc == Some(doc.id()))
+    else {
+        return;
+    };
+
+    let text = doc.text();
+    let line = text.char_to_line(doc.selection(view.id).primary().cursor(text.slice(..)));
+    let line_text = text.line(line).to_string();
+    let Some((path, target_line)) = parse_references_view_line(line_text.trim_end()) else {
+        cx.editor.set_error("No location on this line");
+        return;
+    };
+    let path = path::canonicalize(path);
+    let offset_encoding = references.offset_encoding;
+    let location = references
+        .locations
+        .iter()
+        .find(|location| {
+            location.range.start.line as usize == target_line
+                && location.uri.to_file_path().is_ok_and(|p| p == path)
+        })
+        .cloned();
+
+    match location {
+        Some(location) => jump_to_location(
+            cx.editor,
+            &location,
+            offset_encoding,
+            Action::Replace,
+            "references_view_jump",
+        ),
+        None => {
+            let start = lsp::Position::new(target_line as u32, 0);
+            jump_to_position(
+                cx.editor,
+                &path,
+                lsp::Range::new(start, start),
+                offset_encoding,
+                Action::Replace,
+                "references_view_jump",
+            );
+        }
+    }
 }
 
-pub fn goto_implementation(cx: &mut Context) {
-    goto_single_impl(
-        cx,
-        LanguageServerFeature::GotoImplementation,
-        |ls, pos, doc_id| ls.goto_implementation(doc_id, pos, None),
-    );
+pub fn signature_help(cx: &mut Context) {
+    cx.editor
+        .handlers
+        .trigger_signature_help(SignatureHelpInvoked::Manual, cx.editor)
 }
 
-pub fn goto_reference(cx: &mut Context) {
+/// Requests signature help at the cursor directly, without going through the
+/// signature help handler. The handler's state machine is built around typing
+/// triggers, so an explicit request (e.g. after navigating back into a call's
+/// arguments) bypasses it and reports the outcome either way.
+pub fn signature_help_here(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+
+    // TODO merge multiple language server signature help into one instead of just taking the first language server that supports it
+    let Some((language_server_id, future)) = doc
+        .language_servers_with_feature(LanguageServerFeature::SignatureHelp)
+        .find_map(|language_server| {
+            let pos = doc.position(view.id, language_server.offset_encoding());
+            let future =
+                language_server.text_document_signature_help(doc.identifier(), pos, None)?;
+            Some((language_server.id(), future))
+        })
+    else {
+        cx.editor
+            .set_error("No configured language server supports signature-help");
+        return;
+    };
+
+    cx.jobs.callback(async move {
+        let response = match future.await {
+            Ok(response) => response,
+            Err(err) => {
+                let call = move |editor: &mut Editor, _compositor: &mut Compositor| {
+                    if !handle_server_exit(editor, language_server_id, &err) {
+                        editor.set_error(err.to_string());
+                    }
+                };
+                return Ok(Callback::EditorCompositor(Box::new(call)));
+            }
+        };
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+            // unlike the typing-triggered path, an explicit request always
+            // answers, even if the answer is "nothing here"
+            if response
+                .as_ref()
+                .map_or(true, |help| help.signatures.is_empty())
+            {
+                editor.set_status("No signature help available at this position");
+                return;
+            }
+            crate::handlers::show_signature_help(
+                editor,
+                compositor,
+                SignatureHelpInvoked::Manual,
+                response,
+            );
+        };
+        Ok(Callback::EditorCompositor(Box::new(call)))
+    });
+}
+
+fn marked_string_to_markdown(contents: lsp::MarkedString) -> String {
+    match contents {
+        lsp::MarkedString::String(contents) => contents,
+        lsp::MarkedString::LanguageString(string) => {
+            if string.language == "markdown" {
+                string.value
+            } else {
+                format!("```{}\n{}\n```", string.language, string.value)
+            }
+        }
+    }
+}
+
+fn hover_contents_to_markdown(contents: lsp::HoverContents) -> String {
+    match contents {
+        lsp::HoverContents::Scalar(contents) => marked_string_to_markdown(contents),
+        lsp::HoverContents::Array(contents) => contents
+            .into_iter()
+            .map(marked_string_to_markdown)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        lsp::HoverContents::Markup(contents) => contents.value,
+    }
+}
+
+/// Splits a server's hover response into the popup's collapsible sections,
+/// labeled with the server the contents came from. A multi-part response
+/// (the `Array` variant, e.g. rust-analyzer's type + docs + implementations)
+/// becomes one section per part so each can be folded away on its own.
+fn hover_contents_to_sections(
+    server: &str,
+    contents: lsp::HoverContents,
+) -> Vec<ui::lsp::HoverSection> {
+    match contents {
+        lsp::HoverContents::Array(parts) if parts.len() > 1 => {
+            let total = parts.len();
+            parts
+                .into_iter()
+                .enumerate()
+                .map(|(i, part)| ui::lsp::HoverSection {
+                    header: format!("{server} ({}/{total})", i + 1),
+                    contents: marked_string_to_markdown(part),
+                })
+                .filter(|section| !section.contents.is_empty())
+                .collect()
+        }
+        contents => {
+            let contents = hover_contents_to_markdown(contents);
+            if contents.is_empty() {
+                Vec::new()
+            } else {
+                vec![ui::lsp::HoverSection {
+                    header: server.to_string(),
+                    contents,
+                }]
+            }
+        }
+    }
+}
+
+pub fn hover(cx: &mut Context) {
+    if !gate_on_server_ready(cx, LanguageServerFeature::Hover, "hover") {
+        return;
+    }
     let config = cx.editor.config();
     let (view, doc) = current!(cx.editor);
 
-    // TODO could probably support multiple language servers,
-    // not sure if there's a real practical use case for this though
+    // TODO support multiple language servers (merge UI somehow)
     let language_server =
-        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::GotoReference);
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::Hover);
     let offset_encoding = language_server.offset_encoding();
-    let pos = doc.position(view.id, offset_encoding);
-    let future = language_server
-        .goto_reference(
-            doc.identifier(),
-            pos,
-            config.lsp.goto_reference_include_declaration,
-            None,
-        )
-        .unwrap();
 
-    cx.callback(
-        future,
-        move |editor, compositor, response: Option<Vec<lsp::Location>>| {
-            let items = response.unwrap_or_default();
-            if items.is_empty() {
-                editor.set_error("No references found.");
-            } else {
-                goto_impl(editor, compositor, items, offset_encoding);
+    let selection = doc.selection(view.id);
+    if config.lsp.hover_all_cursors && selection.len() > 1 {
+        // Cap the number of simultaneous requests so a huge multi-cursor selection
+        // doesn't overwhelm the server.
+        const MAX_HOVER_REQUESTS: usize = 8;
+
+        let text = doc.text();
+        let mut futures: FuturesOrdered<_> = selection
+            .iter()
+            .take(MAX_HOVER_REQUESTS)
+            .map(|range| {
+                let cursor = range.cursor(text.slice(..));
+                let line = text.char_to_line(cursor) + 1;
+                let pos = helix_lsp::util::pos_to_lsp_pos(text, cursor, offset_encoding);
+                let request = language_server
+                    .text_document_hover(doc.identifier(), pos, None)
+                    .unwrap();
+                async move {
+                    let json = request.await?;
+                    let response: Option<lsp::Hover> = serde_json::from_value(json)?;
+                    anyhow::Ok((line, response))
+                }
+            })
+            .collect();
+        let capped = selection.len() - futures.len();
+
+        cx.jobs.callback(async move {
+            let mut sections = Vec::new();
+            while let Some((line, response)) = futures.try_next().await? {
+                if let Some(hover) = response {
+                    let contents = hover_contents_to_markdown(hover.contents);
+                    if !contents.is_empty() {
+                        sections.push(ui::lsp::HoverSection {
+                            header: format!("Line {line}"),
+                            contents,
+                        });
+                    }
+                }
             }
-        },
-    );
+            if capped != 0 {
+                sections.push(ui::lsp::HoverSection {
+                    header: format!("({capped} more cursors not queried)"),
+                    contents: String::new(),
+                });
+            }
+
+            let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+                if sections.is_empty() {
+                    editor.set_status("No hover information available");
+                    return;
+                }
+                let contents = ui::lsp::Hover::new(sections, editor.syn_loader.clone());
+                let popup = Popup::new(ui::lsp::Hover::ID, contents)
+                    .auto_close(!editor.config().lsp.refresh_hover_on_edit);
+                compositor.replace_or_push(ui::lsp::Hover::ID, popup);
+            };
+            Ok(Callback::EditorCompositor(Box::new(call)))
+        });
+        return;
+    }
+
+    let doc_id = doc.id();
+    let cursor = doc.selection(view.id).primary().cursor(doc.text().slice(..));
+    hover_at_position(cx.editor, doc_id, cursor, None);
+}
+
+/// Requests hover at the `pos` character index in `doc_id` and shows the
+/// response in a popup. The popup is anchored at `anchor` (a screen-space
+/// position, used by mouse hover) when given, otherwise next to the primary
+/// cursor.
+pub fn hover_at_position(
+    editor: &mut Editor,
+    doc_id: DocumentId,
+    pos: usize,
+    anchor: Option<helix_core::Position>,
+) {
+    let Some(doc) = editor.documents.get(&doc_id) else {
+        return;
+    };
+    // the position was captured earlier (e.g. when the mouse came to rest)
+    // and the document may have shrunk since
+    if pos > doc.text().len_chars() {
+        return;
+    }
+
+    let Some(mut futures) = hover_requests(doc, pos) else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut sections = Vec::new();
+        let mut failed = Vec::new();
+        while let Some((name, language_server_id, result)) = futures.next().await {
+            match result {
+                Ok(Some(hover)) => {
+                    // hover.contents / .range <- used for visualizing
+                    sections.extend(hover_contents_to_sections(&name, hover.contents))
+                }
+                Ok(None) => (),
+                Err(err) => {
+                    log::error!("hover request failed: {err}");
+                    failed.push((language_server_id, err));
+                }
+            }
+        }
+        crate::job::dispatch(move |editor, compositor| {
+            // hover errors stay quiet, but a server exit is surfaced so
+            // subsequent requests skip the server
+            for (language_server_id, err) in &failed {
+                handle_server_exit(editor, *language_server_id, err);
+            }
+            if sections.is_empty() {
+                return;
+            }
+            let contents = ui::lsp::Hover::new(sections, editor.syn_loader.clone());
+            let popup = Popup::new(ui::lsp::Hover::ID, contents)
+                .position(anchor)
+                .auto_close(!editor.config().lsp.refresh_hover_on_edit);
+            compositor.replace_or_push(ui::lsp::Hover::ID, popup);
+        })
+        .await;
+    });
+}
+
+/// Issues a hover request at the `pos` character index to every attached
+/// server supporting hover, yielding `(server name, server id, response)`
+/// triples in configuration order. Returns `None` when no server supports
+/// hover.
+#[allow(clippy::type_complexity)]
+fn hover_requests(
+    doc: &Document,
+    pos: usize,
+) -> Option<
+    FuturesOrdered<
+        impl Future<Output = (String, LanguageServerId, helix_lsp::Result<Option<lsp::Hover>>)>,
+    >,
+> {
+    let mut seen_language_servers = HashSet::new();
+    let futures: FuturesOrdered<_> = doc
+        .language_servers_with_feature(LanguageServerFeature::Hover)
+        .filter(|ls| seen_language_servers.insert(ls.id()))
+        .filter_map(|language_server| {
+            let lsp_pos =
+                helix_lsp::util::pos_to_lsp_pos(doc.text(), pos, language_server.offset_encoding());
+            let future = language_server.text_document_hover(doc.identifier(), lsp_pos, None)?;
+            let name = language_server.name().to_string();
+            let language_server_id = language_server.id();
+            Some(async move {
+                let result: helix_lsp::Result<Option<lsp::Hover>> =
+                    async move { Ok(serde_json::from_value(future.await?)?) }.await;
+                (name, language_server_id, result)
+            })
+        })
+        .collect();
+    (!futures.is_empty()).then_some(futures)
+}
+
+/// Re-issues the hover request at the current primary cursor and replaces the
+/// contents of the open hover popup with the response.
+///
+/// Replacing the popup also resets its anchor, so the popup follows the cursor
+/// to its position after the edit. The caller is expected to have checked that
+/// a hover popup is actually on screen.
+pub fn refresh_hover(editor: &mut Editor) {
+    let (view, doc) = current!(editor);
+    let cursor = doc.selection(view.id).primary().cursor(doc.text().slice(..));
+    let Some(mut futures) = hover_requests(doc, cursor) else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut sections = Vec::new();
+        while let Some((name, _, result)) = futures.next().await {
+            match result {
+                Ok(Some(hover)) => {
+                    sections.extend(hover_contents_to_sections(&name, hover.contents))
+                }
+                Ok(None) => (),
+                Err(err) => log::error!("failed to refresh hover: {err}"),
+            }
+        }
+        crate::job::dispatch(move |editor, compositor| {
+            // the popup may have been dismissed while the request was in flight
+            if compositor
+                .find_id::<Popup<ui::lsp::Hover>>(ui::lsp::Hover::ID)
+                .is_none()
+            {
+                return;
+            }
+            if sections.is_empty() {
+                // nothing to hover at the new position: close the popup
+                compositor.remove(ui::lsp::Hover::ID);
+                return;
+            }
+            let contents = ui::lsp::Hover::new(sections, editor.syn_loader.clone());
+            let popup = Popup::new(ui::lsp::Hover::ID, contents)
+                .auto_close(!editor.config().lsp.refresh_hover_on_edit);
+            compositor.replace_or_push(ui::lsp::Hover::ID, popup);
+        })
+        .await;
+    });
+}
+
+/// Creates the `rename-to:` prompt. When `target` is set, the rename request
+/// is issued against that document and position instead of the cursor of the
+/// focused view, so a symbol can be renamed without jumping to it.
+/// Predicts the post-apply extent of the rename edit in `doc` closest to
+/// `cursor`, so the selection can be moved over the new name as visual
+/// confirmation once the workspace edit lands. Must be called before the
+/// edit is applied.
+fn locate_renamed_range(
+    doc: &Document,
+    workspace_edit: &lsp::WorkspaceEdit,
+    offset_encoding: OffsetEncoding,
+    cursor: usize,
+) -> Option<Range> {
+    let edits = collect_workspace_edit_ranges(doc, workspace_edit, offset_encoding)?;
+    let index = edits
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, (range, _))| {
+            // distance to the cursor; an edit containing it wins outright
+            range.from().saturating_sub(cursor) + cursor.saturating_sub(range.to())
+        })
+        .map(|(index, _)| index)?;
+
+    // shift the occurrence by the size change of every edit before it
+    let (range, new_text) = &edits[index];
+    let mut start = range.from() as isize;
+    for (range, new_text) in &edits[..index] {
+        start += new_text.chars().count() as isize - (range.to() - range.from()) as isize;
+    }
+    let start = usize::try_from(start).ok()?;
+    Some(Range::new(start, start + new_text.chars().count()))
 }
 
-pub fn signature_help(cx: &mut Context) {
-    cx.editor
-        .handlers
-        .trigger_signature_help(SignatureHelpInvoked::Manual, cx.editor)
+fn create_rename_prompt(
+    editor: &Editor,
+    prefill: String,
+    language_server_id: Option<LanguageServerId>,
+    target: Option<(DocumentId, usize)>,
+) -> Box<ui::Prompt> {
+    let prompt = ui::Prompt::new(
+        "rename-to:".into(),
+        None,
+        ui::completers::none,
+        move |cx: &mut compositor::Context, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate {
+                return;
+            }
+            let (doc, pos) = match target {
+                Some((doc_id, pos)) => {
+                    let Some(doc) = cx.editor.documents.get(&doc_id) else {
+                        cx.editor.set_error("Document to rename in was closed");
+                        return;
+                    };
+                    (doc, pos)
+                }
+                None => {
+                    let (view, doc) = current_ref!(cx.editor);
+                    let text = doc.text().slice(..);
+                    (doc, doc.selection(view.id).primary().cursor(text))
+                }
+            };
+
+            let Some(language_server) = doc
+                .language_servers_with_feature(LanguageServerFeature::RenameSymbol)
+                .find(|ls| language_server_id.map_or(true, |id| id == ls.id()))
+            else {
+                cx.editor
+                    .set_error("No configured language server supports symbol renaming");
+                return;
+            };
+
+            let offset_encoding = language_server.offset_encoding();
+            let pos = pos_to_lsp_pos(doc.text(), pos, offset_encoding);
+            let future = language_server
+                .rename_symbol(doc.identifier(), pos, input.to_string())
+                .unwrap();
+
+            match block_on(future) {
+                Ok(edits) => {
+                    // predict where the renamed occurrence nearest to the
+                    // cursor in the focused file ends up, the selection moves
+                    // there afterwards as confirmation of the rename
+                    let renamed = {
+                        let (view, doc) = current_ref!(cx.editor);
+                        let cursor = doc.selection(view.id).primary().cursor(doc.text().slice(..));
+                        locate_renamed_range(doc, &edits, offset_encoding, cursor)
+                            .map(|range| (doc.id(), range))
+                    };
+                    // best effort: a stale reference into a deleted file
+                    // shouldn't prevent the rename everywhere else
+                    let skipped = cx
+                        .editor
+                        .apply_workspace_edit_best_effort(offset_encoding, &edits);
+                    if let Some((doc_id, range)) = renamed {
+                        let scrolloff = cx.editor.config().scrolloff;
+                        let (view, doc) = current!(cx.editor);
+                        if doc.id() == doc_id && range.to() <= doc.text().len_chars() {
+                            doc.set_selection(view.id, Selection::single(range.from(), range.to()));
+                            view.ensure_cursor_in_view(doc, scrolloff);
+                        }
+                    }
+                    if !skipped.is_empty() {
+                        let reasons: Vec<_> = skipped
+                            .iter()
+                            .map(|skip| {
+                                format!("change {}: {}", skip.change_idx, skip.kind.to_string())
+                            })
+                            .collect();
+                        cx.editor.set_error(format!(
+                            "Renamed, but {} change(s) were skipped ({})",
+                            skipped.len(),
+                            reasons.join(", ")
+                        ));
+                    }
+                }
+                Err(err) => cx.editor.set_error(err.to_string()),
+            }
+        },
+    )
+    .with_line(prefill, editor);
+
+    Box::new(prompt)
 }
 
-pub fn hover(cx: &mut Context) {
-    let (view, doc) = current!(cx.editor);
+/// Renames the symbol at `location` without focusing its document: the
+/// document is opened in the background, the rename prompt and workspace edit
+/// run against the symbol's position, and the current view stays where it is.
+/// Bound to the alternate picker action of the symbol pickers.
+fn rename_symbol_at_location(
+    editor: &mut Editor,
+    location: &lsp::Location,
+    offset_encoding: OffsetEncoding,
+    symbol_name: String,
+) {
+    let path = match location.uri.to_file_path() {
+        Ok(path) => path,
+        Err(_) => {
+            editor.set_error(format!(
+                "unable to convert URI to filepath: {}",
+                location.uri
+            ));
+            return;
+        }
+    };
+    let doc_id = match editor.open(&path, Action::Load) {
+        Ok(doc_id) => doc_id,
+        Err(err) => {
+            editor.set_error(format!("failed to open path: {:?}: {:?}", path, err));
+            return;
+        }
+    };
+    let doc = doc!(editor, &doc_id);
+    let Some(pos) = lsp_pos_to_pos(doc.text(), location.range.start, offset_encoding) else {
+        editor.set_error("symbol position is out of bounds");
+        return;
+    };
 
-    // TODO support multiple language servers (merge UI somehow)
-    let language_server =
-        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::Hover);
-    // TODO: factor out a doc.position_identifier() that returns lsp::TextDocumentPositionIdentifier
-    let pos = doc.position(view.id, language_server.offset_encoding());
-    let future = language_server
-        .text_document_hover(doc.identifier(), pos, None)
-        .unwrap();
+    let language_server_with_prepare_rename_support = doc
+        .language_servers_with_feature(LanguageServerFeature::RenameSymbol)
+        .find(|ls| {
+            matches!(
+                ls.capabilities().rename_provider,
+                Some(lsp::OneOf::Right(lsp::RenameOptions {
+                    prepare_provider: Some(true),
+                    ..
+                }))
+            )
+        });
 
-    cx.callback(
-        future,
-        move |editor, compositor, response: Option<lsp::Hover>| {
-            if let Some(hover) = response {
-                // hover.contents / .range <- used for visualizing
-
-                fn marked_string_to_markdown(contents: lsp::MarkedString) -> String {
-                    match contents {
-                        lsp::MarkedString::String(contents) => contents,
-                        lsp::MarkedString::LanguageString(string) => {
-                            if string.language == "markdown" {
-                                string.value
-                            } else {
-                                format!("```{}\n{}\n```", string.language, string.value)
+    if let Some(language_server) = language_server_with_prepare_rename_support {
+        let ls_id = language_server.id();
+        let ls_encoding = language_server.offset_encoding();
+        let lsp_pos = pos_to_lsp_pos(doc.text(), pos, ls_encoding);
+        let future = language_server
+            .prepare_rename(doc.identifier(), lsp_pos)
+            .unwrap();
+        tokio::spawn(async move {
+            let response: Result<Option<lsp::PrepareRenameResponse>, _> = future
+                .await
+                .and_then(|json| serde_json::from_value(json).map_err(Into::into));
+            crate::job::dispatch(move |editor, compositor| {
+                let prefill = match response {
+                    Ok(Some(lsp::PrepareRenameResponse::Range(range))) => {
+                        let Some(doc) = editor.documents.get(&doc_id) else {
+                            return;
+                        };
+                        match lsp_range_to_range(doc.text(), range, ls_encoding) {
+                            Some(range) => range.fragment(doc.text().slice(..)).into(),
+                            None => {
+                                editor.set_error("lsp sent invalid selection range for rename");
+                                return;
                             }
                         }
                     }
-                }
-
-                let contents = match hover.contents {
-                    lsp::HoverContents::Scalar(contents) => marked_string_to_markdown(contents),
-                    lsp::HoverContents::Array(contents) => contents
-                        .into_iter()
-                        .map(marked_string_to_markdown)
-                        .collect::<Vec<_>>()
-                        .join("\n\n"),
-                    lsp::HoverContents::Markup(contents) => contents.value,
+                    Ok(Some(lsp::PrepareRenameResponse::RangeWithPlaceholder {
+                        placeholder,
+                        ..
+                    })) => placeholder,
+                    Ok(Some(lsp::PrepareRenameResponse::DefaultBehavior { .. })) => symbol_name,
+                    Ok(None) => {
+                        editor.set_error("lsp did not respond to prepare rename request");
+                        return;
+                    }
+                    Err(err) => {
+                        editor.set_error(prepare_rename_error(err));
+                        return;
+                    }
                 };
 
-                // skip if contents empty
+                let prompt =
+                    create_rename_prompt(editor, prefill, Some(ls_id), Some((doc_id, pos)));
+                compositor.push(prompt);
+            })
+            .await;
+        });
+    } else if doc
+        .language_servers_with_feature(LanguageServerFeature::RenameSymbol)
+        .next()
+        .is_some()
+    {
+        crate::job::dispatch_blocking(move |editor, compositor| {
+            let prompt = create_rename_prompt(editor, symbol_name, None, Some((doc_id, pos)));
+            compositor.push(prompt);
+        });
+    } else {
+        editor.set_error("No configured language server supports symbol renaming");
+    }
+}
 
-                let contents = ui::Markdown::new(contents, editor.syn_loader.clone());
-                let popup = Popup::new("hover", contents).auto_close(true);
-                compositor.replace_or_push("hover", popup);
-            }
-        },
-    );
+/// Servers that reject a rename from prepareRename usually answer with a
+/// human-readable reason ("cannot rename keyword"); show it verbatim instead
+/// of wrapping it in the generic protocol error message.
+fn prepare_rename_error(err: helix_lsp::Error) -> String {
+    match err {
+        helix_lsp::Error::Rpc(err) => err.message,
+        err => err.to_string(),
+    }
 }
 
 pub fn rename_symbol(cx: &mut Context) {
@@ -1161,49 +6064,9 @@ pub fn rename_symbol(cx: &mut Context) {
         }
     }
 
-    fn create_rename_prompt(
-        editor: &Editor,
-        prefill: String,
-        language_server_id: Option<LanguageServerId>,
-    ) -> Box<ui::Prompt> {
-        let prompt = ui::Prompt::new(
-            "rename-to:".into(),
-            None,
-            ui::completers::none,
-            move |cx: &mut compositor::Context, input: &str, event: PromptEvent| {
-                if event != PromptEvent::Validate {
-                    return;
-                }
-                let (view, doc) = current!(cx.editor);
-
-                let Some(language_server) = doc
-                    .language_servers_with_feature(LanguageServerFeature::RenameSymbol)
-                    .find(|ls| language_server_id.map_or(true, |id| id == ls.id()))
-                else {
-                    cx.editor
-                        .set_error("No configured language server supports symbol renaming");
-                    return;
-                };
-
-                let offset_encoding = language_server.offset_encoding();
-                let pos = doc.position(view.id, offset_encoding);
-                let future = language_server
-                    .rename_symbol(doc.identifier(), pos, input.to_string())
-                    .unwrap();
-
-                match block_on(future) {
-                    Ok(edits) => {
-                        let _ = cx.editor.apply_workspace_edit(offset_encoding, &edits);
-                    }
-                    Err(err) => cx.editor.set_error(err.to_string()),
-                }
-            },
-        )
-        .with_line(prefill, editor);
-
-        Box::new(prompt)
+    if !gate_on_server_ready(cx, LanguageServerFeature::RenameSymbol, "rename_symbol") {
+        return;
     }
-
     let (view, doc) = current_ref!(cx.editor);
 
     if doc
@@ -1235,9 +6098,18 @@ pub fn rename_symbol(cx: &mut Context) {
         let future = language_server
             .prepare_rename(doc.identifier(), pos)
             .unwrap();
-        cx.callback(
-            future,
-            move |editor, compositor, response: Option<lsp::PrepareRenameResponse>| {
+        tokio::spawn(async move {
+            let response: Result<Option<lsp::PrepareRenameResponse>, _> = future
+                .await
+                .and_then(|json| serde_json::from_value(json).map_err(Into::into));
+            crate::job::dispatch(move |editor, compositor| {
+                let response = match response {
+                    Ok(response) => response,
+                    Err(err) => {
+                        editor.set_error(prepare_rename_error(err));
+                        return;
+                    }
+                };
                 let prefill = match get_prefill_from_lsp_response(editor, offset_encoding, response)
                 {
                     Ok(p) => p,
@@ -1247,14 +6119,15 @@ pub fn rename_symbol(cx: &mut Context) {
                     }
                 };
 
-                let prompt = create_rename_prompt(editor, prefill, Some(ls_id));
+                let prompt = create_rename_prompt(editor, prefill, Some(ls_id), None);
 
                 compositor.push(prompt);
-            },
-        );
+            })
+            .await;
+        });
     } else {
         let prefill = get_prefill_from_word_boundary(cx.editor);
-        let prompt = create_rename_prompt(cx.editor, prefill, None);
+        let prompt = create_rename_prompt(cx.editor, prefill, None, None);
         cx.push_layer(prompt);
     }
 }
@@ -1299,6 +6172,224 @@ pub fn select_references_to_symbol_under_cursor(cx: &mut Context) {
     );
 }
 
+pub fn next_document_highlight(cx: &mut Context) {
+    cycle_document_highlight(cx, Direction::Forward);
+}
+
+pub fn prev_document_highlight(cx: &mut Context) {
+    cycle_document_highlight(cx, Direction::Backward);
+}
+
+/// Moves the primary selection to the next/previous document highlight of the
+/// symbol under the cursor (wrapping), without touching the other selections.
+/// The highlights are cached on the editor and reused while the cursor stays
+/// on the symbol, so cycling does not re-query the server on every press.
+fn cycle_document_highlight(cx: &mut Context, direction: Direction) {
+    let (view, doc) = current!(cx.editor);
+    let cursor = doc.selection(view.id).primary().cursor(doc.text().slice(..));
+
+    if let Some(cached) = &cx.editor.cached_document_highlights {
+        if cached.doc == doc.id()
+            && cached.version == doc.version()
+            && cached.ranges.iter().any(|range| range.contains(cursor))
+        {
+            let ranges = cached.ranges.clone();
+            move_to_adjacent_highlight(cx.editor, &ranges, direction);
+            return;
+        }
+    }
+
+    let (view, doc) = current!(cx.editor);
+    let language_server =
+        language_server_with_feature!(cx.editor, doc, LanguageServerFeature::DocumentHighlight);
+    let offset_encoding = language_server.offset_encoding();
+    let pos = doc.position(view.id, offset_encoding);
+    let future = language_server
+        .text_document_document_highlight(doc.identifier(), pos, None)
+        .unwrap();
+
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<Vec<lsp::DocumentHighlight>>| {
+            let highlights = match response {
+                Some(highlights) if !highlights.is_empty() => highlights,
+                _ => {
+                    editor.set_status("No document highlights under cursor");
+                    return;
+                }
+            };
+            let doc = doc!(editor);
+            let text = doc.text();
+            let mut ranges: Vec<_> = highlights
+                .iter()
+                .filter_map(|highlight| lsp_range_to_range(text, highlight.range, offset_encoding))
+                .collect();
+            ranges.sort_unstable_by_key(|range| range.from());
+            editor.cached_document_highlights = Some(CachedDocumentHighlights {
+                doc: doc.id(),
+                version: doc.version(),
+                ranges: ranges.clone(),
+            });
+            // a fresh query marks the start of a cycling session; the cached
+            // fast path deliberately skips this so the whole session leaves a
+            // single jumplist entry
+            let (view, doc) = current!(editor);
+            push_jump(view, doc);
+            move_to_adjacent_highlight(editor, &ranges, direction);
+        },
+    );
+}
+
+fn move_to_adjacent_highlight(editor: &mut Editor, ranges: &[Range], direction: Direction) {
+    let config = editor.config();
+    let (view, doc) = current!(editor);
+    let text = doc.text().slice(..);
+    let cursor = doc.selection(view.id).primary().cursor(text);
+
+    let index = match (
+        ranges.iter().position(|range| range.contains(cursor)),
+        direction,
+    ) {
+        (Some(i), Direction::Forward) => (i + 1) % ranges.len(),
+        (Some(i), Direction::Backward) => (i + ranges.len() - 1) % ranges.len(),
+        // the cursor sits between highlights: take the nearest one in the
+        // requested direction, wrapping around the document
+        (None, Direction::Forward) => ranges
+            .iter()
+            .position(|range| range.from() > cursor)
+            .unwrap_or(0),
+        (None, Direction::Backward) => ranges
+            .iter()
+            .rposition(|range| range.to() <= cursor)
+            .unwrap_or(ranges.len() - 1),
+    };
+
+    let mut selection = doc.selection(view.id).clone();
+    *selection.primary_mut() = ranges[index];
+    doc.set_selection(view.id, selection);
+    view.ensure_cursor_in_view(doc, config.scrolloff);
+}
+
+pub fn compute_inline_values_for_all_views(editor: &mut Editor, jobs: &mut crate::job::Jobs) {
+    if !editor.config().lsp.display_inline_values {
+        return;
+    }
+
+    // Inline values are only meaningful while the debuggee is stopped on a frame.
+    let Some(frame) = editor.current_stack_frame() else {
+        return;
+    };
+    let frame_id = frame.id as i32;
+    // convert from 1-indexed DAP lines/columns
+    let stopped_line = frame.line.saturating_sub(1) as u32;
+    let stopped_location = lsp::Range::new(
+        lsp::Position::new(stopped_line, frame.column.saturating_sub(1) as u32),
+        lsp::Position::new(
+            frame.end_line.unwrap_or(frame.line).saturating_sub(1) as u32,
+            frame.end_column.unwrap_or(frame.column).saturating_sub(1) as u32,
+        ),
+    );
+
+    for (view, _) in editor.tree.views() {
+        let doc = match editor.documents.get(&view.doc) {
+            Some(doc) => doc,
+            None => continue,
+        };
+        if let Some(callback) = compute_inline_values_for_view(view, doc, frame_id, stopped_location)
+        {
+            jobs.callback(callback);
+        }
+    }
+}
+
+fn compute_inline_values_for_view(
+    view: &View,
+    doc: &Document,
+    frame_id: i32,
+    stopped_location: lsp::Range,
+) -> Option<std::pin::Pin<Box<impl Future<Output = Result<crate::job::Callback, anyhow::Error>>>>> {
+    let view_id = view.id;
+    let doc_id = view.doc;
+
+    let language_server = doc
+        .language_servers_with_feature(LanguageServerFeature::InlineValues)
+        .next()?;
+    let offset_encoding = language_server.offset_encoding();
+
+    // Like inlay hints, only request values for the range around the view instead
+    // of the whole file; anything further away is invisible anyway and execution
+    // will usually stay close to the stopped location.
+    let doc_text = doc.text();
+    let len_lines = doc_text.len_lines();
+    let view_height = view.inner_height();
+    let first_visible_line = doc_text.char_to_line(view.offset.anchor.min(doc_text.len_chars()));
+    let first_line = first_visible_line.saturating_sub(view_height);
+    let last_line = first_visible_line
+        .saturating_add(view_height.saturating_mul(2))
+        .min(len_lines);
+
+    let doc_slice = doc_text.slice(..);
+    let first_char_in_range = doc_slice.line_to_char(first_line);
+    let last_char_in_range = doc_slice.line_to_char(last_line);
+
+    let range = helix_lsp::util::range_to_lsp_range(
+        doc_text,
+        helix_core::Range::new(first_char_in_range, last_char_in_range),
+        offset_encoding,
+    );
+
+    let context = lsp::InlineValueContext {
+        frame_id,
+        stopped_location,
+    };
+
+    let callback = super::make_job_callback(
+        language_server.text_document_inline_values(doc.identifier(), range, context, None)?,
+        move |editor, _compositor, response: Option<Vec<lsp::InlineValue>>| {
+            // The config was modified or the window was closed while the request was in flight
+            if !editor.config().lsp.display_inline_values || editor.tree.try_get(view_id).is_none()
+            {
+                return;
+            }
+
+            // Add annotations to relevant document, not the current one (it may have changed in between)
+            let doc = match editor.documents.get_mut(&doc_id) {
+                Some(doc) => doc,
+                None => return,
+            };
+
+            let values = response.unwrap_or_default();
+            let doc_text = doc.text();
+
+            let mut annotations = Vec::with_capacity(values.len());
+            for value in values {
+                // Only text values can be rendered directly; variable lookups and
+                // evaluatable expressions would require another round-trip through
+                // the debug adapter.
+                let lsp::InlineValue::Text(text) = value else {
+                    continue;
+                };
+                let char_idx = match helix_lsp::util::lsp_pos_to_pos(
+                    doc_text,
+                    text.range.end,
+                    offset_encoding,
+                ) {
+                    Some(pos) => pos,
+                    None => continue,
+                };
+                annotations.push(InlineAnnotation::new(char_idx, format!(" {}", text.text)));
+            }
+
+            // Overlapping annotations are ignored apart from the first one, so ensure
+            // they're sorted even if the server sends them out of order.
+            annotations.sort_unstable_by_key(|annotation| annotation.char_idx);
+            doc.set_inline_values(view_id, annotations);
+        },
+    );
+
+    Some(callback)
+}
+
 pub fn compute_inlay_hints_for_all_views(editor: &mut Editor, jobs: &mut crate::job::Jobs) {
     if !editor.config().lsp.display_inlay_hints {
         return;
@@ -1357,11 +6448,12 @@ fn compute_inlay_hints_for_view(
     let first_char_in_range = doc_slice.line_to_char(first_line);
     let last_char_in_range = doc_slice.line_to_char(last_line);
 
-    let range = helix_lsp::util::range_to_lsp_range(
-        doc_text,
+    let range = lsp_position::range_to_lsp_range(
+        doc,
         helix_core::Range::new(first_char_in_range, last_char_in_range),
-        language_server.offset_encoding(),
+        language_server,
     );
+    let view_width = view.inner_width(doc) as usize;
 
     let offset_encoding = language_server.offset_encoding();
 
@@ -1372,6 +6464,8 @@ fn compute_inlay_hints_for_view(
             if !editor.config().lsp.display_inlay_hints || editor.tree.try_get(view_id).is_none() {
                 return;
             }
+            let priority =
+                AnnotationPriority::complete_order(&editor.config().annotation_priority);
 
             // Add annotations to relevant document, not the current one (it may have changed in between)
             let doc = match editor.documents.get_mut(&doc_id) {
@@ -1441,20 +6535,268 @@ fn compute_inlay_hints_for_view(
                 }
             }
 
-            doc.set_inlay_hints(
-                view_id,
-                DocumentInlayHints {
-                    id: new_doc_inlay_hints_id,
-                    type_inlay_hints,
-                    parameter_inlay_hints,
-                    other_inlay_hints,
-                    padding_before_inlay_hints,
-                    padding_after_inlay_hints,
-                },
-            );
+            let mut inlay_hints = DocumentInlayHints {
+                id: new_doc_inlay_hints_id,
+                type_inlay_hints,
+                parameter_inlay_hints,
+                other_inlay_hints,
+                padding_before_inlay_hints,
+                padding_after_inlay_hints,
+            };
+            apply_annotation_priority(doc, view_width, &priority, &mut inlay_hints);
+            doc.set_inlay_hints(view_id, inlay_hints);
             doc.inlay_hints_oudated = false;
         },
     );
 
     Some(callback)
 }
+
+/// Applies `editor.annotation-priority` to freshly computed inlay hints: on
+/// lines where the text plus its annotations no longer fit `view_width`
+/// columns, [budget_line_hints] shortens and then drops the lowest-priority
+/// hints. When diagnostics are ranked first (the default), a line carrying a
+/// diagnostic additionally keeps room for its end-of-line message instead of
+/// letting long hints push it off-screen. Width accounting is in chars,
+/// which matches the column count for typical annotation text.
+fn apply_annotation_priority(
+    doc: &Document,
+    view_width: usize,
+    priority: &[AnnotationPriority],
+    hints: &mut DocumentInlayHints,
+) {
+    let rank = |kind: AnnotationPriority| {
+        priority
+            .iter()
+            .position(|&ranked| ranked == kind)
+            .unwrap_or(priority.len())
+    };
+    let doc_text = doc.text();
+
+    // group the hints per line, priority first and document order within a
+    // kind; the kind vectors are rebuilt from the survivors below
+    let mut per_line: BTreeMap<usize, Vec<(AnnotationPriority, InlineAnnotation)>> =
+        BTreeMap::new();
+    for (kind, annotations) in [
+        (
+            AnnotationPriority::ParameterHints,
+            std::mem::take(&mut hints.parameter_inlay_hints),
+        ),
+        (
+            AnnotationPriority::TypeHints,
+            std::mem::take(&mut hints.type_inlay_hints),
+        ),
+        (
+            AnnotationPriority::OtherHints,
+            std::mem::take(&mut hints.other_inlay_hints),
+        ),
+    ] {
+        for annotation in annotations {
+            let line = doc_text.char_to_line(annotation.char_idx.min(doc_text.len_chars()));
+            per_line.entry(line).or_default().push((kind, annotation));
+        }
+    }
+
+    let mut dropped = Vec::new();
+    for (line, mut line_hints) in per_line {
+        let line_width =
+            helix_core::line_ending::line_end_char_index(&doc_text.slice(..), line)
+                - doc_text.line_to_char(line);
+        let mut budget = view_width.saturating_sub(line_width);
+        if priority.first() == Some(&AnnotationPriority::Diagnostics) {
+            if let Some(diag) = doc.diagnostics().iter().find(|diag| {
+                doc_text.char_to_line(diag.range.start.min(doc_text.len_chars())) == line
+            }) {
+                let message_width = diag
+                    .message
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .count()
+                    + 2; // the separator the diagnostic is rendered with
+                budget = budget.saturating_sub(message_width.min(view_width / 2));
+            }
+        }
+        line_hints.sort_by_key(|&(kind, _)| rank(kind));
+        let (mut kept, dropped_on_line) = budget_line_hints(budget, line_hints);
+        dropped.extend(dropped_on_line);
+        // the annotation layers must stay sorted by position
+        kept.sort_by_key(|(_, annotation)| annotation.char_idx);
+        for (kind, annotation) in kept {
+            let inlay_hints_vec = match kind {
+                AnnotationPriority::ParameterHints => &mut hints.parameter_inlay_hints,
+                AnnotationPriority::TypeHints => &mut hints.type_inlay_hints,
+                _ => &mut hints.other_inlay_hints,
+            };
+            inlay_hints_vec.push(annotation);
+        }
+    }
+
+    // a dropped hint takes its padding with it
+    for char_idx in dropped {
+        for padding in [
+            &mut hints.padding_before_inlay_hints,
+            &mut hints.padding_after_inlay_hints,
+        ] {
+            if let Some(pos) = padding
+                .iter()
+                .position(|annotation| annotation.char_idx == char_idx)
+            {
+                padding.remove(pos);
+            }
+        }
+    }
+}
+
+/// Fits one line's hints, ordered by descending priority, into `budget`
+/// columns: hints are kept whole until the budget runs out, the first
+/// overflowing hint is shortened to the remaining columns with a trailing
+/// `…` (or dropped when not even that fits) and every hint after it is
+/// dropped. Returns the survivors and the positions of the dropped hints.
+fn budget_line_hints(
+    budget: usize,
+    hints: Vec<(AnnotationPriority, InlineAnnotation)>,
+) -> (Vec<(AnnotationPriority, InlineAnnotation)>, Vec<usize>) {
+    let mut used = 0;
+    let mut kept = Vec::with_capacity(hints.len());
+    let mut dropped = Vec::new();
+    for (kind, mut annotation) in hints {
+        let width = annotation.text.chars().count();
+        if used + width <= budget {
+            used += width;
+            kept.push((kind, annotation));
+            continue;
+        }
+        let remaining = budget - used;
+        // a bare `…` carries no information of its own
+        if remaining > 1 {
+            let text: String = annotation.text.chars().take(remaining - 1).collect();
+            annotation.text = format!("{text}…").into();
+            used = budget;
+            kept.push((kind, annotation));
+        } else {
+            dropped.push(annotation.char_idx);
+        }
+    }
+    (kept, dropped)
+}
+
+/// The feature a command requires at least one attached language server to
+/// provide, used to tag unavailable entries in the command palette and the
+/// keybinding infobox. This must stay cheap — capability inspection only, no
+/// requests — since the palette evaluates it for every entry.
+pub fn required_feature(command: &str) -> Option<LanguageServerFeature> {
+    let feature = match command {
+        "symbol_picker" | "symbol_method_picker" | "symbol_for_diagnostic" => {
+            LanguageServerFeature::DocumentSymbols
+        }
+        "workspace_symbol_picker" => LanguageServerFeature::WorkspaceSymbols,
+        "pull_diagnostics" => LanguageServerFeature::PullDiagnostics,
+        "code_action" | "quick_fix_under_cursor" => LanguageServerFeature::CodeAction,
+        "goto_declaration" => LanguageServerFeature::GotoDeclaration,
+        "goto_definition" => LanguageServerFeature::GotoDefinition,
+        "goto_type_definition" => LanguageServerFeature::GotoTypeDefinition,
+        "goto_implementation" | "count_implementations" | "implementations_picker" => {
+            LanguageServerFeature::GotoImplementation
+        }
+        "goto_reference" => LanguageServerFeature::GotoReference,
+        "signature_help" | "signature_help_here" => LanguageServerFeature::SignatureHelp,
+        "hover" => LanguageServerFeature::Hover,
+        "rename_symbol" => LanguageServerFeature::RenameSymbol,
+        "select_references_to_symbol_under_cursor"
+        | "next_document_highlight"
+        | "prev_document_highlight" => LanguageServerFeature::DocumentHighlight,
+        _ => return None,
+    };
+    Some(feature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Servers stash an opaque `data` payload on code actions and expect it
+    /// back verbatim on `codeAction/resolve` (eslint is a prominent example).
+    /// The resolve request serializes [`CodeActionOrCommandItem::lsp_item`],
+    /// so `data` must survive the deserialize/serialize round-trip through a
+    /// mocked server response byte for byte.
+    #[test]
+    fn code_action_data_round_trips_to_resolve() {
+        let response = serde_json::json!([{
+            "title": "Fix all auto-fixable problems",
+            "kind": "source.fixAll.eslint",
+            "data": {
+                "id": 3,
+                "list": [1, 2, 3],
+                "nested": { "uri": "file:///tmp/a.js", "version": 7 },
+            },
+        }]);
+
+        let actions: lsp::CodeActionResponse =
+            serde_json::from_str(&response.to_string()).unwrap();
+        let item = CodeActionOrCommandItem::new(actions[0].clone(), LanguageServerId::default());
+
+        let lsp::CodeActionOrCommand::CodeAction(action) = &item.lsp_item else {
+            panic!("expected a code action, got a command");
+        };
+        // `resolve_code_action` serializes the `lsp::CodeAction` as the
+        // request params; compare the canonical serialization of `data`.
+        let sent = serde_json::to_value(action).unwrap();
+        assert_eq!(sent["data"].to_string(), response[0]["data"].to_string());
+        assert_eq!(item.title, "Fix all auto-fixable problems");
+    }
+
+    /// A line with every hint kind keeps them in priority order and loses
+    /// the lowest-priority ones first when the viewport budget runs out.
+    #[test]
+    fn annotation_budget_truncates_lowest_priority_first() {
+        let hints = || {
+            vec![
+                (
+                    AnnotationPriority::ParameterHints,
+                    InlineAnnotation::new(1, "x:"),
+                ),
+                (
+                    AnnotationPriority::TypeHints,
+                    InlineAnnotation::new(3, ": i32"),
+                ),
+                (
+                    AnnotationPriority::OtherHints,
+                    InlineAnnotation::new(5, ".into()"),
+                ),
+            ]
+        };
+
+        // everything fits: nothing is touched
+        let (kept, dropped) = budget_line_hints(14, hints());
+        assert_eq!(kept.len(), 3);
+        assert!(dropped.is_empty());
+        let kinds: Vec<_> = kept.iter().map(|&(kind, _)| kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                AnnotationPriority::ParameterHints,
+                AnnotationPriority::TypeHints,
+                AnnotationPriority::OtherHints
+            ]
+        );
+
+        // the lowest-priority hint is shortened to the leftover columns
+        let (kept, dropped) = budget_line_hints(10, hints());
+        assert_eq!(kept.len(), 3);
+        assert_eq!(&*kept[2].1.text, ".i\u{2026}");
+        assert!(dropped.is_empty());
+
+        // not even a meaningful prefix fits: the hint is dropped outright
+        let (kept, dropped) = budget_line_hints(7, hints());
+        assert_eq!(kept.len(), 2);
+        assert_eq!(dropped, vec![5]);
+
+        // the highest-priority hint survives a tiny budget alone
+        let (kept, dropped) = budget_line_hints(2, hints());
+        assert_eq!(kept.len(), 1);
+        assert_eq!(&*kept[0].1.text, "x:");
+        assert_eq!(dropped, vec![3, 5]);
+    }
+}
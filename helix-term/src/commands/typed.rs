@@ -1279,6 +1279,9 @@ fn reload(
             .language_servers
             .file_event_handler
             .file_changed(path.clone());
+        // the published diagnostics were computed against the old text: flag
+        // them as stale until the language server sends fresh ones
+        cx.editor.stale_diagnostic_paths.insert(path.clone());
     }
     Ok(())
 }
@@ -1329,6 +1332,8 @@ fn reload_all(
                 .language_servers
                 .file_event_handler
                 .file_changed(path.clone());
+            // see `reload`
+            cx.editor.stale_diagnostic_paths.insert(path.clone());
         }
 
         for view_id in view_ids {
@@ -1504,6 +1509,96 @@ fn lsp_stop(
     Ok(())
 }
 
+fn diagnostics(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(args.len() <= 1, "wrong argument count");
+    let path = match args.first() {
+        Some(arg) => {
+            let path = helix_stdx::path::expand_tilde(Path::new(arg.as_ref()));
+            helix_stdx::path::canonicalize(path)
+        }
+        None => doc!(cx.editor)
+            .path()
+            .cloned()
+            .context("current buffer has no path")?,
+    };
+
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let picker = super::lsp::file_diagnostics_picker(editor, path);
+                compositor.push(Box::new(overlaid(picker)));
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+fn lsp_request(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(args.len() == 1, "wrong argument count");
+    let request = super::lsp::CUSTOM_REQUESTS
+        .iter()
+        .find(|request| request.name == args[0])
+        .with_context(|| format!("no custom request named '{}'", args[0]))?;
+    super::lsp::custom_request(cx, request);
+
+    Ok(())
+}
+
+fn lsp_stats(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let mut contents = String::from("| Server | Background in flight | Background queued |\n");
+    contents.push_str("| --- | --- | --- |\n");
+    for client in cx.editor.language_servers.iter_clients() {
+        contents.push_str(&format!(
+            "| {} | {} | {} |\n",
+            client.name(),
+            client.background_requests_in_flight(),
+            client.background_requests_queued(),
+        ));
+    }
+
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let contents = ui::Markdown::new(contents, editor.syn_loader.clone());
+                let popup = Popup::new("lsp-stats", contents).auto_close(true);
+                compositor.replace_or_push("lsp-stats", popup);
+            },
+        ));
+        Ok(call)
+    };
+
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
 fn tree_sitter_scopes(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
@@ -1791,6 +1886,25 @@ fn update_goto_line_number_preview(
     Ok(())
 }
 
+/// Jump to a document symbol by name without going through the picker UI,
+/// making symbol navigation scriptable from macros and keybindings. The
+/// lookup itself lives in [super::lsp::goto_symbol_by_name].
+fn goto_symbol(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    ensure!(!args.is_empty(), "Symbol name required");
+    // symbol names can contain spaces (e.g. `fn foo` in some outlines)
+    let name = args.join(" ");
+    super::lsp::goto_symbol_by_name(cx, name);
+
+    Ok(())
+}
+
 pub(super) fn goto_line_number(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
@@ -2871,6 +2985,27 @@ pub const TYPABLE_COMMAND_LIST: &[TypableCommand] = &[
         fun: lsp_stop,
         signature: CommandSignature::none(),
     },
+    TypableCommand {
+        name: "lsp-request",
+        aliases: &[],
+        doc: "Send a server-specific LSP extension request for the current document, e.g. :lsp-request expand-macro.",
+        fun: lsp_request,
+        signature: CommandSignature::positional(&[completers::lsp_request]),
+    },
+    TypableCommand {
+        name: "lsp-stats",
+        aliases: &[],
+        doc: "Display per-server request statistics, e.g. the background request queue depth",
+        fun: lsp_stats,
+        signature: CommandSignature::none(),
+    },
+    TypableCommand {
+        name: "diagnostics",
+        aliases: &[],
+        doc: "Open the diagnostics picker for the given file (default: the current buffer) without switching to it.",
+        fun: diagnostics,
+        signature: CommandSignature::positional(&[completers::diagnostic_paths]),
+    },
     TypableCommand {
         name: "tree-sitter-scopes",
         aliases: &[],
@@ -2948,6 +3083,13 @@ pub const TYPABLE_COMMAND_LIST: &[TypableCommand] = &[
         fun: goto_line_number,
         signature: CommandSignature::none(),
     },
+    TypableCommand {
+        name: "goto-symbol",
+        aliases: &[],
+        doc: "Goto a document symbol by exact name or unique prefix, opening a picker of the candidates when the name is ambiguous.",
+        fun: goto_symbol,
+        signature: CommandSignature::all(completers::symbol_name),
+    },
     TypableCommand {
         name: "set-language",
         aliases: &["lang"],
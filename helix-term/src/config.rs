@@ -174,6 +174,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parsing_lsp_or_bindings() {
+        use crate::commands::MappableCommand;
+
+        let command: MappableCommand = "lsp_or(goto_definition, goto_file)".parse().unwrap();
+        assert_eq!(command.name(), "lsp_or(goto_definition, goto_file)");
+        match &command {
+            MappableCommand::LspOr {
+                primary, fallback, ..
+            } => {
+                assert_eq!(primary.name(), "goto_definition");
+                assert_eq!(fallback.name(), "goto_file");
+            }
+            other => panic!("expected an LspOr command, got {:?}", other),
+        }
+
+        // the fallback may be a typable command or a nested combinator
+        assert!("lsp_or(goto_reference, :sh grep -rn foo)"
+            .parse::<MappableCommand>()
+            .is_ok());
+        assert!("lsp_or(goto_definition, lsp_or(goto_reference, goto_file))"
+            .parse::<MappableCommand>()
+            .is_ok());
+
+        // the primary must be feature-mapped, complete and known
+        assert!("lsp_or(move_char_left, goto_file)"
+            .parse::<MappableCommand>()
+            .is_err());
+        assert!("lsp_or(goto_definition)".parse::<MappableCommand>().is_err());
+        assert!("lsp_or(goto_definition, goto_file"
+            .parse::<MappableCommand>()
+            .is_err());
+    }
+
     #[test]
     fn keys_resolve_to_correct_defaults() {
         // From serde default
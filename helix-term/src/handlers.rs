@@ -5,14 +5,20 @@ use helix_event::AsyncHook;
 
 use crate::config::Config;
 use crate::events;
+use crate::handlers::code_action::CodeActionsHandler;
 use crate::handlers::completion::CompletionHandler;
+use crate::handlers::diagnostics::PublishDiagnosticsHandler;
+use crate::handlers::hover::MouseHoverHandler;
 use crate::handlers::signature_help::SignatureHelpHandler;
 
 pub use completion::trigger_auto_completion;
 pub use helix_view::handlers::Handlers;
+pub use signature_help::show_signature_help;
 
+mod code_action;
 pub mod completion;
-mod diagnostics;
+pub(crate) mod diagnostics;
+mod hover;
 mod signature_help;
 
 pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
@@ -20,12 +26,20 @@ pub fn setup(config: Arc<ArcSwap<Config>>) -> Handlers {
 
     let completions = CompletionHandler::new(config).spawn();
     let signature_hints = SignatureHelpHandler::new().spawn();
+    let mouse_hover = MouseHoverHandler::default().spawn();
+    let publish_diagnostics = PublishDiagnosticsHandler::default().spawn();
+    let code_actions = CodeActionsHandler::default().spawn();
     let handlers = Handlers {
         completions,
         signature_hints,
+        mouse_hover,
+        publish_diagnostics,
+        code_actions,
     };
     completion::register_hooks(&handlers);
     signature_help::register_hooks(&handlers);
     diagnostics::register_hooks(&handlers);
+    hover::register_hooks(&handlers);
+    code_action::register_hooks(&handlers);
     handlers
 }
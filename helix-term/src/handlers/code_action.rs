@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use helix_core::Range;
+use helix_event::{register_hook, send_blocking};
+use helix_view::events::DocumentDidChange;
+use helix_view::handlers::lsp::CodeActionsEvent;
+use helix_view::handlers::Handlers;
+use helix_view::{DocumentId, ViewId};
+use tokio::time::Instant;
+
+use crate::commands;
+use crate::job;
+
+/// Debounce before a code action query actually reaches the servers. Long
+/// enough that holding `j` never fires per-line queries, short enough that an
+/// indicator feels immediate once the cursor rests.
+const TIMEOUT: u64 = 250;
+
+/// Debounces and coalesces the code action queries sent on selection change:
+/// only the query for the document+range the cursor settles on fires,
+/// superseded ones are silently dropped.
+#[derive(Debug, Default)]
+pub(super) struct CodeActionsHandler {
+    target: Option<(DocumentId, ViewId, Range)>,
+}
+
+impl helix_event::AsyncHook for CodeActionsHandler {
+    type Event = CodeActionsEvent;
+
+    fn handle_event(&mut self, event: Self::Event, timeout: Option<Instant>) -> Option<Instant> {
+        match event {
+            CodeActionsEvent::Trigger { doc, view, range } => {
+                let target = (doc, view, range);
+                // re-sending the same target (e.g. a selection restored by an
+                // undo of a movement) shouldn't restart the debounce
+                if self.target.as_ref() == Some(&target) {
+                    return timeout;
+                }
+                self.target = Some(target);
+                Some(Instant::now() + Duration::from_millis(TIMEOUT))
+            }
+            CodeActionsEvent::Cancel => {
+                self.target = None;
+                None
+            }
+        }
+    }
+
+    fn finish_debounce(&mut self) {
+        if let Some((doc, view, range)) = self.target.take() {
+            job::dispatch_blocking(move |editor, _| {
+                commands::lsp::query_code_actions(editor, doc, view, range)
+            })
+        }
+    }
+}
+
+pub(super) fn register_hooks(handlers: &Handlers) {
+    let tx = handlers.code_actions.clone();
+    register_hook!(move |_event: &mut DocumentDidChange<'_>| {
+        // an edit moves the positions the pending query was built against;
+        // the next selection change re-triggers it
+        send_blocking(&tx, CodeActionsEvent::Cancel);
+        Ok(())
+    });
+}
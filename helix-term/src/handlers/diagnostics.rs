@@ -1,10 +1,178 @@
+use std::collections::btree_map::Entry;
+use std::time::Duration;
+
 use helix_event::{register_hook, send_blocking};
+use helix_lsp::{lsp, LanguageServerId};
 use helix_view::document::Mode;
 use helix_view::events::DiagnosticsDidChange;
 use helix_view::handlers::diagnostics::DiagnosticEvent;
+use helix_view::handlers::lsp::PublishDiagnosticsEvent;
 use helix_view::handlers::Handlers;
+use helix_view::{DocumentId, Editor};
+use tokio::time::Instant;
 
 use crate::events::OnModeSwitch;
+use crate::job;
+
+/// How long incoming `publishDiagnostics` notifications are collected before
+/// they are applied in one pass. Long enough to catch a server's post-indexing
+/// burst, short enough to go unnoticed for a single publish.
+const PUBLISH_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Coalesces `publishDiagnostics` bursts: notifications for unfocused
+/// documents are collected for [`PUBLISH_DEBOUNCE`] and applied together, so
+/// derived state (statusline counts, open pickers, inline rendering) is only
+/// recomputed once per batch. The focused document bypasses this handler
+/// entirely, see the notification dispatch in `Application`.
+#[derive(Default)]
+pub(super) struct PublishDiagnosticsHandler {
+    pending: Vec<(LanguageServerId, lsp::PublishDiagnosticsParams)>,
+}
+
+impl helix_event::AsyncHook for PublishDiagnosticsHandler {
+    type Event = PublishDiagnosticsEvent;
+
+    fn handle_event(&mut self, event: Self::Event, timeout: Option<Instant>) -> Option<Instant> {
+        self.pending.push((event.server_id, event.params));
+        // keep the first deadline: pushing it back on every notification
+        // would let a steady publish stream starve the flush indefinitely
+        timeout.or_else(|| Some(Instant::now() + PUBLISH_DEBOUNCE))
+    }
+
+    fn finish_debounce(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        job::dispatch_blocking(move |editor, _| {
+            let mut changed_docs = Vec::new();
+            for (server_id, params) in pending {
+                if let Some(doc) = apply_published_diagnostics(editor, server_id, params) {
+                    if !changed_docs.contains(&doc) {
+                        changed_docs.push(doc);
+                    }
+                }
+            }
+            for doc in changed_docs {
+                helix_event::dispatch(DiagnosticsDidChange { editor, doc });
+            }
+            editor.recompute_workspace_diagnostic_counts();
+        });
+    }
+}
+
+/// Applies a single `publishDiagnostics` notification to
+/// [`Editor::diagnostics`] and, when the file is open, to the document
+/// itself. Returns the open document's id so the caller can dispatch
+/// [`DiagnosticsDidChange`] (and recompute the workspace counts) once per
+/// batch rather than per notification.
+pub(crate) fn apply_published_diagnostics(
+    editor: &mut Editor,
+    server_id: LanguageServerId,
+    mut params: lsp::PublishDiagnosticsParams,
+) -> Option<DocumentId> {
+    helix_lsp::util::normalize_uri(&mut params.uri);
+    let path = match params.uri.to_file_path() {
+        Ok(path) => helix_stdx::path::normalize(path),
+        Err(_) => {
+            log::error!("Unsupported file URI: {}", params.uri);
+            return None;
+        }
+    };
+    // have to inline the function because of borrow checking...
+    let doc = editor.documents.values_mut()
+        .find(|doc| doc.path().map(|p| p == &path).unwrap_or(false))
+        .filter(|doc| {
+            if let Some(version) = params.version {
+                if version != doc.version() {
+                    log::info!("Version ({version}) is out of date for {path:?} (expected ({}), dropping PublishDiagnostic notification", doc.version());
+                    return false;
+                }
+            }
+            true
+        });
+
+    let mut unchanged_diag_sources = Vec::new();
+    if let Some(doc) = &doc {
+        let lang_conf = doc.language.clone();
+
+        if let Some(lang_conf) = &lang_conf {
+            if let Some(old_diagnostics) = editor.diagnostics.get(&path) {
+                if !lang_conf.persistent_diagnostic_sources.is_empty() {
+                    // Sort diagnostics first by severity and then by line numbers.
+                    // Note: The `lsp::DiagnosticSeverity` enum is already defined in decreasing order
+                    params
+                        .diagnostics
+                        .sort_by_key(|d| (d.severity, d.range.start));
+                }
+                for source in &lang_conf.persistent_diagnostic_sources {
+                    let new_diagnostics = params
+                        .diagnostics
+                        .iter()
+                        .filter(|d| d.source.as_ref() == Some(source));
+                    let old_diagnostics = old_diagnostics
+                        .iter()
+                        .filter(|(d, d_server)| {
+                            *d_server == server_id && d.source.as_ref() == Some(source)
+                        })
+                        .map(|(d, _)| d);
+                    if new_diagnostics.eq(old_diagnostics) {
+                        unchanged_diag_sources.push(source.clone())
+                    }
+                }
+            }
+        }
+    }
+
+    let diagnostics = params.diagnostics.into_iter().map(|d| (d, server_id));
+
+    // fresh diagnostics for this file arrived, so stored positions are
+    // trustworthy again, and any entries restored from the session cache
+    // are superseded
+    editor.stale_diagnostic_paths.remove(&path);
+    editor.restored_diagnostics.remove(&path);
+
+    // Insert the original lsp::Diagnostics here because we may have no open document
+    // for diagnosic message and so we can't calculate the exact position.
+    // When using them later in the diagnostics picker, we calculate them on-demand.
+    let diagnostics = match editor.diagnostics.entry(path) {
+        Entry::Occupied(o) => {
+            let current_diagnostics = o.into_mut();
+            // there may entries of other language servers, which is why we can't overwrite the whole entry
+            current_diagnostics.retain(|(_, lsp_id)| *lsp_id != server_id);
+            current_diagnostics.extend(diagnostics);
+            current_diagnostics
+            // Sort diagnostics first by severity and then by line numbers.
+        }
+        Entry::Vacant(v) => v.insert(diagnostics.collect()),
+    };
+
+    // Sort diagnostics first by severity and then by line numbers,
+    // breaking ties between servers by their priority in the
+    // language configuration instead of arrival order.
+    diagnostics.sort_by_cached_key(|(d, server_id)| {
+        let priority = doc
+            .as_ref()
+            .map_or(usize::MAX, |doc| doc.language_server_priority(*server_id));
+        Editor::diagnostic_sort_key(d, priority, *server_id)
+    });
+
+    let doc = doc?;
+    let diagnostic_of_language_server_and_not_in_unchanged_sources =
+        |diagnostic: &lsp::Diagnostic, ls_id| {
+            ls_id == server_id
+                && diagnostic
+                    .source
+                    .as_ref()
+                    .map_or(true, |source| !unchanged_diag_sources.contains(source))
+        };
+    let diagnostics = Editor::doc_diagnostics_with_filter(
+        &editor.language_servers,
+        &editor.diagnostics,
+        doc,
+        diagnostic_of_language_server_and_not_in_unchanged_sources,
+    );
+    doc.replace_diagnostics(diagnostics, &unchanged_diag_sources, Some(server_id));
+
+    Some(doc.id())
+}
 
 pub(super) fn register_hooks(_handlers: &Handlers) {
     register_hook!(move |event: &mut DiagnosticsDidChange<'_>| {
@@ -13,6 +181,16 @@ pub(super) fn register_hooks(_handlers: &Handlers) {
                 send_blocking(&view.diagnostics_handler.events, DiagnosticEvent::Refresh)
             }
         }
+        // An open code action menu is tied to the diagnostics it was
+        // requested with; applying an entry for a diagnostic that no longer
+        // exists fails with ContentModified, so drop the stale menu.
+        if view!(event.editor).doc == event.doc {
+            job::dispatch_blocking(|editor, compositor| {
+                if compositor.remove("code-action").is_some() {
+                    editor.set_status("Code actions outdated, diagnostics changed");
+                }
+            });
+        }
         Ok(())
     });
     register_hook!(move |event: &mut OnModeSwitch<'_, '_>| {
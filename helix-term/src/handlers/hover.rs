@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use helix_core::Position;
+use helix_event::register_hook;
+use helix_view::events::DocumentDidChange;
+use helix_view::handlers::lsp::MouseHoverEvent;
+use helix_view::handlers::Handlers;
+use helix_view::DocumentId;
+use tokio::time::Instant;
+
+use crate::commands;
+use crate::job;
+use crate::ui::Popup;
+
+/// Debounces mouse movement and requests hover at the position the pointer
+/// comes to rest over, like GUI editors do.
+#[derive(Debug, Default)]
+pub(super) struct MouseHoverHandler {
+    target: Option<(DocumentId, usize, Position)>,
+}
+
+impl helix_event::AsyncHook for MouseHoverHandler {
+    type Event = MouseHoverEvent;
+
+    fn handle_event(&mut self, event: Self::Event, timeout: Option<Instant>) -> Option<Instant> {
+        match event {
+            MouseHoverEvent::Moved {
+                doc,
+                pos,
+                anchor,
+                delay,
+            } => {
+                let target = (doc, pos, anchor);
+                // jitter within the same cell shouldn't restart the debounce
+                if self.target.as_ref() == Some(&target) {
+                    return timeout;
+                }
+                self.target = Some(target);
+                Some(Instant::now() + Duration::from_millis(delay))
+            }
+            MouseHoverEvent::Cancel => {
+                self.target = None;
+                None
+            }
+        }
+    }
+
+    fn finish_debounce(&mut self) {
+        if let Some((doc, pos, anchor)) = self.target.take() {
+            job::dispatch_blocking(move |editor, _| {
+                commands::lsp::hover_at_position(editor, doc, pos, Some(anchor))
+            })
+        }
+    }
+}
+
+pub(super) fn register_hooks(_handlers: &Handlers) {
+    register_hook!(move |event: &mut DocumentDidChange<'_>| {
+        if event.doc.config.load().lsp.refresh_hover_on_edit {
+            job::dispatch_blocking(|editor, compositor| {
+                if compositor
+                    .find_id::<Popup<crate::ui::lsp::Hover>>(crate::ui::lsp::Hover::ID)
+                    .is_some()
+                {
+                    commands::lsp::refresh_hover(editor);
+                }
+            });
+        }
+        Ok(())
+    });
+}
@@ -6,7 +6,7 @@ use arc_swap::{
     access::{DynAccess, DynGuard},
     ArcSwap,
 };
-use helix_view::{document::Mode, info::Info, input::KeyEvent};
+use helix_view::{document::Mode, info::Info, input::KeyEvent, Document};
 use serde::Deserialize;
 use std::{
     borrow::Cow,
@@ -72,18 +72,24 @@ impl KeyTrieNode {
         }
     }
 
-    pub fn infobox(&self) -> Info {
-        let mut body: Vec<(BTreeSet<KeyEvent>, &str)> = Vec::with_capacity(self.len());
+    pub fn infobox(&self, doc: &Document) -> Info {
+        let mut body: Vec<(BTreeSet<KeyEvent>, Cow<str>)> = Vec::with_capacity(self.len());
         for (&key, trie) in self.iter() {
-            let desc = match trie {
+            let desc: Cow<str> = match trie {
                 KeyTrie::MappableCommand(cmd) => {
                     if cmd.name() == "no_op" {
                         continue;
                     }
-                    cmd.doc()
+                    if cmd.is_supported(doc) {
+                        Cow::Borrowed(cmd.doc())
+                    } else {
+                        // tag commands whose required language server feature
+                        // no attached server provides
+                        Cow::Owned(format!("{} (no language server support)", cmd.doc()))
+                    }
                 }
-                KeyTrie::Node(n) => &n.name,
-                KeyTrie::Sequence(_) => "[Multiple commands]",
+                KeyTrie::Node(n) => Cow::Borrowed(n.name.as_str()),
+                KeyTrie::Sequence(_) => Cow::Borrowed("[Multiple commands]"),
             };
             match body.iter().position(|(_, d)| d == &desc) {
                 Some(pos) => {
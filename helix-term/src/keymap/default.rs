@@ -119,6 +119,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "e" => goto_prev_entry,
             "T" => goto_prev_test,
             "p" => goto_prev_paragraph,
+            "o" => prev_document_highlight,
             "space" => add_newline_above,
         },
         "]" => { "Right bracket"
@@ -133,6 +134,7 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
             "e" => goto_next_entry,
             "T" => goto_next_test,
             "p" => goto_next_paragraph,
+            "o" => next_document_highlight,
             "space" => add_newline_below,
         },
 
@@ -218,6 +220,8 @@ pub fn default() -> HashMap<Mode, KeyTrie> {
         "C-o" => jump_backward,
         "C-s" => save_selection,
 
+        "ret" => references_view_jump, // no-op outside the references view
+
         "space" => { "Space"
             "f" => file_picker,
             "F" => file_picker_in_current_directory,
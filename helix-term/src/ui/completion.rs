@@ -5,7 +5,7 @@ use crate::{
 use helix_view::{
     document::SavePoint,
     editor::CompleteAction,
-    handlers::lsp::SignatureHelpInvoked,
+    handlers::lsp::{SignatureHelpEvent, SignatureHelpInvoked},
     theme::{Modifier, Style},
     ViewId,
 };
@@ -233,6 +233,8 @@ impl Completion {
                 };
             }
 
+            let mut accepted_callable = false;
+
             match event {
                 PromptEvent::Abort => {}
                 PromptEvent::Update if preview_completion_insert => {
@@ -319,17 +321,41 @@ impl Completion {
                             doc.apply(&transaction, view.id);
                         }
                     }
+                    // accepting a function-like completion usually leaves the cursor
+                    // inside the call's parentheses (through a snippet or the inserted
+                    // `()`), in which case signature help is popped up below
+                    if matches!(
+                        item.item.kind,
+                        Some(lsp::CompletionItemKind::FUNCTION)
+                            | Some(lsp::CompletionItemKind::METHOD)
+                            | Some(lsp::CompletionItemKind::CONSTRUCTOR)
+                    ) {
+                        let text = doc.text().slice(..);
+                        let cursor = doc.selection(view.id).primary().cursor(text);
+                        accepted_callable =
+                            cursor > 0 && matches!(text.char(cursor - 1), '(' | ',');
+                    }
+
                     // we could have just inserted a trigger char (like a `crate::` completion for rust
                     // so we want to retrigger immediately when accepting a completion.
                     trigger_auto_completion(&editor.handlers.completions, editor, true);
                 }
             };
 
-            // In case the popup was deleted because of an intersection w/ the auto-complete menu.
             if event != PromptEvent::Update {
-                editor
-                    .handlers
-                    .trigger_signature_help(SignatureHelpInvoked::Automatic, editor);
+                if accepted_callable {
+                    editor
+                        .handlers
+                        .trigger_signature_help(SignatureHelpInvoked::Automatic, editor);
+                } else {
+                    // In case the popup was deleted because of an intersection w/ the
+                    // auto-complete menu: only re-open a signature popup that was
+                    // already showing, a non-callable completion shouldn't pop one up.
+                    helix_event::send_blocking(
+                        &editor.handlers.signature_hints,
+                        SignatureHelpEvent::ReTrigger,
+                    );
+                }
             }
         });
 
@@ -21,11 +21,13 @@ use helix_core::{
     unicode::width::UnicodeWidthStr,
     visual_offset_from_block, Change, Position, Range, Selection, Transaction,
 };
+use helix_event::send_blocking;
 use helix_view::{
     annotations::diagnostics::DiagnosticFilter,
     document::{Mode, SavePoint, SCRATCH_BUFFER_NAME},
     editor::{CompleteAction, CursorShapeConfig},
     graphics::{Color, CursorKind, Modifier, Rect, Style},
+    handlers::lsp::MouseHoverEvent,
     input::{KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     keyboard::{KeyCode, KeyModifiers},
     Document, Editor, Theme, View,
@@ -198,6 +200,7 @@ impl EditorView {
             primary_cursor,
             inline_diagnostic_config,
             config.end_of_line_diagnostics,
+            config.lsp.diagnostic_related_suffix,
         ));
         render_document(
             surface,
@@ -866,7 +869,11 @@ impl EditorView {
         let mut last_mode = mode;
         self.pseudo_pending.extend(self.keymaps.pending());
         let key_result = self.keymaps.get(mode, event);
-        cxt.editor.autoinfo = self.keymaps.sticky().map(|node| node.infobox());
+        let sticky_infobox = self
+            .keymaps
+            .sticky()
+            .map(|node| node.infobox(doc!(cxt.editor)));
+        cxt.editor.autoinfo = sticky_infobox;
 
         let mut execute_command = |command: &commands::MappableCommand| {
             command.execute(cxt);
@@ -897,7 +904,9 @@ impl EditorView {
             KeymapResult::Matched(command) => {
                 execute_command(command);
             }
-            KeymapResult::Pending(node) => cxt.editor.autoinfo = Some(node.infobox()),
+            KeymapResult::Pending(node) => {
+                cxt.editor.autoinfo = Some(node.infobox(doc!(cxt.editor)))
+            }
             KeymapResult::MatchedSequence(commands) => {
                 for command in commands {
                     execute_command(command);
@@ -1301,6 +1310,30 @@ impl EditorView {
                 EventResult::Ignored(None)
             }
 
+            MouseEventKind::Moved => {
+                if !config.lsp.mouse_hover {
+                    return EventResult::Ignored(None);
+                }
+
+                let editor = &cxt.editor;
+                if let Some((pos, view_id)) = pos_and_view(editor, row, column, true) {
+                    let doc = view!(editor, view_id).doc;
+                    send_blocking(
+                        &editor.handlers.mouse_hover,
+                        MouseHoverEvent::Moved {
+                            doc,
+                            pos,
+                            anchor: Position::new(row as usize, column as usize),
+                            delay: config.lsp.mouse_hover_delay,
+                        },
+                    );
+                } else {
+                    send_blocking(&editor.handlers.mouse_hover, MouseHoverEvent::Cancel);
+                }
+
+                EventResult::Ignored(None)
+            }
+
             _ => EventResult::Ignored(None),
         }
     }
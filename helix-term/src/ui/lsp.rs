@@ -4,9 +4,10 @@ use arc_swap::ArcSwap;
 use helix_core::syntax;
 use helix_view::graphics::{Margin, Rect, Style};
 use helix_view::input::Event;
+use helix_view::Theme;
 use tui::buffer::Buffer;
 use tui::layout::Alignment;
-use tui::text::Text;
+use tui::text::{Span, Spans, Text};
 use tui::widgets::{BorderType, Paragraph, Widget, Wrap};
 
 use crate::compositor::{Component, Compositor, Context, EventResult};
@@ -197,3 +198,390 @@ impl Component for SignatureHelp {
         Some((width + PADDING + sig_index_width as u16, height + PADDING))
     }
 }
+
+/// One diagnostic overlapping the cursor, rendered in full by the
+/// `show_diagnostics_under_cursor` popup.
+pub struct DiagnosticEntry {
+    /// Markdown with the severity, source, code and full message.
+    pub contents: String,
+    /// Locations of the diagnostic's related information, in the order they
+    /// are listed in `contents`.
+    pub related: Vec<helix_lsp::lsp::Location>,
+    pub offset_encoding: helix_lsp::OffsetEncoding,
+}
+
+/// Popup showing the diagnostics under the cursor without the end-of-line
+/// truncation: `Alt-n`/`Alt-p` cycle through overlapping diagnostics and the
+/// `1`-`9` keys jump to the matching related-information entry.
+pub struct DiagnosticInfo {
+    config_loader: Arc<ArcSwap<syntax::Loader>>,
+    active: usize,
+    entries: Vec<DiagnosticEntry>,
+}
+
+impl DiagnosticInfo {
+    pub const ID: &'static str = "diagnostic-info";
+
+    pub fn new(entries: Vec<DiagnosticEntry>, config_loader: Arc<ArcSwap<syntax::Loader>>) -> Self {
+        Self {
+            config_loader,
+            active: 0,
+            entries,
+        }
+    }
+
+    fn entry_index(&self) -> String {
+        format!("({}/{})", self.active + 1, self.entries.len())
+    }
+}
+
+impl Component for DiagnosticInfo {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        use helix_view::input::{KeyCode, KeyModifiers};
+
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+
+        match event {
+            alt!('p') if self.entries.len() > 1 => {
+                self.active = self
+                    .active
+                    .checked_sub(1)
+                    .unwrap_or(self.entries.len() - 1);
+                EventResult::Consumed(None)
+            }
+            alt!('n') if self.entries.len() > 1 => {
+                self.active = (self.active + 1) % self.entries.len();
+                EventResult::Consumed(None)
+            }
+            helix_view::input::KeyEvent {
+                code: KeyCode::Char(digit @ '1'..='9'),
+                modifiers: KeyModifiers::NONE,
+            } => {
+                let entry = &self.entries[self.active];
+                let index = *digit as usize - '1' as usize;
+                let Some(location) = entry.related.get(index).cloned() else {
+                    return EventResult::Ignored(None);
+                };
+                let offset_encoding = entry.offset_encoding;
+                // jump through the same path the diagnostics picker takes
+                EventResult::Consumed(Some(Box::new(move |compositor, cx| {
+                    compositor.remove(Self::ID);
+                    crate::commands::lsp::jump_to_location(
+                        cx.editor,
+                        &location,
+                        offset_encoding,
+                        helix_view::editor::Action::Replace,
+                        "show_diagnostics_under_cursor",
+                    );
+                })))
+            }
+            _ => EventResult::Ignored(None),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Buffer, cx: &mut Context) {
+        let margin = Margin::horizontal(1);
+
+        if self.entries.len() > 1 {
+            let text = Text::from(self.entry_index());
+            let paragraph = Paragraph::new(&text).alignment(Alignment::Right);
+            paragraph.render(area.with_height(1).clip_right(1), surface);
+        }
+
+        let contents = Markdown::new(
+            self.entries[self.active].contents.clone(),
+            Arc::clone(&self.config_loader),
+        );
+        let contents = contents.parse(Some(&cx.editor.theme));
+        let paragraph = Paragraph::new(&contents)
+            .wrap(Wrap { trim: false })
+            .scroll((cx.scroll.unwrap_or_default() as u16, 0));
+        paragraph.render(area.inner(margin), surface);
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        const PADDING: u16 = 2;
+
+        let max_text_width = viewport.0.saturating_sub(PADDING).clamp(10, 120);
+        let contents = Markdown::new(
+            self.entries[self.active].contents.clone(),
+            Arc::clone(&self.config_loader),
+        );
+        let contents = contents.parse(None);
+        let (width, height) = crate::ui::text::required_size(&contents, max_text_width);
+
+        let index_width = if self.entries.len() > 1 {
+            self.entry_index().len() as u16 + 1
+        } else {
+            0
+        };
+
+        Some((width + PADDING + index_width, height + PADDING))
+    }
+}
+
+/// Locations fetched by the `symbol_info` command, kept together with the
+/// server they came from so jumps and the references picker use the right
+/// offset encoding.
+#[derive(Clone)]
+pub struct SymbolInfoLocations {
+    pub locations: Vec<helix_lsp::lsp::Location>,
+    pub language_server_id: helix_lsp::LanguageServerId,
+    pub server_name: String,
+    pub offset_encoding: helix_lsp::OffsetEncoding,
+}
+
+/// Popup assembled by the `symbol_info` command: the hover contents, the
+/// definition locations and the reference count of the symbol under the
+/// cursor in one view. `d` (or `1`-`9` when there are several definitions)
+/// jumps to a definition and `r` opens the references picker with the
+/// already-fetched locations.
+pub struct SymbolInfo {
+    config_loader: Arc<ArcSwap<syntax::Loader>>,
+    contents: String,
+    definitions: Option<SymbolInfoLocations>,
+    references: Option<SymbolInfoLocations>,
+}
+
+impl SymbolInfo {
+    pub const ID: &'static str = "symbol-info";
+
+    pub fn new(
+        contents: String,
+        definitions: Option<SymbolInfoLocations>,
+        references: Option<SymbolInfoLocations>,
+        config_loader: Arc<ArcSwap<syntax::Loader>>,
+    ) -> Self {
+        Self {
+            config_loader,
+            contents,
+            definitions,
+            references,
+        }
+    }
+
+    fn jump_to_definition(&self, index: usize) -> EventResult {
+        let Some(definitions) = &self.definitions else {
+            return EventResult::Ignored(None);
+        };
+        let Some(location) = definitions.locations.get(index).cloned() else {
+            return EventResult::Ignored(None);
+        };
+        let offset_encoding = definitions.offset_encoding;
+        EventResult::Consumed(Some(Box::new(move |compositor, cx| {
+            compositor.remove(Self::ID);
+            crate::commands::lsp::jump_to_location(
+                cx.editor,
+                &location,
+                offset_encoding,
+                helix_view::editor::Action::Replace,
+                "symbol_info",
+            );
+        })))
+    }
+}
+
+impl Component for SymbolInfo {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        use helix_view::input::{KeyCode, KeyModifiers};
+
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+
+        match event {
+            helix_view::input::KeyEvent {
+                code: KeyCode::Char('d'),
+                modifiers: KeyModifiers::NONE,
+            } => self.jump_to_definition(0),
+            helix_view::input::KeyEvent {
+                code: KeyCode::Char(digit @ '1'..='9'),
+                modifiers: KeyModifiers::NONE,
+            } => self.jump_to_definition(*digit as usize - '1' as usize),
+            helix_view::input::KeyEvent {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::NONE,
+            } => {
+                let Some(references) = self.references.clone() else {
+                    return EventResult::Ignored(None);
+                };
+                EventResult::Consumed(Some(Box::new(move |compositor, cx| {
+                    compositor.remove(Self::ID);
+                    crate::commands::lsp::show_fetched_references(
+                        cx.editor, compositor, references,
+                    );
+                })))
+            }
+            _ => EventResult::Ignored(None),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Buffer, cx: &mut Context) {
+        let margin = Margin::horizontal(1);
+        let contents = Markdown::new(self.contents.clone(), Arc::clone(&self.config_loader));
+        let contents = contents.parse(Some(&cx.editor.theme));
+        let paragraph = Paragraph::new(&contents)
+            .wrap(Wrap { trim: false })
+            .scroll((cx.scroll.unwrap_or_default() as u16, 0));
+        paragraph.render(area.inner(margin), surface);
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        const PADDING: u16 = 2;
+
+        let max_text_width = viewport.0.saturating_sub(PADDING).clamp(10, 120);
+        let contents = Markdown::new(self.contents.clone(), Arc::clone(&self.config_loader));
+        let contents = contents.parse(None);
+        let (width, height) = crate::ui::text::required_size(&contents, max_text_width);
+
+        Some((width + PADDING, height + PADDING))
+    }
+}
+
+/// One part of a hover response: the contents a single server (or, for the
+/// `HoverContents::Array` variant, a single part of one server's response)
+/// returned, labeled with where it came from.
+pub struct HoverSection {
+    pub header: String,
+    /// Markdown contents of the section.
+    pub contents: String,
+}
+
+/// The hover popup. A response made up of several sections (multiple servers,
+/// multi-part hovers like rust-analyzer's type + docs + implementations) is
+/// rendered with a collapsible header per section instead of one flat
+/// markdown blob: `A-n`/`A-p` select a section and `A-o` folds or unfolds it,
+/// so the headers can be skimmed first. A single-section response renders
+/// exactly like the plain markdown popup did.
+pub struct Hover {
+    config_loader: Arc<ArcSwap<syntax::Loader>>,
+    sections: Vec<HoverSection>,
+    collapsed: Vec<bool>,
+    active: usize,
+}
+
+impl Hover {
+    pub const ID: &'static str = "hover";
+
+    pub fn new(sections: Vec<HoverSection>, config_loader: Arc<ArcSwap<syntax::Loader>>) -> Self {
+        let collapsed = vec![false; sections.len()];
+        Self {
+            config_loader,
+            sections,
+            collapsed,
+            active: 0,
+        }
+    }
+
+    /// Parses one section's markdown into an owned `Text`, so sections can be
+    /// concatenated without tying the result to a temporary [`Markdown`].
+    fn section_text(&self, index: usize, theme: Option<&Theme>) -> Text<'static> {
+        let markdown = Markdown::new(
+            self.sections[index].contents.clone(),
+            Arc::clone(&self.config_loader),
+        );
+        let lines = markdown
+            .parse(theme)
+            .lines
+            .into_iter()
+            .map(|spans| {
+                Spans(
+                    spans
+                        .0
+                        .into_iter()
+                        .map(|span| Span::styled(span.content.into_owned(), span.style))
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        Text::from(lines)
+    }
+
+    fn text(&self, theme: Option<&Theme>) -> Text<'static> {
+        if self.sections.len() == 1 {
+            return self.section_text(0, theme);
+        }
+
+        let header_style = theme
+            .map(|theme| theme.get("markup.heading.1"))
+            .unwrap_or_default();
+        let active_style = theme
+            .map(|theme| theme.get("ui.selection"))
+            .unwrap_or_default();
+
+        let mut lines = Vec::new();
+        for (i, section) in self.sections.iter().enumerate() {
+            let marker = if self.collapsed[i] { "▶" } else { "▼" };
+            let style = if i == self.active {
+                header_style.patch(active_style)
+            } else {
+                header_style
+            };
+            lines.push(Spans::from(Span::styled(
+                format!("{marker} {}", section.header),
+                style,
+            )));
+            if !self.collapsed[i] && !section.contents.is_empty() {
+                lines.extend(self.section_text(i, theme).lines);
+            }
+            if i + 1 != self.sections.len() {
+                lines.push(Spans::default());
+            }
+        }
+        Text::from(lines)
+    }
+}
+
+impl Component for Hover {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        let Event::Key(event) = event else {
+            return EventResult::Ignored(None);
+        };
+
+        if self.sections.len() <= 1 {
+            return EventResult::Ignored(None);
+        }
+
+        match event {
+            alt!('p') => {
+                self.active = self
+                    .active
+                    .checked_sub(1)
+                    .unwrap_or(self.sections.len() - 1);
+                EventResult::Consumed(None)
+            }
+            alt!('n') => {
+                self.active = (self.active + 1) % self.sections.len();
+                EventResult::Consumed(None)
+            }
+            alt!('o') => {
+                self.collapsed[self.active] = !self.collapsed[self.active];
+                EventResult::Consumed(None)
+            }
+            _ => EventResult::Ignored(None),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Buffer, cx: &mut Context) {
+        let text = self.text(Some(&cx.editor.theme));
+
+        let par = Paragraph::new(&text)
+            .wrap(Wrap { trim: false })
+            .scroll((cx.scroll.unwrap_or_default() as u16, 0));
+
+        let margin = Margin::all(1);
+        par.render(area.inner(margin), surface);
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        let padding = 2;
+        let contents = self.text(None);
+
+        let max_text_width = (viewport.0.saturating_sub(padding)).min(120);
+        let (width, height) = crate::ui::text::required_size(&contents, max_text_width);
+
+        Some((width + padding, height + padding))
+    }
+}
@@ -11,7 +11,7 @@ use tui::{buffer::Buffer as Surface, widgets::Table};
 
 pub use tui::widgets::{Cell, Row};
 
-use helix_view::{editor::SmartTabConfig, graphics::Rect, Editor};
+use helix_view::{editor::SmartTabConfig, graphics::Rect, input::KeyEvent, Editor};
 use tui::layout::Constraint;
 
 pub trait Item: Sync + Send + 'static {
@@ -44,6 +44,7 @@ impl Item for PathBuf {
 }
 
 pub type MenuCallback<T> = Box<dyn Fn(&mut Editor, Option<&T>, MenuEvent)>;
+pub type MenuKeyCallback<T> = Box<dyn Fn(&mut Editor, Option<&T>)>;
 
 pub struct Menu<T: Item> {
     options: Vec<T>,
@@ -57,6 +58,9 @@ pub struct Menu<T: Item> {
     widths: Vec<Constraint>,
 
     callback_fn: MenuCallback<T>,
+    /// An additional key bound by the caller, called with the highlighted
+    /// entry; the menu stays open. E.g. pinning in the code action menu.
+    extra_key: Option<(KeyEvent, MenuKeyCallback<T>)>,
 
     scroll: usize,
     size: (u16, u16),
@@ -82,6 +86,7 @@ impl<T: Item> Menu<T> {
             cursor: None,
             widths: Vec::new(),
             callback_fn: Box::new(callback_fn),
+            extra_key: None,
             scroll: 0,
             size: (0, 0),
             viewport: (0, 0),
@@ -89,6 +94,17 @@ impl<T: Item> Menu<T> {
         }
     }
 
+    /// Binds an additional `key` that calls `callback` with the highlighted
+    /// entry without closing the menu.
+    pub fn with_key(
+        mut self,
+        key: KeyEvent,
+        callback: impl Fn(&mut Editor, Option<&T>) + 'static,
+    ) -> Self {
+        self.extra_key = Some((key, Box::new(callback)));
+        self
+    }
+
     pub fn score(&mut self, pattern: &str, incremental: bool) {
         let mut matcher = MATCHER.lock();
         matcher.config = Config::DEFAULT;
@@ -273,6 +289,13 @@ impl<T: Item + 'static> Component for Menu<T> {
             return EventResult::Ignored(None);
         }
 
+        if let Some((key, callback)) = &self.extra_key {
+            if event == *key {
+                callback(cx.editor, self.selection());
+                return EventResult::Consumed(None);
+            }
+        }
+
         match event {
             // esc or ctrl-c aborts the completion and closes the menu
             key!(Esc) | ctrl!('c') => {
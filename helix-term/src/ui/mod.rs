@@ -283,6 +283,22 @@ pub mod completers {
             .collect()
     }
 
+    /// Paths of the files that currently have diagnostics, for `:diagnostics`.
+    pub fn diagnostic_paths(editor: &Editor, input: &str) -> Vec<Completion> {
+        let paths = editor.diagnostics.keys().map(|path| {
+            Cow::from(
+                helix_stdx::path::get_relative_path(path.as_path())
+                    .display()
+                    .to_string(),
+            )
+        });
+
+        fuzzy_match(input, paths, true)
+            .into_iter()
+            .map(|(path, _)| ((0..), path))
+            .collect()
+    }
+
     pub fn theme(_editor: &Editor, input: &str) -> Vec<Completion> {
         let mut names = theme::Loader::read_names(&helix_loader::config_dir().join("themes"));
         for rt_dir in helix_loader::runtime_dirs() {
@@ -378,6 +394,21 @@ pub mod completers {
             .collect()
     }
 
+    /// Names of the custom requests that a server attached to the current
+    /// document can serve, for `:lsp-request`.
+    pub fn lsp_request(editor: &Editor, input: &str) -> Vec<Completion> {
+        let doc = doc!(editor);
+        let names = crate::commands::lsp::CUSTOM_REQUESTS
+            .iter()
+            .filter(|request| doc.language_servers().any(|ls| ls.name() == request.server))
+            .map(|request| Cow::from(request.name));
+
+        fuzzy_match(input, names, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name))
+            .collect()
+    }
+
     pub fn directory(editor: &Editor, input: &str) -> Vec<Completion> {
         directory_with_git_ignore(editor, input, true)
     }
@@ -509,6 +540,24 @@ pub mod completers {
         }
     }
 
+    /// Completes `:goto-symbol` arguments from the symbol names cached by
+    /// the last document symbols request for the focused document; empty
+    /// until symbols were fetched once (e.g. by running the command or the
+    /// symbol picker).
+    pub fn symbol_name(editor: &Editor, input: &str) -> Vec<Completion> {
+        let current_doc = doc!(editor).id();
+        let names = editor
+            .cached_symbol_names
+            .iter()
+            .filter(|(doc_id, _)| *doc_id == current_doc)
+            .flat_map(|(_, names)| names.iter().cloned());
+
+        fuzzy_match(input, names, false)
+            .into_iter()
+            .map(|(name, _)| ((0..), name.into()))
+            .collect()
+    }
+
     pub fn register(editor: &Editor, input: &str) -> Vec<Completion> {
         let iter = editor
             .registers
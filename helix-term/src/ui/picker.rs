@@ -17,13 +17,14 @@ use nucleo::{Config, Nucleo, Utf32String};
 use tui::{
     buffer::Buffer as Surface,
     layout::Constraint,
-    text::{Span, Spans},
-    widgets::{Block, BorderType, Cell, Table},
+    text::{Span, Spans, Text},
+    widgets::{Block, BorderType, Cell, Paragraph, Table, Wrap},
 };
 
 use tui::widgets::Widget;
 
 use std::{
+    borrow::Cow,
     collections::HashMap,
     io::Read,
     path::PathBuf,
@@ -92,6 +93,15 @@ pub enum CachedPreview {
     Binary,
     LargeFile,
     NotFound,
+    /// The file exists but could not be read for previewing (permissions,
+    /// deleted from under us, invalid contents, ...).
+    Unavailable(String),
+}
+
+impl CachedPreview {
+    fn unavailable(reason: impl std::fmt::Display) -> Self {
+        CachedPreview::Unavailable(format!("<Preview unavailable: {}>", reason))
+    }
 }
 
 // We don't store this enum in the cache so as to avoid lifetime constraints
@@ -119,6 +129,7 @@ impl Preview<'_, '_> {
                 CachedPreview::Binary => "<Binary file>",
                 CachedPreview::LargeFile => "<File too large to preview>",
                 CachedPreview::NotFound => "<File not found>",
+                CachedPreview::Unavailable(reason) => reason,
             },
         }
     }
@@ -195,6 +206,36 @@ pub struct Picker<T: Item> {
     widths: Vec<Constraint>,
 
     callback_fn: PickerCallback<T>,
+    /// An additional action bound to `A-r`, for pickers that support acting
+    /// on the selected item without jumping to it (e.g. renaming a symbol
+    /// straight from the symbol picker).
+    alternate_callback_fn: Option<AlternateCallback<T>>,
+    /// Pretty-prints the selected item as JSON for `A-j`, a debugging aid
+    /// for inspecting raw LSP responses. Only active when
+    /// `editor.lsp.debug-picker-json` is enabled.
+    raw_json_fn: Option<RawJsonCallback<T>>,
+    /// Full, possibly multi-line text of the highlighted item (e.g. a rustc
+    /// diagnostic message that the column truncates to one line), rendered
+    /// word-wrapped in a pane beneath the list. Only active when
+    /// `editor.lsp.diagnostic-picker-detail` is enabled.
+    detail_fn: Option<DetailCallback<T>>,
+    /// The [Action] the plain `Enter` confirm passes to the callback;
+    /// `Ctrl-s`, `Ctrl-v` and `Alt-Enter` are not affected. Defaults to
+    /// [Action::Replace].
+    confirm_action: Action,
+    /// Called with the highlighted item once the editor has been idle, e.g.
+    /// to lazily resolve extra information for the selection.
+    idle_fn: Option<IdleCallback<T>>,
+    /// Shows the highlighted item's documentation on `A-k` without closing
+    /// the picker, e.g. hover docs in the symbol pickers.
+    doc_preview_fn: Option<DocPreviewCallback<T>>,
+    /// Rebuilds the option list when `A-i` toggles showing items that a
+    /// workspace exclude filter suppressed, see
+    /// [`Picker::with_hidden_options`]. Called with the new toggle state.
+    hidden_options_fn: Option<HiddenOptionsCallback<T>>,
+    /// Whether suppressed options are currently shown; shared with the
+    /// picker's creator so sources that re-query can follow the toggle.
+    show_hidden: Arc<AtomicBool>,
 
     pub truncate_start: bool,
     /// Caches paths to documents
@@ -276,6 +317,14 @@ impl<T: Item + 'static> Picker<T> {
             truncate_start: true,
             show_preview: true,
             callback_fn: Box::new(callback_fn),
+            alternate_callback_fn: None,
+            raw_json_fn: None,
+            detail_fn: None,
+            confirm_action: Action::Replace,
+            idle_fn: None,
+            doc_preview_fn: None,
+            hidden_options_fn: None,
+            show_hidden: Arc::new(AtomicBool::new(false)),
             completion_height: 0,
             widths: Vec::new(),
             preview_cache: HashMap::new(),
@@ -308,6 +357,68 @@ impl<T: Item + 'static> Picker<T> {
         self
     }
 
+    /// Binds `callback_fn` to `A-r`, see [`Picker::alternate_callback_fn`].
+    pub fn with_alternate_action(
+        mut self,
+        callback_fn: impl Fn(&mut Context, &T) + 'static,
+    ) -> Self {
+        self.alternate_callback_fn = Some(Box::new(callback_fn));
+        self
+    }
+
+    /// Binds `to_json` to `A-j`, see [`Picker::raw_json_fn`].
+    pub fn with_raw_json(mut self, to_json: impl Fn(&T) -> Option<String> + 'static) -> Self {
+        self.raw_json_fn = Some(Box::new(to_json));
+        self
+    }
+
+    /// Sets `to_detail`, see [`Picker::detail_fn`].
+    pub fn with_detail(mut self, to_detail: impl Fn(&T) -> Option<String> + 'static) -> Self {
+        self.detail_fn = Some(Box::new(to_detail));
+        self
+    }
+
+    /// Sets `confirm_action`, see [`Picker::confirm_action`].
+    pub fn with_confirm_action(mut self, action: Action) -> Self {
+        self.confirm_action = action;
+        self
+    }
+
+    /// Sets `on_idle`, see [`Picker::idle_fn`].
+    pub fn with_idle_callback(mut self, on_idle: impl Fn(&mut Context, &T) + 'static) -> Self {
+        self.idle_fn = Some(Box::new(on_idle));
+        self
+    }
+
+    /// Binds `callback_fn` to `A-k`, see [`Picker::doc_preview_fn`]. Unlike
+    /// the other actions this keeps the picker open.
+    pub fn with_doc_preview(mut self, callback_fn: impl Fn(&mut Context, &T) + 'static) -> Self {
+        self.doc_preview_fn = Some(Box::new(callback_fn));
+        self
+    }
+
+    /// Sets the text rendered in front of the input line, used as a title,
+    /// e.g. the file name in the single-file diagnostics picker.
+    pub fn with_prompt_text(mut self, prompt: Cow<'static, str>) -> Self {
+        self.prompt.set_prompt(prompt);
+        self
+    }
+
+    /// Binds `A-i` to toggle between hiding and showing options that a
+    /// workspace exclude filter suppressed. `rebuild` produces the full
+    /// option list for a given toggle state; `show_hidden` is shared with
+    /// the caller so sources that re-query (e.g. dynamic pickers) can follow
+    /// the current state.
+    pub fn with_hidden_options(
+        mut self,
+        show_hidden: Arc<AtomicBool>,
+        rebuild: impl Fn(bool) -> Vec<T> + 'static,
+    ) -> Self {
+        self.show_hidden = show_hidden;
+        self.hidden_options_fn = Some(Box::new(rebuild));
+        self
+    }
+
     pub fn set_options(&mut self, new_options: Vec<T>) {
         self.matcher.restart(false);
         let injector = self.matcher.injector();
@@ -421,19 +532,21 @@ impl<T: Item + 'static> Picker<T> {
                     self.read_buffer.clear();
                     Ok((metadata, content_type))
                 });
-                let preview = data
-                    .map(
-                        |(metadata, content_type)| match (metadata.len(), content_type) {
-                            (_, content_inspector::ContentType::BINARY) => CachedPreview::Binary,
-                            (size, _) if size > MAX_FILE_SIZE_FOR_PREVIEW => {
-                                CachedPreview::LargeFile
-                            }
-                            _ => Document::open(path, None, None, editor.config.clone())
-                                .map(|doc| CachedPreview::Document(Box::new(doc)))
-                                .unwrap_or(CachedPreview::NotFound),
-                        },
-                    )
-                    .unwrap_or(CachedPreview::NotFound);
+                let preview = match data {
+                    Ok((metadata, content_type)) => match (metadata.len(), content_type) {
+                        (_, content_inspector::ContentType::BINARY) => CachedPreview::Binary,
+                        (size, _) if size > MAX_FILE_SIZE_FOR_PREVIEW => CachedPreview::LargeFile,
+                        _ => Document::open(path, None, None, editor.config.clone())
+                            .map(|doc| CachedPreview::Document(Box::new(doc)))
+                            .unwrap_or_else(CachedPreview::unavailable),
+                    },
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                        CachedPreview::NotFound
+                    }
+                    // tell read-protected apart from missing files, it matters when
+                    // debugging why a goto target can't be previewed
+                    Err(err) => CachedPreview::unavailable(err),
+                };
                 self.preview_cache.insert(path.to_owned(), preview);
                 Preview::Cached(&self.preview_cache[path])
             }
@@ -445,6 +558,12 @@ impl<T: Item + 'static> Picker<T> {
     }
 
     fn handle_idle_timeout(&mut self, cx: &mut Context) -> EventResult {
+        if let Some(idle_fn) = &self.idle_fn {
+            if let Some(item) = self.selection() {
+                idle_fn(cx, item);
+            }
+        }
+
         let Some((current_file, _)) = self.current_file(cx.editor) else {
             return EventResult::Consumed(None);
         };
@@ -683,6 +802,22 @@ impl<T: Item + 'static> Picker<T> {
         );
     }
 
+    /// Renders the detail pane beneath the list, see [`Picker::detail_fn`].
+    /// Newlines in `text` are preserved, everything else is word-wrapped.
+    fn render_detail(&mut self, area: Rect, text: &Text, surface: &mut Surface, cx: &mut Context) {
+        let background = cx.editor.theme.get("ui.background");
+        surface.clear_with(area, background);
+
+        const BLOCK: Block<'_> = Block::bordered();
+        let inner = BLOCK.inner(area).inner(Margin::horizontal(1));
+        BLOCK.render(area, surface);
+
+        Paragraph::new(text)
+            .style(cx.editor.theme.get("ui.text"))
+            .wrap(Wrap { trim: false })
+            .render(inner, surface);
+    }
+
     fn render_preview(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         // -- Render the frame:
         // clear area
@@ -700,6 +835,8 @@ impl<T: Item + 'static> Picker<T> {
         BLOCK.render(area, surface);
 
         if let Some((path, range)) = self.current_file(cx.editor) {
+            let center =
+                cx.editor.config().picker_preview_align == helix_view::editor::PreviewAlign::Center;
             let preview = self.get_preview(path, cx.editor);
             let doc = match preview.document() {
                 Some(doc)
@@ -724,7 +861,7 @@ impl<T: Item + 'static> Picker<T> {
                 let text = doc.text().slice(..);
                 let start = text.line_to_char(start_line);
                 let middle = text.line_to_char(start_line + height / 2);
-                if height < inner.height as usize {
+                if center && height < inner.height as usize {
                     let text_fmt = doc.text_format(inner.width, None);
                     let annotations = TextAnnotations::default();
                     (offset.anchor, offset.vertical_offset) = char_idx_at_visual_offset(
@@ -816,8 +953,30 @@ impl<T: Item + 'static + Send + Sync> Component for Picker<T> {
             area.width
         };
 
-        let picker_area = area.with_width(picker_width);
+        let mut picker_area = area.with_width(picker_width);
+
+        // the highlighted item's full text, rendered beneath the list so long
+        // entries (e.g. multi-line diagnostic messages) stay readable even
+        // though their column is truncated to a single line
+        let mut detail = None;
+        if cx.editor.config().lsp.diagnostic_picker_detail {
+            if let (Some(detail_fn), Some(option)) = (&self.detail_fn, self.selection()) {
+                detail = detail_fn(option).map(Text::from);
+            }
+        }
+        let detail_area = detail.as_ref().map(|text| {
+            // borders plus the horizontal margin on both sides
+            let (_, height) = crate::ui::text::required_size(text, picker_width.saturating_sub(4));
+            let height = (height + 2).min(area.height / 3).max(3);
+            let detail_area = picker_area.clip_top(picker_area.height.saturating_sub(height));
+            picker_area = picker_area.clip_bottom(height);
+            detail_area
+        });
+
         self.render_picker(picker_area, surface, cx);
+        if let (Some(text), Some(detail_area)) = (detail, detail_area) {
+            self.render_detail(detail_area, &text, surface, cx);
+        }
 
         if render_preview {
             let preview_area = area.clip_left(picker_width);
@@ -891,7 +1050,7 @@ impl<T: Item + 'static + Send + Sync> Component for Picker<T> {
             }
             key!(Enter) => {
                 if let Some(option) = self.selection() {
-                    (self.callback_fn)(ctx, option, Action::Replace);
+                    (self.callback_fn)(ctx, option, self.confirm_action);
                 }
                 return close_fn(self);
             }
@@ -910,6 +1069,51 @@ impl<T: Item + 'static + Send + Sync> Component for Picker<T> {
             ctrl!('t') => {
                 self.toggle_preview();
             }
+            alt!('r') if self.alternate_callback_fn.is_some() => {
+                if let Some(option) = self.selection() {
+                    // always set above
+                    (self.alternate_callback_fn.as_ref().unwrap())(ctx, option);
+                }
+                return close_fn(self);
+            }
+            alt!('k') if self.doc_preview_fn.is_some() => {
+                if let Some(option) = self.selection() {
+                    // always set above
+                    (self.doc_preview_fn.as_ref().unwrap())(ctx, option);
+                }
+            }
+            alt!('j')
+                if self.raw_json_fn.is_some() && ctx.editor.config().lsp.debug_picker_json =>
+            {
+                let json = self
+                    .selection()
+                    // always set above
+                    .and_then(|option| (self.raw_json_fn.as_ref().unwrap())(option));
+                if let Some(json) = json {
+                    let contents = ui::Markdown::new(
+                        format!("```json\n{}\n```", json),
+                        ctx.editor.syn_loader.clone(),
+                    );
+                    let callback: compositor::Callback = Box::new(|compositor, _ctx| {
+                        compositor.push(Box::new(ui::Popup::new("picker-raw-json", contents)));
+                    });
+                    // keep the picker open underneath so the popup can be
+                    // dismissed and another entry inspected
+                    return EventResult::Consumed(Some(callback));
+                }
+            }
+            alt!('i') if self.hidden_options_fn.is_some() => {
+                let shown = !self.show_hidden.load(atomic::Ordering::Relaxed);
+                self.show_hidden.store(shown, atomic::Ordering::Relaxed);
+                // always set above
+                let options = (self.hidden_options_fn.as_ref().unwrap())(shown);
+                self.set_options(options);
+                ctx.editor.set_status(if shown {
+                    "Showing workspace-excluded results"
+                } else {
+                    "Hiding workspace-excluded results"
+                });
+            }
             _ => {
                 self.prompt_handle_event(event, ctx);
             }
@@ -946,6 +1150,12 @@ impl<T: Item> Drop for Picker<T> {
 }
 
 type PickerCallback<T> = Box<dyn Fn(&mut Context, &T, Action)>;
+type AlternateCallback<T> = Box<dyn Fn(&mut Context, &T)>;
+type RawJsonCallback<T> = Box<dyn Fn(&T) -> Option<String>>;
+type DetailCallback<T> = Box<dyn Fn(&T) -> Option<String>>;
+type IdleCallback<T> = Box<dyn Fn(&mut Context, &T)>;
+type DocPreviewCallback<T> = Box<dyn Fn(&mut Context, &T)>;
+type HiddenOptionsCallback<T> = Box<dyn Fn(bool) -> Vec<T>>;
 
 /// Returns a new list of options to replace the contents of the picker
 /// when called with the current picker query,
@@ -108,6 +108,10 @@ impl Prompt {
         self
     }
 
+    pub fn set_prompt(&mut self, prompt: Cow<'static, str>) {
+        self.prompt = prompt;
+    }
+
     pub fn line(&self) -> &String {
         &self.line
     }
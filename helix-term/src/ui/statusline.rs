@@ -1,5 +1,4 @@
 use helix_core::{coords_at_pos, encoding, Position};
-use helix_lsp::lsp::DiagnosticSeverity;
 use helix_view::document::DEFAULT_LANGUAGE_NAME;
 use helix_view::{
     document::{Mode, SCRATCH_BUFFER_NAME},
@@ -163,6 +162,7 @@ where
         helix_view::editor::StatusLineElement::Spacer => render_spacer,
         helix_view::editor::StatusLineElement::VersionControl => render_version_control,
         helix_view::editor::StatusLineElement::Register => render_register,
+        helix_view::editor::StatusLineElement::Occurrences => render_occurrences,
     }
 }
 
@@ -263,20 +263,9 @@ fn render_workspace_diagnostics<F>(context: &mut RenderContext, write: F)
 where
     F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
 {
-    let (warnings, errors) =
-        context
-            .editor
-            .diagnostics
-            .values()
-            .flatten()
-            .fold((0, 0), |mut counts, (diag, _)| {
-                match diag.severity {
-                    Some(DiagnosticSeverity::WARNING) => counts.0 += 1,
-                    Some(DiagnosticSeverity::ERROR) | None => counts.1 += 1,
-                    _ => {}
-                }
-                counts
-            });
+    // aggregated whenever diagnostics are published, not per frame
+    let helix_view::editor::DiagnosticCounts { warnings, errors } =
+        context.editor.workspace_diagnostic_counts;
 
     if warnings > 0 || errors > 0 {
         write(context, " W ".into(), None);
@@ -531,3 +520,31 @@ where
         write(context, format!(" reg={} ", reg), None)
     }
 }
+
+fn render_occurrences<F>(context: &mut RenderContext, write: F)
+where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    let Some(cached) = &context.editor.cached_document_highlights else {
+        return;
+    };
+    let doc = context.doc;
+    let cursor = doc
+        .selection(context.view.id)
+        .primary()
+        .cursor(doc.text().slice(..));
+    // only shown while the highlights are still valid, i.e. the document is
+    // unchanged and the cursor still sits on the highlighted symbol
+    if cached.doc != doc.id()
+        || cached.version != doc.version()
+        || !cached.ranges.iter().any(|range| range.contains(cursor))
+    {
+        return;
+    }
+    let count = cached.ranges.len();
+    write(
+        context,
+        format!(" {} occurrence{} ", count, if count == 1 { "" } else { "s" }),
+        None,
+    );
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 
 use helix_core::diagnostic::Severity;
@@ -46,6 +47,7 @@ impl Styles {
 pub struct InlineDiagnostics<'a> {
     state: InlineDiagnosticAccumulator<'a>,
     eol_diagnostics: DiagnosticFilter,
+    related_suffix: bool,
     styles: Styles,
 }
 
@@ -56,11 +58,13 @@ impl<'a> InlineDiagnostics<'a> {
         cursor: usize,
         config: InlineDiagnosticsConfig,
         eol_diagnostics: DiagnosticFilter,
+        related_suffix: bool,
     ) -> Self {
         InlineDiagnostics {
             state: InlineDiagnosticAccumulator::new(cursor, doc, config),
             styles: Styles::new(theme),
             eol_diagnostics,
+            related_suffix,
         }
     }
 }
@@ -78,10 +82,21 @@ struct Renderer<'a, 'b> {
     first_row: u16,
     row: u16,
     config: &'a InlineDiagnosticsConfig,
+    related_suffix: bool,
     styles: &'a Styles,
 }
 
 impl Renderer<'_, '_> {
+    /// The message to render for `diag`: its LSP message, with the pointer to
+    /// the first related location appended when `lsp.diagnostic-related-suffix`
+    /// is enabled.
+    fn message<'d>(&self, diag: &'d Diagnostic) -> Cow<'d, str> {
+        match &diag.related_suffix {
+            Some(suffix) if self.related_suffix => format!("{}{}", diag.message, suffix).into(),
+            _ => diag.message.as_str().into(),
+        }
+    }
+
     fn draw_decoration(&mut self, g: &'static str, severity: Severity, col: u16) {
         self.draw_decoration_at(g, severity, col, self.row)
     }
@@ -105,7 +120,7 @@ impl Renderer<'_, '_> {
         let (new_col, _) = self.renderer.set_string_truncated(
             self.renderer.viewport.x + col + 1,
             row,
-            &diag.message,
+            &self.message(diag),
             width.saturating_sub(col + 1) as usize,
             |_| style,
             true,
@@ -129,8 +144,9 @@ impl Renderer<'_, '_> {
         let text_col = col + self.config.prefix_len + 1;
         let text_fmt = self.config.text_fmt(text_col, self.renderer.viewport.width);
         let annotations = TextAnnotations::default();
+        let message = self.message(diag);
         let formatter = DocumentFormatter::new_at_prev_checkpoint(
-            diag.message.as_str().trim().into(),
+            message.trim().into(),
             &text_fmt,
             &annotations,
             0,
@@ -267,6 +283,7 @@ impl Decoration for InlineDiagnostics<'_> {
                 first_row: pos.visual_line,
                 row: pos.visual_line,
                 config: &self.state.config,
+                related_suffix: self.related_suffix,
                 styles: &self.styles,
             };
             col_off = renderer.draw_eol_diagnostic(eol_diagnostic, pos.visual_line, virt_off.col);
@@ -278,6 +295,7 @@ impl Decoration for InlineDiagnostics<'_> {
             first_row: pos.visual_line + virt_off.row as u16,
             row: pos.visual_line + virt_off.row as u16,
             config: &self.state.config,
+            related_suffix: self.related_suffix,
             styles: &self.styles,
         };
         renderer.draw_multi_diagnostics(&mut self.state.stack);
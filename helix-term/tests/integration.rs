@@ -19,6 +19,7 @@ mod test {
     mod auto_pairs;
     mod commands;
     mod languages;
+    mod lsp;
     mod movement;
     mod prompt;
     mod splits;
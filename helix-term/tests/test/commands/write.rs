@@ -367,6 +367,48 @@ async fn test_write_new_path() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_write_new_path_moves_diagnostics() -> anyhow::Result<()> {
+    let file1 = tempfile::NamedTempFile::new().unwrap();
+    let file2 = tempfile::NamedTempFile::new().unwrap();
+    let mut app = helpers::AppBuilder::new()
+        .with_file(file1.path(), None)
+        .build()?;
+
+    // as if a server had published a diagnostic for the file's original URI
+    app.editor.diagnostics.insert(
+        path::normalize(file1.path()),
+        vec![(
+            helix_lsp::lsp::Diagnostic {
+                message: "stale".to_string(),
+                ..Default::default()
+            },
+            helix_lsp::LanguageServerId::default(),
+        )],
+    );
+
+    test_key_sequence(
+        &mut app,
+        Some(&format!(":w {}<ret>", file2.path().to_string_lossy())),
+        Some(&|app| {
+            assert!(!app.editor.is_err());
+            // save-as is a rename from the LSP's perspective: nothing may
+            // stay keyed by the old path and the entries move to the new one
+            assert!(!app
+                .editor
+                .diagnostics
+                .contains_key(&path::normalize(file1.path())));
+            let diagnostics = &app.editor.diagnostics[&path::normalize(file2.path())];
+            assert_eq!(1, diagnostics.len());
+            assert_eq!("stale", diagnostics[0].0.message);
+        }),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_write_fail_new_path() -> anyhow::Result<()> {
     let file = helpers::new_readonly_tempfile()?;
@@ -0,0 +1,478 @@
+use super::*;
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use helix_lsp::{
+    lsp,
+    test_server::{ScriptedResponse, TestServer},
+    Client, OffsetEncoding,
+};
+use helix_term::application::Application;
+use helix_view::handlers::lsp::ApplyEditErrorKind;
+use serde_json::{json, Value};
+
+/// Binds the rust language to the scripted server so opening a `.rs` file
+/// attaches it. The configured command never runs; the client is registered
+/// in-process by [`app_with_test_server`].
+const LANG_OVERRIDES: &str = r#"
+[language-server.test-server]
+command = "test-server"
+
+[[language]]
+name = "rust"
+language-servers = ["test-server"]
+"#;
+
+fn capabilities() -> Value {
+    json!({
+        "documentSymbolProvider": true,
+        "definitionProvider": true,
+        "codeActionProvider": true,
+    })
+}
+
+fn file_content() -> String {
+    (0..8).map(|i| format!("// line {i}\n")).collect()
+}
+
+/// A temp file with a `.rs` extension so the language (and with it the
+/// scripted server) is detected.
+fn temp_rs_file() -> anyhow::Result<tempfile::NamedTempFile> {
+    use std::io::Write;
+
+    let mut file = tempfile::Builder::new().suffix(".rs").tempfile()?;
+    file.as_file_mut().write_all(file_content().as_bytes())?;
+    file.as_file_mut().flush()?;
+    Ok(file)
+}
+
+fn file_uri(path: &Path) -> lsp::Url {
+    lsp::Url::from_file_path(helix_stdx::path::canonicalize(path)).unwrap()
+}
+
+/// Builds an app on `file` with a client connected to `server` attached to
+/// its document, going through the same registry and transport code paths as
+/// a spawned language server.
+async fn app_with_test_server(
+    file: &Path,
+    server: &Arc<TestServer>,
+) -> anyhow::Result<Application> {
+    app_with_test_server_and_config(file, server, test_config()).await
+}
+
+/// [app_with_test_server] with a caller-tweaked config, e.g. non-default
+/// `lsp.picker-actions`.
+async fn app_with_test_server_and_config(
+    file: &Path,
+    server: &Arc<TestServer>,
+    mut config: helix_term::config::Config,
+) -> anyhow::Result<Application> {
+    config.editor.lsp.enable = true;
+
+    let mut app = AppBuilder::new()
+        .with_config(config)
+        .with_lang_loader(test_syntax_loader(Some(LANG_OVERRIDES.into())))
+        .with_file(file, None)
+        .build()?;
+
+    // mirror what `Registry::start_client` passes to `Client::start` for a
+    // document outside of any workspace, so `try_add_doc` accepts the client
+    let (workspace, _) = helix_core::find_workspace();
+    let workspace = helix_stdx::path::normalize(workspace);
+    let root_uri = lsp::Url::from_file_path(&workspace).ok();
+
+    let (reader, writer) = server.connect();
+    let client = app.editor.language_servers.register_client(
+        "test-server".to_string(),
+        false,
+        |id| {
+            Client::start_with_transport(
+                reader,
+                writer,
+                workspace,
+                root_uri,
+                id,
+                "test-server".to_string(),
+                5,
+            )
+        },
+    );
+
+    // the handshake is in-process, so this resolves almost immediately
+    for _ in 0..100 {
+        if client.is_initialized() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    anyhow::ensure!(client.is_initialized(), "test server failed to initialize");
+
+    let doc_id = helix_view::doc!(app.editor).id();
+    app.editor.refresh_language_servers(doc_id);
+
+    Ok(app)
+}
+
+fn cursor_line(app: &Application) -> usize {
+    let (view, doc) = helix_view::current_ref!(app.editor);
+    let text = doc.text();
+    text.char_to_line(doc.selection(view.id).primary().cursor(text.slice(..)))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn symbol_picker_flattens_nested_symbols() -> anyhow::Result<()> {
+    let file = temp_rs_file()?;
+    let server = TestServer::new(capabilities());
+
+    // a hierarchical response; the picker must flatten it to [Foo, bar]
+    server.respond(
+        "textDocument/documentSymbol",
+        ScriptedResponse::ok(json!([
+            {
+                "name": "Foo",
+                "kind": 23,
+                "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 4, "character": 0 } },
+                "selectionRange": { "start": { "line": 1, "character": 7 }, "end": { "line": 1, "character": 10 } },
+                "children": [
+                    {
+                        "name": "bar",
+                        "kind": 12,
+                        "range": { "start": { "line": 3, "character": 0 }, "end": { "line": 3, "character": 11 } },
+                        "selectionRange": { "start": { "line": 3, "character": 3 }, "end": { "line": 3, "character": 6 } },
+                    }
+                ],
+            }
+        ])),
+    );
+
+    let mut app = app_with_test_server(file.path(), &server).await?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some("<space>s"), None),
+            // the child symbol is the second (flattened) entry
+            (
+                Some("<down><ret>"),
+                Some(&|app| assert_eq!(3, cursor_line(app))),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn code_action_menu_sorts_preferred_action_first() -> anyhow::Result<()> {
+    let file = temp_rs_file()?;
+    let server = TestServer::new(capabilities());
+    let uri = file_uri(file.path());
+
+    let insertion = |new_text: &str| {
+        json!({
+            "changes": {
+                uri.to_string(): [{
+                    "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+                    "newText": new_text,
+                }]
+            }
+        })
+    };
+
+    // the server reports the preferred action last; accepting the first menu
+    // entry must still apply it
+    server.respond(
+        "textDocument/codeAction",
+        ScriptedResponse::ok(json!([
+            {
+                "title": "not preferred",
+                "kind": "quickfix",
+                "edit": insertion("SECOND "),
+            },
+            {
+                "title": "preferred",
+                "kind": "quickfix",
+                "isPreferred": true,
+                "edit": insertion("FIRST "),
+            }
+        ])),
+    );
+
+    let mut app = app_with_test_server(file.path(), &server).await?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            (Some("<space>a"), None),
+            (
+                Some("<ret>"),
+                Some(&|app| {
+                    let doc = helix_view::doc!(app.editor);
+                    assert!(
+                        doc.text().to_string().starts_with("FIRST "),
+                        "the preferred action was not applied: {}",
+                        doc.text()
+                    );
+                }),
+            ),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn goto_definition_jumps_to_a_single_location() -> anyhow::Result<()> {
+    let file = temp_rs_file()?;
+    let server = TestServer::new(capabilities());
+    let uri = file_uri(file.path());
+
+    server.respond(
+        "textDocument/definition",
+        ScriptedResponse::ok(json!([
+            {
+                "uri": uri,
+                "range": { "start": { "line": 5, "character": 0 }, "end": { "line": 5, "character": 4 } },
+            }
+        ])),
+    );
+
+    let mut app = app_with_test_server(file.path(), &server).await?;
+
+    // a single location must be jumped to directly, without a picker
+    test_key_sequence(
+        &mut app,
+        Some("gd"),
+        Some(&|app| assert_eq!(5, cursor_line(app))),
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn goto_definition_opens_a_picker_for_multiple_locations() -> anyhow::Result<()> {
+    let file = temp_rs_file()?;
+    let server = TestServer::new(capabilities());
+    let uri = file_uri(file.path());
+
+    server.respond(
+        "textDocument/definition",
+        ScriptedResponse::ok(json!([
+            {
+                "uri": uri,
+                "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 4 } },
+            },
+            {
+                "uri": uri,
+                "range": { "start": { "line": 6, "character": 0 }, "end": { "line": 6, "character": 4 } },
+            }
+        ])),
+    );
+
+    let mut app = app_with_test_server(file.path(), &server).await?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            // multiple locations open a picker instead of jumping
+            (Some("gd"), Some(&|app| assert_eq!(0, cursor_line(app)))),
+            (Some("<ret>"), Some(&|app| assert_eq!(2, cursor_line(app)))),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn picker_jump_leaves_other_views_of_the_document_alone() -> anyhow::Result<()> {
+    let file = temp_rs_file()?;
+    let other_file = temp_file_with_contents(&file_content())?;
+    let server = TestServer::new(capabilities());
+    let other_uri = file_uri(other_file.path());
+
+    // two locations so confirming goes through the picker
+    server.respond(
+        "textDocument/definition",
+        ScriptedResponse::ok(json!([
+            {
+                "uri": other_uri,
+                "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 4 } },
+            },
+            {
+                "uri": other_uri,
+                "range": { "start": { "line": 6, "character": 0 }, "end": { "line": 6, "character": 4 } },
+            }
+        ])),
+    );
+
+    let mut config = test_config();
+    // `load` keeps the focused view on its document, so the jump lands in a
+    // buffer that is only visible in the other splits
+    config.editor.lsp.picker_actions.goto = helix_view::editor::Action::Load;
+
+    let mut app = app_with_test_server_and_config(file.path(), &server, config).await?;
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            // the target document in two splits, the attached document focused
+            (
+                Some(&format!(
+                    ":o {}<ret><C-w>s<C-w>s:o {}<ret>",
+                    other_file.path().display(),
+                    file.path().display()
+                )),
+                None,
+            ),
+            (Some("gd"), None),
+            (
+                Some("<ret>"),
+                Some(&|app| {
+                    let focused = app.editor.tree.focus;
+                    let mut unfocused = 0;
+                    for (view, _) in app.editor.tree.views() {
+                        if view.id == focused {
+                            continue;
+                        }
+                        unfocused += 1;
+                        let doc = app.editor.documents.get(&view.doc).unwrap();
+                        let text = doc.text();
+                        // only the focused view is targeted by the jump: the
+                        // splits showing the document keep selection and scroll
+                        let line = text
+                            .char_to_line(doc.selection(view.id).primary().cursor(text.slice(..)));
+                        assert_eq!(0, line);
+                        assert_eq!(0, view.offset.anchor);
+                        // the confirmed location is recorded against the
+                        // focused view, shown once the buffer is displayed there
+                        let focused_line = text.char_to_line(
+                            doc.selections()[&focused].primary().cursor(text.slice(..)),
+                        );
+                        assert_eq!(2, focused_line);
+                    }
+                    assert_eq!(2, unfocused);
+                }),
+            ),
+            // `:q!` alone wouldn't close the splits
+            (Some(":qa!<ret>"), None),
+        ],
+        true,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn workspace_edit_reports_the_failing_change() -> anyhow::Result<()> {
+    let file = temp_file_with_contents(&file_content())?;
+    let mut app = AppBuilder::new().with_file(file.path(), None).build()?;
+
+    let text_edit = lsp::OneOf::Left(lsp::TextEdit::new(
+        lsp::Range::default(),
+        "patched ".to_string(),
+    ));
+    let good = lsp::TextDocumentEdit {
+        text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+            uri: file_uri(file.path()),
+            version: None,
+        },
+        edits: vec![text_edit.clone()],
+    };
+    let bad = lsp::TextDocumentEdit {
+        text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+            uri: lsp::Url::parse("untitled://server/nope").unwrap(),
+            version: None,
+        },
+        edits: vec![text_edit],
+    };
+    let edit = lsp::WorkspaceEdit {
+        document_changes: Some(lsp::DocumentChanges::Edits(vec![good, bad])),
+        ..Default::default()
+    };
+
+    let err = app
+        .editor
+        .apply_workspace_edit(OffsetEncoding::Utf16, &edit)
+        .expect_err("the edit must fail on the bogus URI");
+    assert_eq!(1, err.failed_change_idx);
+    assert!(matches!(err.kind, ApplyEditErrorKind::UnknownURISchema));
+
+    // changes before the failing one were applied
+    let doc = helix_view::doc!(app.editor);
+    assert!(doc.text().to_string().starts_with("patched "));
+
+    let _ = app.close().await;
+
+    Ok(())
+}
+
+/// A versioned change whose document was mutated between the request and the
+/// application must be rejected as [`ApplyEditErrorKind::DocumentChanged`],
+/// reporting the index of the stale change.
+#[tokio::test(flavor = "multi_thread")]
+async fn workspace_edit_rejects_a_stale_document_version() -> anyhow::Result<()> {
+    let file = temp_file_with_contents(&file_content())?;
+    let mut app = AppBuilder::new().with_file(file.path(), None).build()?;
+
+    // the version the server would have seen when the edit was requested
+    let requested_version = helix_view::doc!(app.editor).version();
+
+    let text_edit = lsp::OneOf::Left(lsp::TextEdit::new(
+        lsp::Range::default(),
+        "patched ".to_string(),
+    ));
+    let unversioned = lsp::TextDocumentEdit {
+        text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+            uri: file_uri(file.path()),
+            version: None,
+        },
+        edits: vec![text_edit.clone()],
+    };
+    let stale = lsp::TextDocumentEdit {
+        text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+            uri: file_uri(file.path()),
+            version: Some(requested_version),
+        },
+        edits: vec![text_edit],
+    };
+    let edit = lsp::WorkspaceEdit {
+        document_changes: Some(lsp::DocumentChanges::Edits(vec![unversioned, stale])),
+        ..Default::default()
+    };
+
+    // mutate the buffer before the edit is applied
+    {
+        let (view, doc) = helix_view::current!(app.editor);
+        let transaction = helix_core::Transaction::change(
+            doc.text(),
+            [(0, 0, Some("mutated ".into()))].into_iter(),
+        );
+        doc.apply(&transaction, view.id);
+    }
+
+    let err = app
+        .editor
+        .apply_workspace_edit(OffsetEncoding::Utf16, &edit)
+        .expect_err("the edit must fail on the stale version");
+    assert_eq!(1, err.failed_change_idx);
+    assert!(matches!(err.kind, ApplyEditErrorKind::DocumentChanged));
+
+    // the unversioned change before the stale one was applied regardless
+    let doc = helix_view::doc!(app.editor);
+    assert!(doc.text().to_string().starts_with("patched mutated "));
+
+    let _ = app.close().await;
+
+    Ok(())
+}
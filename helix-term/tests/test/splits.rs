@@ -195,3 +195,73 @@ async fn test_changes_in_splits_apply_to_all_views() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_buffer_switch_centers_selection() -> anyhow::Result<()> {
+    use helix_view::editor::Action;
+    use std::io::Write;
+
+    // Jumping into a tall buffer must leave the selection visible and roughly
+    // centered no matter how the buffer reaches a view: loaded in the
+    // background and focused later, switched to in place, or switched to from
+    // a fresh split.
+    fn assert_centered(app: &helix_term::application::Application) {
+        let (view, doc) = helix_view::current_ref!(app.editor);
+        let text = doc.text();
+        let cursor_line =
+            text.char_to_line(doc.selection(view.id).primary().cursor(text.slice(..)));
+        let anchor_line = text.char_to_line(view.offset.anchor);
+        let height = view.inner_height();
+        assert!(
+            (anchor_line..anchor_line + height).contains(&cursor_line),
+            "cursor line {cursor_line} not visible (viewport starts at {anchor_line}, height {height})"
+        );
+        assert!(
+            (anchor_line + height / 2).abs_diff(cursor_line) <= 1,
+            "cursor line {cursor_line} not centered (viewport starts at {anchor_line}, height {height})"
+        );
+    }
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    let content: String = (0..500).map(|i| format!("line {i}\n")).collect();
+    file.as_file_mut().write_all(content.as_bytes())?;
+    file.as_file_mut().flush()?;
+
+    let mut app = helpers::AppBuilder::new().build()?;
+
+    // Simulate an LSP jump with `Action::Load`: open the buffer in the
+    // background and store the jump target selection for the focused view.
+    let doc_id = app.editor.open(file.path(), Action::Load)?;
+    let view_id = app.editor.tree.focus;
+    let doc = app.editor.documents.get_mut(&doc_id).unwrap();
+    let target = doc.text().line_to_char(400);
+    doc.set_selection(view_id, Selection::point(target));
+
+    test_key_sequences(
+        &mut app,
+        vec![
+            // focusing the loaded buffer must center the jump target
+            (
+                Some(":buffer-next<ret>"),
+                Some(&|app| assert_centered(app)),
+            ),
+            // switching away and back (`Action::Replace`) must re-center the
+            // far-away selection instead of leaving it at the viewport edge
+            (
+                Some(":buffer-previous<ret>:buffer-next<ret>"),
+                Some(&|app| assert_centered(app)),
+            ),
+            // switching inside a fresh split must center as well
+            (
+                Some(":buffer-previous<ret><C-w>v:buffer-next<ret>"),
+                Some(&|app| assert_centered(app)),
+            ),
+            // close the split so the app can exit cleanly
+            (Some("<C-w>q"), None),
+        ],
+        false,
+    )
+    .await?;
+
+    Ok(())
+}
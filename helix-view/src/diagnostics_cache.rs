@@ -0,0 +1,161 @@
+//! Opt-in persistence of workspace diagnostics across sessions
+//! (`lsp.persist-diagnostics`): the diagnostics store is written to a
+//! per-workspace cache file on exit and loaded on startup, so the workspace
+//! diagnostics picker has something to triage before the language servers
+//! finish their first check. Restored entries land in
+//! [`Editor::restored_diagnostics`] — kept apart from live diagnostics since
+//! they have no live server — show as stale in the picker and are replaced
+//! per file as fresh diagnostics arrive. Entries for files modified since
+//! the cache was written are dropped on load rather than shown with
+//! possibly-wrong positions.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use helix_lsp::{lsp, OffsetEncoding};
+use serde::{Deserialize, Serialize};
+
+use crate::Editor;
+
+#[derive(Serialize, Deserialize)]
+struct CachedFile {
+    /// Modification time (seconds since the Unix epoch) of the file when the
+    /// cache was written.
+    mtime: u64,
+    diagnostics: Vec<CachedDiagnostic>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedDiagnostic {
+    diagnostic: lsp::Diagnostic,
+    /// The position encoding the publishing server negotiated; positions in
+    /// `diagnostic` are meaningless without it.
+    offset_encoding: String,
+}
+
+fn encoding_label(encoding: OffsetEncoding) -> &'static str {
+    match encoding {
+        OffsetEncoding::Utf8 => "utf-8",
+        OffsetEncoding::Utf16 => "utf-16",
+        OffsetEncoding::Utf32 => "utf-32",
+    }
+}
+
+fn encoding_from_label(label: &str) -> Option<OffsetEncoding> {
+    match label {
+        "utf-8" => Some(OffsetEncoding::Utf8),
+        "utf-16" => Some(OffsetEncoding::Utf16),
+        "utf-32" => Some(OffsetEncoding::Utf32),
+        _ => None,
+    }
+}
+
+/// One cache file per workspace, keyed by a hash of the workspace path.
+fn cache_path(workspace: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    workspace.hash(&mut hasher);
+    helix_loader::cache_dir().join(format!("diagnostics-{:016x}.json", hasher.finish()))
+}
+
+fn file_mtime(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Writes the current diagnostics to the workspace's cache file. Diagnostics
+/// whose server is gone are skipped (their encoding is unknown); files whose
+/// fresh diagnostics never arrived keep their restored entries, so a short
+/// session does not wipe the cache.
+pub fn save(editor: &Editor) {
+    if !editor.config().lsp.persist_diagnostics {
+        return;
+    }
+    let mut files: HashMap<PathBuf, CachedFile> = HashMap::new();
+    for (path, diags) in &editor.diagnostics {
+        let diagnostics: Vec<_> = diags
+            .iter()
+            .filter_map(|(diagnostic, server_id)| {
+                let encoding = editor
+                    .language_server_by_id(*server_id)?
+                    .offset_encoding();
+                Some(CachedDiagnostic {
+                    diagnostic: diagnostic.clone(),
+                    offset_encoding: encoding_label(encoding).to_string(),
+                })
+            })
+            .collect();
+        let Some(mtime) = file_mtime(path) else {
+            continue;
+        };
+        if !diagnostics.is_empty() {
+            files.insert(path.clone(), CachedFile { mtime, diagnostics });
+        }
+    }
+    for (path, diags) in &editor.restored_diagnostics {
+        if files.contains_key(path) {
+            continue;
+        }
+        let Some(mtime) = file_mtime(path) else {
+            continue;
+        };
+        let diagnostics = diags
+            .iter()
+            .map(|(diagnostic, encoding)| CachedDiagnostic {
+                diagnostic: diagnostic.clone(),
+                offset_encoding: encoding_label(*encoding).to_string(),
+            })
+            .collect();
+        files.insert(path.clone(), CachedFile { mtime, diagnostics });
+    }
+
+    let path = cache_path(&helix_loader::find_workspace().0);
+    let write = || -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_vec(&files)?)?;
+        Ok(())
+    };
+    if let Err(err) = write() {
+        log::error!("failed to write the diagnostics cache to {path:?}: {err}");
+    }
+}
+
+/// Loads the workspace's cached diagnostics into
+/// [`Editor::restored_diagnostics`], dropping entries for files that were
+/// modified since the cache was written.
+pub fn load(editor: &mut Editor) {
+    if !editor.config().lsp.persist_diagnostics {
+        return;
+    }
+    let path = cache_path(&helix_loader::find_workspace().0);
+    let Ok(contents) = std::fs::read(&path) else {
+        return;
+    };
+    let files: HashMap<PathBuf, CachedFile> = match serde_json::from_slice(&contents) {
+        Ok(files) => files,
+        Err(err) => {
+            log::warn!("discarding the unreadable diagnostics cache {path:?}: {err}");
+            return;
+        }
+    };
+    for (path, file) in files {
+        if file_mtime(&path) != Some(file.mtime) {
+            continue;
+        }
+        let diagnostics: Vec<_> = file
+            .diagnostics
+            .into_iter()
+            .filter_map(|cached| {
+                let encoding = encoding_from_label(&cached.offset_encoding)?;
+                Some((cached.diagnostic, encoding))
+            })
+            .collect();
+        if !diagnostics.is_empty() {
+            editor.restored_diagnostics.insert(path, diagnostics);
+        }
+    }
+}
@@ -125,6 +125,9 @@ pub struct Document {
     ///
     /// To know if they're up-to-date, check the `id` field in `DocumentInlayHints`.
     pub(crate) inlay_hints: HashMap<ViewId, DocumentInlayHints>,
+    /// Inline value annotations for the document, by view. Only set while a debug
+    /// session is stopped, cleared when execution resumes.
+    pub(crate) inline_values: HashMap<ViewId, Vec<InlineAnnotation>>,
     pub(crate) jump_labels: HashMap<ViewId, Vec<Overlay>>,
     /// Set to `true` when the document is updated, reset to `false` on the next inlay hints
     /// update from the LSP
@@ -169,6 +172,11 @@ pub struct Document {
 
     pub(crate) diagnostics: Vec<Diagnostic>,
     pub(crate) language_servers: HashMap<LanguageServerName, Arc<Client>>,
+    /// The language id last sent to the attached servers in `didOpen`. When
+    /// it changes (e.g. a header toggled between c and cpp with
+    /// `:set-language`) servers that stay attached still need the document
+    /// closed and reopened, since `didOpen` is what carries the id.
+    pub(crate) opened_language_id: Option<String>,
 
     diff_handle: Option<DiffHandle>,
     version_control_head: Option<Arc<ArcSwap<Box<str>>>>,
@@ -645,6 +653,7 @@ impl Document {
             text,
             selections: HashMap::default(),
             inlay_hints: HashMap::default(),
+            inline_values: HashMap::default(),
             inlay_hints_oudated: false,
             indent_style: DEFAULT_INDENT,
             line_ending,
@@ -661,6 +670,7 @@ impl Document {
             last_saved_revision: 0,
             modified_since_accessed: false,
             language_servers: HashMap::new(),
+            opened_language_id: None,
             diff_handle: None,
             config,
             version_control_head: None,
@@ -1184,6 +1194,7 @@ impl Document {
     pub fn remove_view(&mut self, view_id: ViewId) {
         self.selections.remove(&view_id);
         self.inlay_hints.remove(&view_id);
+        self.inline_values.remove(&view_id);
         self.jump_labels.remove(&view_id);
     }
 
@@ -1303,8 +1314,8 @@ impl Document {
                 true
             });
 
-            self.diagnostics.sort_by_key(|diagnostic| {
-                (diagnostic.range, diagnostic.severity, diagnostic.provider)
+            self.diagnostics.sort_by_cached_key(|diagnostic| {
+                diagnostic_sort_key(diagnostic, self.language.as_deref(), &self.language_servers)
             });
 
             // Update the inlay hint annotations' positions, helping ensure they are displayed in the proper place
@@ -1637,6 +1648,7 @@ impl Document {
             config.language_servers.iter().filter_map(move |features| {
                 let ls = &**self.language_servers.get(&features.name)?;
                 if ls.is_initialized()
+                    && !ls.is_crashed()
                     && ls.supports_feature(feature)
                     && features.has_feature(feature)
                 {
@@ -1767,6 +1779,21 @@ impl Document {
         )
     }
 
+    /// A compact ` → file.rs:42` pointer to the first related-information
+    /// location of `diagnostic` — for rustc borrow errors the "borrow later
+    /// used here" spot. `None` when there is no related information or the
+    /// location's URI does not resolve to a file path.
+    pub fn related_location_suffix(diagnostic: &helix_lsp::lsp::Diagnostic) -> Option<String> {
+        let info = diagnostic.related_information.as_ref()?.first()?;
+        let path = info.location.uri.to_file_path().ok()?;
+        let file_name = path.file_name()?.to_string_lossy();
+        Some(format!(
+            " → {}:{}",
+            file_name,
+            info.location.range.start.line + 1
+        ))
+    }
+
     pub fn lsp_diagnostic_to_diagnostic(
         text: &Rope,
         language_config: Option<&LanguageConfiguration>,
@@ -1849,6 +1876,7 @@ impl Document {
             source: diagnostic.source.clone(),
             data: diagnostic.data.clone(),
             provider: language_server_id,
+            related_suffix: Self::related_location_suffix(diagnostic),
         })
     }
 
@@ -1857,6 +1885,14 @@ impl Document {
         &self.diagnostics
     }
 
+    /// Ranks `provider` by its position in the `language-servers` list of the
+    /// language configuration, servers that are not part of the configuration
+    /// rank last. Used to order diagnostics merged from multiple language
+    /// servers deterministically instead of by arrival order.
+    pub fn language_server_priority(&self, provider: LanguageServerId) -> usize {
+        provider_priority(self.language.as_deref(), &self.language_servers, provider)
+    }
+
     pub fn replace_diagnostics(
         &mut self,
         diagnostics: impl IntoIterator<Item = Diagnostic>,
@@ -1872,15 +1908,66 @@ impl Document {
                 }
 
                 if let Some(source) = &d.source {
-                    unchanged_sources.contains(source)
+                    // a deduplicated entry lists every source it was merged
+                    // from, see [Document::deduplicate_diagnostics]
+                    source
+                        .split(',')
+                        .any(|source| unchanged_sources.iter().any(|unchanged| unchanged == source))
                 } else {
                     false
                 }
             });
         }
         self.diagnostics.extend(diagnostics);
-        self.diagnostics
-            .sort_by_key(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.provider));
+        self.diagnostics.sort_by_cached_key(|diagnostic| {
+            diagnostic_sort_key(diagnostic, self.language.as_deref(), &self.language_servers)
+        });
+        if self.config.load().lsp.deduplicate_diagnostics {
+            self.deduplicate_diagnostics();
+        }
+    }
+
+    /// Merges diagnostics that different language servers published for the
+    /// same issue -- identical range and code, and a whitespace-normalized
+    /// identical message -- into the higher-priority entry, whose `source`
+    /// then lists every server that reported it, e.g. "ruff,pylsp".
+    /// Conservative by design: differing ranges or codes never merge.
+    ///
+    /// Expects `self.diagnostics` to be sorted by [diagnostic_sort_key],
+    /// which makes candidates (same range) adjacent.
+    fn deduplicate_diagnostics(&mut self) {
+        fn normalized(message: &str) -> String {
+            message.split_whitespace().collect::<Vec<_>>().join(" ")
+        }
+
+        let mut deduped: Vec<Diagnostic> = Vec::with_capacity(self.diagnostics.len());
+        for diagnostic in std::mem::take(&mut self.diagnostics) {
+            let duplicate = deduped
+                .iter_mut()
+                .rev()
+                .take_while(|kept| kept.range == diagnostic.range)
+                .find(|kept| {
+                    kept.provider != diagnostic.provider
+                        && kept.code == diagnostic.code
+                        && normalized(&kept.message) == normalized(&diagnostic.message)
+                });
+            match duplicate {
+                Some(kept) => {
+                    if let Some(source) = &diagnostic.source {
+                        match &mut kept.source {
+                            Some(merged) if !merged.split(',').any(|s| s == source) => {
+                                merged.push(',');
+                                merged.push_str(source);
+                            }
+                            None => kept.source = Some(source.clone()),
+                            _ => (),
+                        }
+                    }
+                }
+                None => deduped.push(diagnostic),
+            }
+        }
+        self.diagnostics = deduped;
     }
 
     /// clears diagnostics for a given language server id if set, otherwise all diagnostics are cleared
@@ -1983,6 +2070,10 @@ impl Document {
         self.inlay_hints.insert(view_id, inlay_hints);
     }
 
+    pub fn set_inline_values(&mut self, view_id: ViewId, inline_values: Vec<InlineAnnotation>) {
+        self.inline_values.insert(view_id, inline_values);
+    }
+
     pub fn set_jump_labels(&mut self, view_id: ViewId, labels: Vec<Overlay>) {
         self.jump_labels.insert(view_id, labels);
     }
@@ -2001,6 +2092,61 @@ impl Document {
     pub fn reset_all_inlay_hints(&mut self) {
         self.inlay_hints = Default::default();
     }
+
+    /// Completely removes all the inline values saved for the document, typically
+    /// because the debuggee resumed execution or the debug session ended.
+    pub fn reset_all_inline_values(&mut self) {
+        self.inline_values = Default::default();
+    }
+}
+
+/// See [`Document::language_server_priority`].
+fn provider_priority(
+    language_config: Option<&LanguageConfiguration>,
+    language_servers: &HashMap<LanguageServerName, Arc<Client>>,
+    provider: LanguageServerId,
+) -> usize {
+    language_config
+        .and_then(|config| {
+            let name = language_servers
+                .iter()
+                .find_map(|(name, client)| (client.id() == provider).then_some(name))?;
+            config
+                .language_servers
+                .iter()
+                .position(|language_server| language_server.name == *name)
+        })
+        .unwrap_or(usize::MAX)
+}
+
+/// The sort key for a document's diagnostics: position and severity first,
+/// with ties between diagnostics of different language servers broken by the
+/// provider's priority and the diagnostic code so that the order does not
+/// depend on which server published first.
+fn diagnostic_sort_key(
+    diagnostic: &Diagnostic,
+    language_config: Option<&LanguageConfiguration>,
+    language_servers: &HashMap<LanguageServerName, Arc<Client>>,
+) -> (
+    helix_core::diagnostic::Range,
+    Option<helix_core::diagnostic::Severity>,
+    usize,
+    Option<String>,
+    LanguageServerId,
+) {
+    use helix_core::diagnostic::NumberOrString;
+
+    let code = diagnostic.code.as_ref().map(|code| match code {
+        NumberOrString::Number(number) => number.to_string(),
+        NumberOrString::String(string) => string.clone(),
+    });
+    (
+        diagnostic.range,
+        diagnostic.severity,
+        provider_priority(language_config, language_servers, diagnostic.provider),
+        code,
+        diagnostic.provider,
+    )
 }
 
 #[derive(Clone, Debug)]
@@ -2198,6 +2344,90 @@ mod test {
         );
     }
 
+    /// Toggling a buffer between languages must change the set of language
+    /// servers the attachment logic will request, not just the syntax: a
+    /// header switched from c to rust should drop clangd's configuration and
+    /// pick up rust-analyzer's.
+    #[test]
+    fn set_language_by_language_id_changes_configured_servers() {
+        let loader = Arc::new(ArcSwap::from_pointee(
+            helix_core::config::default_lang_loader(),
+        ));
+        let mut doc = Document::from(
+            Rope::from(""),
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+        );
+        let configured = |doc: &Document| -> Vec<String> {
+            doc.language_config()
+                .map(|config| {
+                    config
+                        .language_servers
+                        .iter()
+                        .map(|ls| ls.name.clone())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        doc.set_language_by_language_id("c", loader.clone()).unwrap();
+        assert_eq!(doc.language_id(), Some("c"));
+        let c_servers = configured(&doc);
+        assert!(c_servers.iter().any(|name| name == "clangd"));
+
+        doc.set_language_by_language_id("rust", loader).unwrap();
+        assert_eq!(doc.language_id(), Some("rust"));
+        let rust_servers = configured(&doc);
+        assert!(rust_servers.iter().any(|name| name == "rust-analyzer"));
+        assert_ne!(c_servers, rust_servers);
+    }
+
+    #[test]
+    fn cursor_position_counts_astral_chars_per_offset_encoding() {
+        use helix_lsp::{lsp, OffsetEncoding};
+
+        // two astral-plane emoji before the cursor, each 1 char, 2 UTF-16
+        // code units and 4 UTF-8 bytes
+        let text = Rope::from("ab🚀🦀x\n");
+        let mut doc = Document::from(
+            text,
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+        );
+        let view = ViewId::default();
+
+        // block cursor on 'x' (char index 4)
+        doc.set_selection(view, Selection::single(4, 5));
+        assert_eq!(
+            doc.position(view, OffsetEncoding::Utf32),
+            lsp::Position::new(0, 4)
+        );
+        assert_eq!(
+            doc.position(view, OffsetEncoding::Utf16),
+            lsp::Position::new(0, 6)
+        );
+        assert_eq!(
+            doc.position(view, OffsetEncoding::Utf8),
+            lsp::Position::new(0, 10)
+        );
+
+        // cursor sitting on the second emoji itself: the reported position is
+        // the start of the grapheme, never inside it
+        doc.set_selection(view, Selection::single(3, 4));
+        assert_eq!(
+            doc.position(view, OffsetEncoding::Utf32),
+            lsp::Position::new(0, 3)
+        );
+        assert_eq!(
+            doc.position(view, OffsetEncoding::Utf16),
+            lsp::Position::new(0, 4)
+        );
+        assert_eq!(
+            doc.position(view, OffsetEncoding::Utf8),
+            lsp::Position::new(0, 6)
+        );
+    }
+
     #[test]
     fn test_line_ending() {
         assert_eq!(
@@ -2270,4 +2500,36 @@ mod test {
     decode!(jis0212_decode, "jis0212", "EUC-JP");
     decode!(shift_jis_decode, "shift_jis");
     encode!(shift_jis_encode, "shift_jis");
+
+    #[test]
+    fn related_location_suffix_points_at_the_first_entry() {
+        use helix_lsp::lsp;
+
+        let related = |uri: &str| lsp::DiagnosticRelatedInformation {
+            location: lsp::Location {
+                uri: lsp::Url::parse(uri).unwrap(),
+                range: lsp::Range::new(lsp::Position::new(41, 4), lsp::Position::new(41, 9)),
+            },
+            message: "borrow later used here".to_string(),
+        };
+        let mut diagnostic = lsp::Diagnostic {
+            message: "cannot borrow `x` as mutable".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(Document::related_location_suffix(&diagnostic), None);
+
+        diagnostic.related_information = Some(vec![
+            related("file:///src/other.rs"),
+            related("file:///src/main.rs"),
+        ]);
+        assert_eq!(
+            Document::related_location_suffix(&diagnostic).as_deref(),
+            Some(" → other.rs:42")
+        );
+
+        // non-file URIs cannot be jumped to and produce no suffix
+        diagnostic.related_information = Some(vec![related("jdt://contents/Foo.class")]);
+        assert_eq!(Document::related_location_suffix(&diagnostic), None);
+    }
 }
@@ -43,7 +43,7 @@ pub use helix_core::diagnostic::Severity;
 use helix_core::{
     auto_pairs::AutoPairs,
     syntax::{self, AutoPairConfig, IndentationHeuristic, LanguageServerFeature, SoftWrap},
-    Change, LineEnding, Position, Range, Selection, NATIVE_LINE_ENDING,
+    Change, LineEnding, Position, Range, Selection, Transaction, NATIVE_LINE_ENDING,
 };
 use helix_dap as dap;
 use helix_lsp::lsp;
@@ -313,6 +313,8 @@ pub struct Config {
     pub whitespace: WhitespaceConfig,
     /// Persistently display open buffers along the top
     pub bufferline: BufferLine,
+    /// How to vertically align the target range in picker previews
+    pub picker_preview_align: PreviewAlign,
     /// Vertical indent width guides.
     pub indent_guides: IndentGuidesConfig,
     /// Whether to color modes with different colors. Defaults to `false`.
@@ -340,6 +342,9 @@ pub struct Config {
     /// Display diagnostic below the line they occur.
     pub inline_diagnostics: InlineDiagnosticsConfig,
     pub end_of_line_diagnostics: DiagnosticFilter,
+    /// Priority order of the LSP inline annotations competing for space on
+    /// a line, see [AnnotationPriority]
+    pub annotation_priority: Vec<AnnotationPriority>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq, PartialOrd, Ord)]
@@ -424,10 +429,84 @@ pub struct LspConfig {
     pub display_signature_help_docs: bool,
     /// Display inlay hints
     pub display_inlay_hints: bool,
+    /// Display inline values from the language server while a debug session is stopped
+    pub display_inline_values: bool,
     /// Whether to enable snippet support
     pub snippets: bool,
     /// Whether to include declaration in the goto reference query
     pub goto_reference_include_declaration: bool,
+    /// Whether hover also queries the positions of secondary cursors
+    pub hover_all_cursors: bool,
+    /// Whether editing the document re-requests hover at the new cursor
+    /// position instead of closing the hover popup
+    pub refresh_hover_on_edit: bool,
+    /// Maximum number of entries the workspace symbol picker accepts across
+    /// all language servers combined before truncating the results
+    pub workspace_symbol_limit: usize,
+    /// Whether `code_action` applies the action directly when exactly one is
+    /// available instead of opening a one-item menu
+    pub code_action_auto_apply_single: bool,
+    /// Which order code actions are shown in, see [CodeActionSort]
+    pub code_action_sort: CodeActionSort,
+    /// Where the code action menu opens, see [CodeActionAnchor]
+    pub code_action_anchor: CodeActionAnchor,
+    /// Whether `A-j` in LSP pickers shows the raw JSON of the selected item,
+    /// for debugging server responses
+    pub debug_picker_json: bool,
+    /// Whether the diagnostics pickers show the highlighted diagnostic's full
+    /// message word-wrapped in a pane beneath the list; the message column
+    /// itself stays single-line
+    pub diagnostic_picker_detail: bool,
+    /// Whether diagnostics that several language servers publish for the same
+    /// issue (identical range, code and message) are merged into one entry
+    /// that lists every source, e.g. "ruff,pylsp"
+    pub deduplicate_diagnostics: bool,
+    /// Whether inline diagnostics and the diagnostics pickers append a
+    /// compact ` → file.rs:42` pointer to the first related-information
+    /// location of a diagnostic, e.g. the "borrow later used here" spot of
+    /// a rustc borrow error
+    pub diagnostic_related_suffix: bool,
+    /// Whether the fallback of an `lsp_or(primary, fallback)` key binding
+    /// also runs when the primary's request comes back empty, instead of
+    /// only when no attached server provides the required feature
+    pub fallthrough_on_empty: bool,
+    /// Whether workspace diagnostics are written to a per-workspace cache on
+    /// exit and restored as stale entries on startup, so the workspace
+    /// diagnostics picker works before the servers finish re-checking (see
+    /// [`crate::diagnostics_cache`])
+    pub persist_diagnostics: bool,
+    /// Additional gitignore-style globs that hide results from the workspace
+    /// symbol and workspace diagnostics pickers; the workspace's own ignore
+    /// files always apply
+    pub workspace_excludes: Vec<String>,
+    /// Whether resting the mouse pointer over a document position shows hover
+    /// information in a popup next to the pointer
+    pub mouse_hover: bool,
+    /// How long the pointer has to rest before mouse hover triggers, in ms
+    pub mouse_hover_delay: u64,
+    /// Whether jumping to a location selected in a picker pushes the previous
+    /// position onto the jumplist; direct jumps (e.g. `goto_definition` with a
+    /// single result) always push
+    pub picker_jumps_in_jumplist: bool,
+    /// Whether goto commands hide locations outside the workspace root, e.g.
+    /// in the standard library or `node_modules`. Hidden locations can be
+    /// revealed in the picker; if every location is external they are all
+    /// shown as usual
+    pub goto_workspace_only: bool,
+    /// Which [Action] the plain confirm uses in each LSP picker, see
+    /// [LspPickerActions]
+    pub picker_actions: LspPickerActions,
+    /// Pinned code actions shown by `code_action_favorites`, see
+    /// [CodeActionPin]. Usually set per project in `.helix/config.toml`;
+    /// `A-p` in the code action menu pins or unpins the highlighted entry
+    pub code_action_favorites: Vec<CodeActionPin>,
+    /// What user-invoked commands do while a server is still initializing or
+    /// indexing, see [ServerNotReady]
+    pub server_not_ready: ServerNotReady,
+    /// Substrings marking a diagnostic as a task annotation; a diagnostic
+    /// whose source or message contains one of them shows up in
+    /// `todo_diagnostics_picker`
+    pub todo_patterns: Vec<String>,
 }
 
 impl Default for LspConfig {
@@ -438,9 +517,181 @@ impl Default for LspConfig {
             auto_signature_help: true,
             display_signature_help_docs: true,
             display_inlay_hints: false,
+            display_inline_values: true,
             snippets: true,
             goto_reference_include_declaration: true,
+            hover_all_cursors: false,
+            refresh_hover_on_edit: false,
+            workspace_symbol_limit: 10_000,
+            code_action_auto_apply_single: false,
+            code_action_sort: CodeActionSort::default(),
+            code_action_anchor: CodeActionAnchor::default(),
+            debug_picker_json: false,
+            diagnostic_picker_detail: false,
+            deduplicate_diagnostics: false,
+            diagnostic_related_suffix: false,
+            fallthrough_on_empty: false,
+            persist_diagnostics: false,
+            workspace_excludes: Vec::new(),
+            mouse_hover: false,
+            mouse_hover_delay: 500,
+            picker_jumps_in_jumplist: true,
+            goto_workspace_only: false,
+            picker_actions: LspPickerActions::default(),
+            code_action_favorites: Vec::new(),
+            server_not_ready: ServerNotReady::default(),
+            todo_patterns: ["TODO", "FIXME", "HACK"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// What a user-invoked LSP command does when no server that could answer it
+/// is ready yet (`initialize` incomplete or start-up indexing in progress),
+/// `lsp.server-not-ready`. Either way the status line names the busy server,
+/// so an empty answer right after startup is distinguishable from "the symbol
+/// really has no definition".
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServerNotReady {
+    /// Park the command and run it once the server reports ready. A single
+    /// slot: a newer command replaces the parked one, Escape cancels it.
+    #[default]
+    Wait,
+    /// Fail immediately.
+    Fail,
+}
+
+/// A user-invoked LSP command parked by `lsp.server-not-ready = "wait"`
+/// until `server` finishes initializing and indexing.
+#[derive(Debug, Clone, Copy)]
+pub struct PendingLspCommand {
+    pub server: LanguageServerId,
+    /// The name of the command to re-run, looked up in the static command
+    /// list.
+    pub command: &'static str,
+}
+
+/// The fallback half of an `lsp_or(primary, fallback)` key binding while the
+/// primary's request is in flight (`lsp.fallthrough-on-empty`). The command
+/// spec is re-parsed by the consumer; the slot is cleared at the next command
+/// dispatch so a stale fallback cannot fire.
+#[derive(Debug, Clone)]
+pub enum LspFallback {
+    /// The primary request went out; an empty result triggers the fallback.
+    Armed(String),
+    /// The request came back empty; the application loop runs the fallback
+    /// once a full command context is available.
+    Triggered(String),
+}
+
+/// One pinned code action, `lsp.code-action-favorites`. An action matches a
+/// pin when its kind equals the pin's (a pin without a kind matches any) and
+/// its title starts with the pin's title pattern, so parameterized titles
+/// ("Import `foo` from …") still match a pin made from one of their
+/// instances.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CodeActionPin {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Title prefix pattern.
+    pub title: String,
+}
+
+/// Which [Action] the plain picker confirm (`Enter`) uses, per LSP picker,
+/// `lsp.picker-actions`. `Ctrl-s`, `Ctrl-v` and `Alt-Enter` keep their usual
+/// meaning regardless.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct LspPickerActions {
+    /// The goto definition, declaration, type definition and implementation
+    /// pickers
+    pub goto: Action,
+    /// The goto references picker
+    pub reference: Action,
+    /// The document and workspace symbol pickers
+    pub symbol: Action,
+    /// The single-file diagnostics picker
+    pub diagnostics: Action,
+    /// The workspace diagnostics picker
+    pub workspace_diagnostics: Action,
+}
+
+impl Default for LspPickerActions {
+    fn default() -> Self {
+        Self {
+            goto: Action::Replace,
+            reference: Action::Replace,
+            symbol: Action::Replace,
+            diagnostics: Action::Replace,
+            workspace_diagnostics: Action::Replace,
+        }
+    }
+}
+
+/// The order code actions are shown in, `lsp.code-action-sort`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodeActionSort {
+    /// A VSCode-style heuristic: actions are grouped by category, actions
+    /// that fix a diagnostic or are marked preferred by the server move to
+    /// the front
+    #[default]
+    Helix,
+    /// The order the server returned the actions in; some servers order
+    /// their actions deliberately
+    Server,
+}
+
+/// Where the code action menu opens, `lsp.code-action-anchor`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodeActionAnchor {
+    /// Next to the first diagnostic the request covered, so the menu does not
+    /// hide the code the fix is about; falls back to the cursor when the
+    /// request covered no diagnostic
+    #[default]
+    Diagnostic,
+    /// Always next to the cursor
+    Cursor,
+}
+
+/// One kind of LSP-produced inline annotation, ranked by
+/// `editor.annotation-priority`: at equal positions higher-ranked kinds
+/// render first, and when a line overflows the viewport the lowest-ranked
+/// hints are truncated first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnnotationPriority {
+    /// The end-of-line diagnostic message
+    Diagnostics,
+    ParameterHints,
+    TypeHints,
+    /// Inlay hints the server did not assign a kind to
+    OtherHints,
+}
+
+impl AnnotationPriority {
+    pub const DEFAULT_ORDER: [AnnotationPriority; 4] = [
+        AnnotationPriority::Diagnostics,
+        AnnotationPriority::ParameterHints,
+        AnnotationPriority::TypeHints,
+        AnnotationPriority::OtherHints,
+    ];
+
+    /// The configured order extended with any kind the user left out, so
+    /// every kind always has a deterministic rank.
+    pub fn complete_order(configured: &[AnnotationPriority]) -> Vec<AnnotationPriority> {
+        let mut order = configured.to_vec();
+        for kind in Self::DEFAULT_ORDER {
+            if !order.contains(&kind) {
+                order.push(kind);
+            }
         }
+        order
     }
 }
 
@@ -572,6 +823,10 @@ pub enum StatusLineElement {
 
     /// Indicator for selected register
     Register,
+
+    /// The number of occurrences of the symbol under the cursor, while
+    /// cached document highlights are available for it
+    Occurrences,
 }
 
 // Cursor shape is read and used on every rendered frame and so needs
@@ -628,6 +883,17 @@ impl Default for CursorShapeConfig {
     }
 }
 
+/// Vertical alignment of the target range within a picker preview
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreviewAlign {
+    /// Scroll the preview so the range sits at the top of the pane
+    Top,
+    /// Center the range vertically, keeping the context above it visible
+    #[default]
+    Center,
+}
+
 /// bufferline render modes
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -902,6 +1168,7 @@ impl Default for Config {
             rulers: Vec::new(),
             whitespace: WhitespaceConfig::default(),
             bufferline: BufferLine::default(),
+            picker_preview_align: PreviewAlign::default(),
             indent_guides: IndentGuidesConfig::default(),
             color_modes: false,
             soft_wrap: SoftWrap {
@@ -919,6 +1186,7 @@ impl Default for Config {
             jump_label_alphabet: ('a'..='z').collect(),
             inline_diagnostics: InlineDiagnosticsConfig::default(),
             end_of_line_diagnostics: DiagnosticFilter::Disable,
+            annotation_priority: AnnotationPriority::DEFAULT_ORDER.to_vec(),
         }
     }
 }
@@ -947,6 +1215,85 @@ pub struct Breakpoint {
 
 use futures_util::stream::{Flatten, Once};
 
+/// Implementations found by the last `count_implementations` run, kept around
+/// so a follow-up command can open a picker over them without re-querying the
+/// language server.
+#[derive(Debug, Clone)]
+pub struct CachedImplementations {
+    pub language_server_id: LanguageServerId,
+    pub offset_encoding: helix_lsp::OffsetEncoding,
+    pub locations: Vec<lsp::Location>,
+}
+
+/// State behind the persistent references view: a scratch buffer listing the
+/// references of one symbol as grep-style `path:line: snippet` lines. The
+/// inputs of the query are recorded so `references_view_refresh` can re-run
+/// it, and the locations so jumps from the view use the server's exact ranges.
+#[derive(Debug, Clone)]
+pub struct ReferencesView {
+    /// The scratch buffer holding the rendered lines, created on first
+    /// render.
+    pub doc: Option<DocumentId>,
+    /// The symbol the references belong to, shown in the header line.
+    pub symbol: String,
+    pub language_server_id: LanguageServerId,
+    pub offset_encoding: helix_lsp::OffsetEncoding,
+    /// Document and position of the original `textDocument/references`
+    /// request, re-sent on refresh.
+    pub text_document: lsp::TextDocumentIdentifier,
+    pub position: lsp::Position,
+    /// The rendered locations, sorted by path and position.
+    pub locations: Vec<lsp::Location>,
+}
+
+/// Document highlight ranges from the last `next_document_highlight` /
+/// `prev_document_highlight` request, reused while the primary cursor stays
+/// inside one of them so repeated presses don't re-query the server.
+#[derive(Debug, Clone)]
+pub struct CachedDocumentHighlights {
+    pub doc: DocumentId,
+    /// Document version the ranges were computed against; any edit
+    /// invalidates the cache.
+    pub version: i32,
+    /// Highlight ranges in document order.
+    pub ranges: Vec<Range>,
+}
+
+/// Code actions fetched by the debounced selection-change query
+/// (`Handlers::trigger_code_actions`), kept around so a lightbulb-style
+/// indicator and the code action menu can reuse them while the selection
+/// stays put instead of re-querying the servers.
+#[derive(Debug, Clone)]
+pub struct CachedCodeActions {
+    pub doc: DocumentId,
+    /// Document version the actions were requested against; any edit
+    /// invalidates the cache.
+    pub version: i32,
+    /// The primary selection the actions were requested for.
+    pub range: Range,
+    /// Each action with the id of the server that sent it.
+    pub actions: Vec<(lsp::CodeActionOrCommand, LanguageServerId)>,
+}
+
+/// A jump performed by an LSP navigation command, kept in
+/// [`Editor::lsp_jump_history`] so the `lsp_jump_picker` can revisit it along
+/// with the command that caused it.
+#[derive(Debug, Clone)]
+pub struct LspJump {
+    pub from: (DocumentId, usize),
+    pub to: (DocumentId, usize),
+    /// Name of the command that caused the jump, e.g. `goto_definition`.
+    pub command: &'static str,
+}
+
+/// Workspace-wide diagnostic counts, shown by the `workspace-diagnostics`
+/// statusline element.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiagnosticCounts {
+    pub warnings: usize,
+    pub errors: usize,
+}
+
 pub struct Editor {
     /// Current editing mode.
     pub mode: Mode,
@@ -967,6 +1314,52 @@ pub struct Editor {
     pub macro_replaying: Vec<char>,
     pub language_servers: helix_lsp::Registry,
     pub diagnostics: BTreeMap<PathBuf, Vec<(lsp::Diagnostic, LanguageServerId)>>,
+    /// Paths whose entries in [`Editor::diagnostics`] predate a reload from
+    /// disk: the stored positions may no longer match the text until the
+    /// language server publishes fresh diagnostics for them.
+    pub stale_diagnostic_paths: HashSet<PathBuf>,
+    /// Diagnostics restored from the previous session's cache
+    /// (`lsp.persist-diagnostics`, see [`crate::diagnostics_cache`]); kept
+    /// apart from [`Editor::diagnostics`] since they have no live server.
+    /// Shown as stale in the workspace diagnostics picker and dropped per
+    /// file once fresh diagnostics arrive.
+    pub restored_diagnostics: BTreeMap<PathBuf, Vec<(lsp::Diagnostic, helix_lsp::OffsetEncoding)>>,
+    /// Cached workspace-wide diagnostic counts, aggregated from [`Editor::diagnostics`]
+    /// whenever diagnostics change instead of on every statusline render.
+    pub workspace_diagnostic_counts: DiagnosticCounts,
+    /// See [`CachedImplementations`].
+    pub cached_implementations: Option<CachedImplementations>,
+    /// Scratch buffer collecting a server's log and progress output while a
+    /// user-invoked LSP command runs, reused across runs. Created lazily.
+    pub lsp_command_output_doc: Option<DocumentId>,
+    /// Server whose log and progress messages are currently routed into the
+    /// command output buffer.
+    pub lsp_command_capture: Option<LanguageServerId>,
+    /// Jumps made by LSP navigation commands, newest last. See [`LspJump`].
+    pub lsp_jump_history: Vec<LspJump>,
+    /// See [`CachedDocumentHighlights`].
+    pub cached_document_highlights: Option<CachedDocumentHighlights>,
+    /// See [`CachedCodeActions`].
+    pub cached_code_actions: Option<CachedCodeActions>,
+    /// Symbol names from the last document symbols response for a document.
+    /// Symbols can only be fetched asynchronously, so the `:goto-symbol`
+    /// argument completer works off this cache.
+    pub cached_symbol_names: Option<(DocumentId, Vec<String>)>,
+    /// Servers currently reporting work-done progress, with the latest
+    /// human-readable status ("Indexing 42%"). Maintained by the
+    /// application's `$/progress` handling; commands consult it (together
+    /// with `Client::is_initialized`) for readiness.
+    pub lsp_busy: HashMap<LanguageServerId, String>,
+    /// See [`PendingLspCommand`].
+    pub pending_lsp_command: Option<PendingLspCommand>,
+    /// See [`LspFallback`].
+    pub lsp_fallback: Option<LspFallback>,
+    /// See [`ReferencesView`].
+    pub references_view: Option<ReferencesView>,
+    /// Session-level override of `lsp.goto-reference-include-declaration`,
+    /// flipped by `toggle_goto_reference_declaration`. Takes precedence over
+    /// the config until toggled back; not persisted.
+    pub goto_reference_declaration_override: Option<bool>,
     pub diff_providers: DiffProviderRegistry,
 
     pub debugger: Option<dap::Client>,
@@ -1057,7 +1450,8 @@ pub enum CompleteAction {
     },
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Action {
     Load,
     Replace,
@@ -1112,6 +1506,21 @@ impl Editor {
             theme: theme_loader.default(),
             language_servers,
             diagnostics: BTreeMap::new(),
+            stale_diagnostic_paths: HashSet::new(),
+            restored_diagnostics: BTreeMap::new(),
+            workspace_diagnostic_counts: DiagnosticCounts::default(),
+            cached_implementations: None,
+            lsp_command_output_doc: None,
+            lsp_command_capture: None,
+            lsp_jump_history: Vec::new(),
+            cached_document_highlights: None,
+            cached_code_actions: None,
+            cached_symbol_names: None,
+            lsp_busy: HashMap::new(),
+            pending_lsp_command: None,
+            lsp_fallback: None,
+            references_view: None,
+            goto_reference_declaration_override: None,
             diff_providers: DiffProviderRegistry::default(),
             debugger: None,
             debugger_events: SelectAll::new(),
@@ -1331,9 +1740,9 @@ impl Editor {
 
     pub fn set_doc_path(&mut self, doc_id: DocumentId, path: &Path) {
         let doc = doc_mut!(self, &doc_id);
-        let old_path = doc.path();
+        let old_path = doc.path().cloned();
 
-        if let Some(old_path) = old_path {
+        if let Some(old_path) = &old_path {
             // sanity check, should not occur but some callers (like an LSP) may
             // create bogus calls
             if old_path == path {
@@ -1350,6 +1759,18 @@ impl Editor {
         // we have fully unregistered this document from its LS
         doc.language_servers.clear();
         doc.set_path(Some(path));
+        if let Some(old_path) = old_path {
+            // From the servers' perspective the buffer moved: carry the
+            // published diagnostics over to the new path so the diagnostics
+            // pickers don't keep showing entries keyed by a URI the servers
+            // no longer track. Fresh publishes for the new URI replace them.
+            if let Some(diagnostics) = self.diagnostics.remove(&old_path) {
+                self.diagnostics
+                    .entry(path.to_path_buf())
+                    .or_default()
+                    .extend(diagnostics);
+            }
+        }
         self.refresh_doc_language(doc_id)
     }
 
@@ -1412,23 +1833,43 @@ impl Editor {
         }
 
         let language_id = doc.language_id().map(ToOwned::to_owned).unwrap_or_default();
+        // A changed language id (e.g. a header toggled between c and cpp with
+        // `:set-language`) must reach servers that stay attached too: the id
+        // only travels in `didOpen`, so they get the document closed and
+        // reopened below instead of being skipped as unchanged.
+        let language_id_changed = doc
+            .opened_language_id
+            .as_ref()
+            .is_some_and(|opened| opened != &language_id);
 
         // only spawn new language servers if the servers aren't the same
         let doc_language_servers_not_in_registry =
             doc.language_servers.iter().filter(|(name, doc_ls)| {
-                language_servers
-                    .get(*name)
-                    .map_or(true, |ls| ls.id() != doc_ls.id())
+                language_id_changed
+                    || language_servers
+                        .get(*name)
+                        .map_or(true, |ls| ls.id() != doc_ls.id())
             });
 
+        // servers closed here and not reopened below no longer track the
+        // document; their published diagnostics are dropped further down
+        let mut detached_ids = Vec::new();
         for (_, language_server) in doc_language_servers_not_in_registry {
             tokio::spawn(language_server.text_document_did_close(doc.identifier()));
+            if language_servers
+                .values()
+                .all(|ls| ls.id() != language_server.id())
+            {
+                detached_ids.push(language_server.id());
+            }
         }
 
         let language_servers_not_in_doc = language_servers.iter().filter(|(name, ls)| {
-            doc.language_servers
-                .get(*name)
-                .map_or(true, |doc_ls| ls.id() != doc_ls.id())
+            language_id_changed
+                || doc
+                    .language_servers
+                    .get(*name)
+                    .map_or(true, |doc_ls| ls.id() != doc_ls.id())
         });
 
         for (_, language_server) in language_servers_not_in_doc {
@@ -1442,6 +1883,24 @@ impl Editor {
         }
 
         doc.language_servers = language_servers;
+        doc.opened_language_id = Some(language_id);
+
+        // clear the detached servers' diagnostics for this document so the
+        // diagnostics pickers don't keep showing entries the servers no
+        // longer maintain
+        if !detached_ids.is_empty() {
+            if let Some(path) = path {
+                if let Some(diagnostics) = self.diagnostics.get_mut(&path) {
+                    diagnostics.retain(|(_, server_id)| !detached_ids.contains(server_id));
+                    if diagnostics.is_empty() {
+                        self.diagnostics.remove(&path);
+                    }
+                }
+            }
+            let doc = doc_mut!(self, &doc_id);
+            let diagnostics = Editor::doc_diagnostics(&self.language_servers, &self.diagnostics, doc);
+            doc.replace_diagnostics(diagnostics, &[], None);
+        }
     }
 
     fn _refresh(&mut self) {
@@ -1603,6 +2062,47 @@ impl Editor {
         self.new_file_from_document(action, Document::default(self.config.clone()))
     }
 
+    /// The scratch document collecting LSP command output, created on first
+    /// use and reused across command runs.
+    pub fn lsp_command_output_document(&mut self) -> DocumentId {
+        match self
+            .lsp_command_output_doc
+            .filter(|id| self.documents.contains_key(id))
+        {
+            Some(id) => id,
+            None => {
+                let id = self.new_document(Document::default(self.config.clone()));
+                self.lsp_command_output_doc = Some(id);
+                id
+            }
+        }
+    }
+
+    /// Appends a line to the LSP command output buffer.
+    pub fn append_lsp_command_output(&mut self, text: &str) {
+        let doc_id = self.lsp_command_output_document();
+        let view_id = self.get_synced_view_id(doc_id);
+        let doc = doc_mut!(self, &doc_id);
+        let end = doc.text().len_chars();
+        let transaction = Transaction::change(
+            doc.text(),
+            [(end, end, Some(format!("{text}\n").into()))].into_iter(),
+        );
+        let view = view_mut!(self, view_id);
+        doc.apply(&transaction, view.id);
+        doc.append_changes_to_history(view);
+    }
+
+    /// Records a jump made by an LSP navigation command for the
+    /// `lsp_jump_picker`, keeping the history bounded.
+    pub fn record_lsp_jump(&mut self, jump: LspJump) {
+        const MAX_LSP_JUMP_HISTORY: usize = 100;
+        self.lsp_jump_history.push(jump);
+        if self.lsp_jump_history.len() > MAX_LSP_JUMP_HISTORY {
+            self.lsp_jump_history.remove(0);
+        }
+    }
+
     pub fn new_file_from_stdin(&mut self, action: Action) -> Result<DocumentId, Error> {
         let (stdin, encoding, has_bom) = crate::document::read_to_string(&mut stdin(), None)?;
         let doc = Document::from(
@@ -1677,6 +2177,10 @@ impl Editor {
         // This will also disallow any follow-up writes
         self.saves.remove(&doc_id);
 
+        // jump history entries into the closed document can't be revisited
+        self.lsp_jump_history
+            .retain(|jump| jump.from.0 != doc_id && jump.to.0 != doc_id);
+
         for language_server in doc.language_servers() {
             // TODO: track error
             tokio::spawn(language_server.text_document_did_close(doc.identifier()));
@@ -1870,6 +2374,20 @@ impl Editor {
             .find(|doc| doc.path().map(|p| p == path.as_ref()).unwrap_or(false))
     }
 
+    /// Recomputes the cached workspace-wide diagnostic counts. Must be called
+    /// after mutating [`Editor::diagnostics`].
+    pub fn recompute_workspace_diagnostic_counts(&mut self) {
+        let mut counts = DiagnosticCounts::default();
+        for (diag, _) in self.diagnostics.values().flatten() {
+            match diag.severity {
+                Some(lsp::DiagnosticSeverity::WARNING) => counts.warnings += 1,
+                Some(lsp::DiagnosticSeverity::ERROR) | None => counts.errors += 1,
+                _ => {}
+            }
+        }
+        self.workspace_diagnostic_counts = counts;
+    }
+
     /// Returns all supported diagnostics for the document
     pub fn doc_diagnostics<'a>(
         language_servers: &'a helix_lsp::Registry,
@@ -1922,6 +2440,40 @@ impl Editor {
             .flatten()
     }
 
+    /// A stable sort key for the diagnostics of a file collected from
+    /// multiple language servers.
+    ///
+    /// Diagnostics are ordered by severity and position first; ties between
+    /// servers are broken by `provider_priority` (see
+    /// [`Document::language_server_priority`]) and then by the diagnostic
+    /// code, so the resulting order does not depend on which server
+    /// responded first.
+    ///
+    /// Note: The `lsp::DiagnosticSeverity` enum is already defined in decreasing order
+    pub fn diagnostic_sort_key(
+        diagnostic: &lsp::Diagnostic,
+        provider_priority: usize,
+        server_id: LanguageServerId,
+    ) -> (
+        Option<lsp::DiagnosticSeverity>,
+        lsp::Position,
+        usize,
+        Option<String>,
+        LanguageServerId,
+    ) {
+        let code = diagnostic.code.as_ref().map(|code| match code {
+            lsp::NumberOrString::Number(number) => number.to_string(),
+            lsp::NumberOrString::String(string) => string.clone(),
+        });
+        (
+            diagnostic.severity,
+            diagnostic.range.start,
+            provider_priority,
+            code,
+            server_id,
+        )
+    }
+
     /// Gets the primary cursor position in screen coordinates,
     /// or `None` if the primary cursor is not visible on screen.
     pub fn cursor(&self) -> (Option<Position>, CursorKind) {
@@ -2147,3 +2699,63 @@ impl CursorCache {
         self.0.set(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Diagnostics merged from two servers must sort the same regardless of
+    /// the order in which the servers published them.
+    #[test]
+    fn diagnostic_sort_key_is_stable_across_publish_order() {
+        let mut ids: slotmap::SlotMap<LanguageServerId, ()> = slotmap::SlotMap::with_key();
+        let server_a = ids.insert(());
+        let server_b = ids.insert(());
+        // server priorities as they would come from the language configuration
+        let priority = |server_id| if server_id == server_b { 0 } else { 1 };
+
+        let diag = |line: u32, severity, code: Option<&str>, server_id| {
+            let diagnostic = lsp::Diagnostic {
+                range: lsp::Range::new(
+                    lsp::Position::new(line, 0),
+                    lsp::Position::new(line, 1),
+                ),
+                severity: Some(severity),
+                code: code.map(|code| lsp::NumberOrString::String(code.to_string())),
+                ..Default::default()
+            };
+            (diagnostic, server_id)
+        };
+
+        let mut first = vec![
+            diag(0, lsp::DiagnosticSeverity::ERROR, Some("E0001"), server_a),
+            diag(0, lsp::DiagnosticSeverity::WARNING, Some("W2"), server_a),
+            diag(0, lsp::DiagnosticSeverity::ERROR, Some("lint"), server_b),
+            diag(3, lsp::DiagnosticSeverity::ERROR, None, server_b),
+        ];
+        // the same diagnostics arriving in the opposite server order
+        let mut second: Vec<_> = first.iter().rev().cloned().collect();
+
+        for diagnostics in [&mut first, &mut second] {
+            diagnostics.sort_by_cached_key(|(d, server_id)| {
+                Editor::diagnostic_sort_key(d, priority(*server_id), *server_id)
+            });
+        }
+
+        assert_eq!(first, second);
+        // within the same severity and position the higher priority server sorts first
+        let order: Vec<_> = first
+            .iter()
+            .map(|(d, server_id)| (d.code.clone(), *server_id))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (Some(lsp::NumberOrString::String("lint".into())), server_b),
+                (Some(lsp::NumberOrString::String("E0001".into())), server_a),
+                (None, server_b),
+                (Some(lsp::NumberOrString::String("W2".into())), server_a),
+            ]
+        );
+    }
+}
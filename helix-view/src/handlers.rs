@@ -12,6 +12,12 @@ pub struct Handlers {
     // only public because most of the actual implementation is in helix-term right now :/
     pub completions: Sender<lsp::CompletionEvent>,
     pub signature_hints: Sender<lsp::SignatureHelpEvent>,
+    pub mouse_hover: Sender<lsp::MouseHoverEvent>,
+    /// Batches `publishDiagnostics` bursts, see [`lsp::PublishDiagnosticsEvent`].
+    pub publish_diagnostics: Sender<lsp::PublishDiagnosticsEvent>,
+    /// Debounces code action queries on selection change, see
+    /// [`lsp::CodeActionsEvent`].
+    pub code_actions: Sender<lsp::CodeActionsEvent>,
 }
 
 impl Handlers {
@@ -39,4 +45,14 @@ impl Handlers {
         };
         send_blocking(&self.signature_hints, event)
     }
+
+    /// Queries the code actions available for `range` once the selection has
+    /// settled, coalescing with other pending queries. The result lands in
+    /// [`Editor::cached_code_actions`](crate::editor::Editor).
+    pub fn trigger_code_actions(&self, doc: DocumentId, view: ViewId, range: helix_core::Range) {
+        send_blocking(
+            &self.code_actions,
+            lsp::CodeActionsEvent::Trigger { doc, view, range },
+        );
+    }
 }
@@ -199,6 +199,10 @@ impl Editor {
                     if debugger.thread_id == Some(thread_id) {
                         debugger.resume_application();
                     }
+                    // stale inline values would be misleading while the debuggee is running
+                    for doc in self.documents_mut() {
+                        doc.reset_all_inline_values();
+                    }
                 }
                 Event::Thread(_) => {
                     // TODO: update thread_states, make threads request
@@ -327,6 +331,9 @@ impl Editor {
                         }
                         None => {
                             self.debugger = None;
+                            for doc in self.documents_mut() {
+                                doc.reset_all_inline_values();
+                            }
                             self.set_status(
                                 "Terminated debugging session and disconnected debugger.",
                             );
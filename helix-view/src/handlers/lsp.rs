@@ -1,8 +1,9 @@
 use crate::editor::Action;
 use crate::Editor;
 use crate::{DocumentId, ViewId};
+use helix_core::Position;
 use helix_lsp::util::generate_transaction_from_edits;
-use helix_lsp::{lsp, OffsetEncoding};
+use helix_lsp::{lsp, LanguageServerId, OffsetEncoding};
 
 pub enum CompletionEvent {
     /// Auto completion was triggered by typing a word char
@@ -44,6 +45,48 @@ pub enum SignatureHelpEvent {
     RequestComplete { open: bool },
 }
 
+pub enum MouseHoverEvent {
+    /// The mouse pointer moved over a document position. Sent on every mouse
+    /// move; the receiving hook debounces and requests hover once the pointer
+    /// rests.
+    Moved {
+        doc: DocumentId,
+        /// Character index under the pointer.
+        pos: usize,
+        /// Screen-space position of the pointer, used to anchor the popup.
+        anchor: Position,
+        /// `editor.lsp.mouse-hover-delay`, captured at send time since the
+        /// debouncing hook has no access to the editor config.
+        delay: u64,
+    },
+    /// The pointer left the text area.
+    Cancel,
+}
+
+pub enum CodeActionsEvent {
+    /// The selection moved (or an indicator wants to refresh). Sent freely on
+    /// every change; the receiving hook debounces per document+range so rapid
+    /// cursor movement only fires the query for the range the cursor settles
+    /// on, superseded queries are dropped before they reach the servers.
+    Trigger {
+        doc: DocumentId,
+        view: ViewId,
+        /// Primary selection at send time.
+        range: helix_core::Range,
+    },
+    /// Invalidate whatever query is pending (e.g. the document was edited).
+    Cancel,
+}
+
+/// A `textDocument/publishDiagnostics` notification whose application was
+/// deferred. Servers can publish for hundreds of files in a burst after
+/// startup indexing; the receiving hook batches these and applies them in one
+/// pass instead of recomputing derived state per notification.
+pub struct PublishDiagnosticsEvent {
+    pub server_id: LanguageServerId,
+    pub params: lsp::PublishDiagnosticsParams,
+}
+
 #[derive(Debug)]
 pub struct ApplyEditError {
     pub kind: ApplyEditErrorKind,
@@ -71,7 +114,49 @@ impl ToString for ApplyEditErrorKind {
     }
 }
 
+/// A change of a `WorkspaceEdit` that best-effort application skipped, see
+/// [`Editor::apply_workspace_edit_best_effort`].
+#[derive(Debug)]
+pub struct SkippedEdit {
+    pub change_idx: usize,
+    pub kind: ApplyEditErrorKind,
+}
+
 impl Editor {
+    /// Validates the document version a workspace edit carries against the
+    /// open buffer. Every application path — code actions, rename and
+    /// server-initiated `workspace/applyEdit` requests — funnels through
+    /// here, so the policy is uniform: a mismatching version rejects the
+    /// change with [`ApplyEditErrorKind::DocumentChanged`], while a `null`
+    /// version (including the version-less `changes` shape) applies
+    /// unchecked with only a log warning, since the server opted out of the
+    /// round-trip.
+    fn check_document_version(
+        &mut self,
+        doc_id: DocumentId,
+        path: &std::path::Path,
+        version: Option<i32>,
+    ) -> Result<(), ApplyEditErrorKind> {
+        let doc = doc_mut!(self, &doc_id);
+        match version {
+            Some(version) if version != doc.version() => {
+                let err = format!(
+                    "outdated workspace edit for {path:?}, re-run the action to get a fresh one"
+                );
+                log::error!("{err}, expected {} but got {version}", doc.version());
+                self.set_error(err);
+                Err(ApplyEditErrorKind::DocumentChanged)
+            }
+            Some(_) => Ok(()),
+            None => {
+                log::warn!(
+                    "workspace edit for {path:?} carries no document version, applying unchecked"
+                );
+                Ok(())
+            }
+        }
+    }
+
     fn apply_text_edits(
         &mut self,
         uri: &helix_lsp::Url,
@@ -99,15 +184,7 @@ impl Editor {
             }
         };
 
-        let doc = doc_mut!(self, &doc_id);
-        if let Some(version) = version {
-            if version != doc.version() {
-                let err = format!("outdated workspace edit for {path:?}");
-                log::error!("{err}, expected {} but got {version}", doc.version());
-                self.set_error(err);
-                return Err(ApplyEditErrorKind::DocumentChanged);
-            }
-        }
+        self.check_document_version(doc_id, &path, version)?;
 
         // Need to determine a view for apply/append_changes_to_history
         let view_id = self.get_synced_view_id(doc_id);
@@ -126,6 +203,30 @@ impl Editor {
         offset_encoding: OffsetEncoding,
         workspace_edit: &lsp::WorkspaceEdit,
     ) -> Result<(), ApplyEditError> {
+        self.apply_workspace_edit_impl(offset_encoding, workspace_edit, false)
+            .map(|_| ())
+    }
+
+    /// Applies as much of `workspace_edit` as possible: changes that fail,
+    /// e.g. because they reference a file that was deleted in the meantime,
+    /// are skipped instead of aborting the whole edit and returned together
+    /// with the reason so the caller can report them.
+    pub fn apply_workspace_edit_best_effort(
+        &mut self,
+        offset_encoding: OffsetEncoding,
+        workspace_edit: &lsp::WorkspaceEdit,
+    ) -> Vec<SkippedEdit> {
+        self.apply_workspace_edit_impl(offset_encoding, workspace_edit, true)
+            .expect("best-effort application never aborts")
+    }
+
+    fn apply_workspace_edit_impl(
+        &mut self,
+        offset_encoding: OffsetEncoding,
+        workspace_edit: &lsp::WorkspaceEdit,
+        best_effort: bool,
+    ) -> Result<Vec<SkippedEdit>, ApplyEditError> {
+        let mut skipped = Vec::new();
         if let Some(ref document_changes) = workspace_edit.document_changes {
             match document_changes {
                 lsp::DocumentChanges::Edits(document_edits) => {
@@ -141,16 +242,23 @@ impl Editor {
                             })
                             .cloned()
                             .collect();
-                        self.apply_text_edits(
+                        if let Err(kind) = self.apply_text_edits(
                             &document_edit.text_document.uri,
                             document_edit.text_document.version,
                             edits,
                             offset_encoding,
-                        )
-                        .map_err(|kind| ApplyEditError {
-                            kind,
-                            failed_change_idx: i,
-                        })?;
+                        ) {
+                            if !best_effort {
+                                return Err(ApplyEditError {
+                                    kind,
+                                    failed_change_idx: i,
+                                });
+                            }
+                            skipped.push(SkippedEdit {
+                                change_idx: i,
+                                kind,
+                            });
+                        }
                     }
                 }
                 lsp::DocumentChanges::Operations(operations) => {
@@ -158,12 +266,19 @@ impl Editor {
                     for (i, operation) in operations.iter().enumerate() {
                         match operation {
                             lsp::DocumentChangeOperation::Op(op) => {
-                                self.apply_document_resource_op(op).map_err(|io| {
-                                    ApplyEditError {
-                                        kind: ApplyEditErrorKind::IoError(io),
-                                        failed_change_idx: i,
+                                if let Err(io) = self.apply_document_resource_op(op) {
+                                    let kind = ApplyEditErrorKind::IoError(io);
+                                    if !best_effort {
+                                        return Err(ApplyEditError {
+                                            kind,
+                                            failed_change_idx: i,
+                                        });
                                     }
-                                })?;
+                                    skipped.push(SkippedEdit {
+                                        change_idx: i,
+                                        kind,
+                                    });
+                                }
                             }
 
                             lsp::DocumentChangeOperation::Edit(document_edit) => {
@@ -178,40 +293,52 @@ impl Editor {
                                     })
                                     .cloned()
                                     .collect();
-                                self.apply_text_edits(
+                                if let Err(kind) = self.apply_text_edits(
                                     &document_edit.text_document.uri,
                                     document_edit.text_document.version,
                                     edits,
                                     offset_encoding,
-                                )
-                                .map_err(|kind| {
-                                    ApplyEditError {
-                                        kind,
-                                        failed_change_idx: i,
+                                ) {
+                                    if !best_effort {
+                                        return Err(ApplyEditError {
+                                            kind,
+                                            failed_change_idx: i,
+                                        });
                                     }
-                                })?;
+                                    skipped.push(SkippedEdit {
+                                        change_idx: i,
+                                        kind,
+                                    });
+                                }
                             }
                         }
                     }
                 }
             }
 
-            return Ok(());
+            return Ok(skipped);
         }
 
         if let Some(ref changes) = workspace_edit.changes {
             log::debug!("workspace changes: {:?}", changes);
             for (i, (uri, text_edits)) in changes.iter().enumerate() {
                 let text_edits = text_edits.to_vec();
-                self.apply_text_edits(uri, None, text_edits, offset_encoding)
-                    .map_err(|kind| ApplyEditError {
+                if let Err(kind) = self.apply_text_edits(uri, None, text_edits, offset_encoding) {
+                    if !best_effort {
+                        return Err(ApplyEditError {
+                            kind,
+                            failed_change_idx: i,
+                        });
+                    }
+                    skipped.push(SkippedEdit {
+                        change_idx: i,
                         kind,
-                        failed_change_idx: i,
-                    })?;
+                    });
+                }
             }
         }
 
-        Ok(())
+        Ok(skipped)
     }
 
     fn apply_document_resource_op(&mut self, op: &lsp::ResourceOp) -> std::io::Result<()> {
@@ -4,6 +4,7 @@ pub mod macros;
 pub mod annotations;
 pub mod base64;
 pub mod clipboard;
+pub mod diagnostics_cache;
 pub mod document;
 pub mod editor;
 pub mod events;
@@ -13,6 +14,7 @@ pub mod handlers;
 pub mod info;
 pub mod input;
 pub mod keyboard;
+pub mod lsp_position;
 pub mod register;
 pub mod theme;
 pub mod tree;
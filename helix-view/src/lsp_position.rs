@@ -0,0 +1,74 @@
+//! Conversions between editor-side positions and the `lsp::Position`s and
+//! `lsp::Range`s that go over the wire, parameterized on the server the
+//! payload belongs to. Servers negotiate their offset encoding individually,
+//! so the same cursor maps to a different `lsp::Position` per server; routing
+//! conversions through these helpers keeps the encoding handling in one
+//! audited place instead of being re-derived at each call site.
+
+use helix_lsp::{lsp, util, Client};
+
+use crate::{Document, ViewId};
+
+/// The primary cursor of `view` in `doc`, as the `lsp::Position` `client`
+/// expects it.
+pub fn cursor_to_lsp_pos(doc: &Document, view: ViewId, client: &Client) -> lsp::Position {
+    doc.position(view, client.offset_encoding())
+}
+
+/// Converts a char position in `doc` into the `lsp::Position` `client`
+/// expects.
+pub fn pos_to_lsp_pos(doc: &Document, pos: usize, client: &Client) -> lsp::Position {
+    util::pos_to_lsp_pos(doc.text(), pos, client.offset_encoding())
+}
+
+/// Converts a char range of `doc` into the `lsp::Range` `client` expects.
+pub fn range_to_lsp_range(doc: &Document, range: helix_core::Range, client: &Client) -> lsp::Range {
+    util::range_to_lsp_range(doc.text(), range, client.offset_encoding())
+}
+
+/// Converts an `lsp::Range` received from `client` into a char range of
+/// `doc`. Returns `None` when the range does not point inside the document,
+/// e.g. because it was produced against an older revision.
+pub fn lsp_range_to_range(
+    doc: &Document,
+    range: lsp::Range,
+    client: &Client,
+) -> Option<helix_core::Range> {
+    util::lsp_range_to_range(doc.text(), range, client.offset_encoding())
+}
+
+#[cfg(test)]
+mod test {
+    use helix_core::Rope;
+    use helix_lsp::{util, OffsetEncoding};
+
+    const ENCODINGS: [OffsetEncoding; 3] = [
+        OffsetEncoding::Utf8,
+        OffsetEncoding::Utf16,
+        OffsetEncoding::Utf32,
+    ];
+
+    quickcheck::quickcheck! {
+        // every encoding round-trips any cursor position on any (multibyte)
+        // content back to the same char index
+        fn lsp_pos_round_trips_in_every_encoding(text: String, pos: usize) -> bool {
+            let text = Rope::from(text.as_str());
+            let pos = pos % (text.len_chars() + 1);
+            ENCODINGS.iter().all(|&encoding| {
+                let lsp_pos = util::pos_to_lsp_pos(&text, pos, encoding);
+                util::lsp_pos_to_pos(&text, lsp_pos, encoding) == Some(pos)
+            })
+        }
+
+        // the encodings only disagree on how a column is counted, never on
+        // which line a position sits on
+        fn encodings_agree_on_the_line(text: String, pos: usize) -> bool {
+            let text = Rope::from(text.as_str());
+            let pos = pos % (text.len_chars() + 1);
+            let utf8 = util::pos_to_lsp_pos(&text, pos, OffsetEncoding::Utf8);
+            let utf16 = util::pos_to_lsp_pos(&text, pos, OffsetEncoding::Utf16);
+            let utf32 = util::pos_to_lsp_pos(&text, pos, OffsetEncoding::Utf32);
+            utf8.line == utf16.line && utf16.line == utf32.line
+        }
+    }
+}
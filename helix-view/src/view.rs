@@ -2,7 +2,7 @@ use crate::{
     align_view,
     annotations::diagnostics::InlineDiagnostics,
     document::DocumentInlayHints,
-    editor::{GutterConfig, GutterType},
+    editor::{AnnotationPriority, GutterConfig, GutterType},
     graphics::Rect,
     handlers::diagnostics::DiagnosticsHandler,
     Align, Document, DocumentId, Theme, ViewId,
@@ -447,16 +447,40 @@ impl View {
                 .and_then(|t| t.find_scope_index("ui.virtual.inlay-hint"))
                 .map(Highlight);
 
-            // Overlapping annotations are ignored apart from the first so the order here is not random:
-            // types -> parameters -> others should hopefully be the "correct" order for most use cases,
-            // with the padding coming before and after as expected.
-            text_annotations
-                .add_inline_annotations(padding_before_inlay_hints, None)
-                .add_inline_annotations(type_inlay_hints, type_style)
-                .add_inline_annotations(parameter_inlay_hints, parameter_style)
-                .add_inline_annotations(other_inlay_hints, other_style)
-                .add_inline_annotations(padding_after_inlay_hints, None);
+            // Overlapping annotations are ignored apart from the first, so
+            // adding the hint kinds in their `editor.annotation-priority`
+            // rank makes the order at equal positions deterministic, with
+            // the padding coming before and after as expected. Diagnostics
+            // are not an inline layer; their rank matters for truncation
+            // (see `apply_annotation_priority` in the hint computation).
+            let priority =
+                AnnotationPriority::complete_order(&doc.config.load().annotation_priority);
+            text_annotations.add_inline_annotations(padding_before_inlay_hints, None);
+            for kind in priority {
+                match kind {
+                    AnnotationPriority::ParameterHints => {
+                        text_annotations
+                            .add_inline_annotations(parameter_inlay_hints, parameter_style);
+                    }
+                    AnnotationPriority::TypeHints => {
+                        text_annotations.add_inline_annotations(type_inlay_hints, type_style);
+                    }
+                    AnnotationPriority::OtherHints => {
+                        text_annotations.add_inline_annotations(other_inlay_hints, other_style);
+                    }
+                    AnnotationPriority::Diagnostics => (),
+                }
+            }
+            text_annotations.add_inline_annotations(padding_after_inlay_hints, None);
         };
+
+        if let Some(inline_values) = doc.inline_values.get(&self.id) {
+            let style = theme
+                .and_then(|t| t.find_scope_index("ui.virtual.inline-value"))
+                .or_else(|| theme.and_then(|t| t.find_scope_index("ui.virtual.inlay-hint")))
+                .map(Highlight);
+            text_annotations.add_inline_annotations(inline_values, style);
+        }
         let config = doc.config.load();
         let width = self.inner_width(doc);
         let enable_cursor_line = self
@@ -1048,4 +1072,37 @@ mod tests {
             Some(7)
         );
     }
+
+    #[test]
+    fn test_jumplist_maps_selections_through_edits() {
+        use helix_core::Range;
+
+        let view = View::new(DocumentId::default(), GutterConfig::default());
+        let rope = Rope::from_str("aaa\nbbb\nccc\n");
+        let mut doc = Document::from(
+            rope,
+            None,
+            Arc::new(ArcSwap::new(Arc::new(Config::default()))),
+        );
+        doc.ensure_view_init(view.id);
+
+        // Two ranges, the first one reversed, so that mapping must preserve
+        // the anchor/head orientation and not just a cursor position.
+        let selection = Selection::single(6, 4).push(Range::new(8, 10));
+        let mut jumps = JumpList::new((doc.id(), Selection::point(0)));
+        jumps.push((doc.id(), selection));
+
+        // Edit the document before the stored selection, as if another split
+        // (or a workspace edit) changed the file while we were jumped away.
+        let transaction =
+            Transaction::change(doc.text(), [(0, 0, Some("xxx".into()))].into_iter());
+        assert!(doc.apply(&transaction, view.id));
+        jumps.apply(&transaction, &doc);
+
+        let expected = Selection::single(9, 7).push(Range::new(11, 13));
+        assert_eq!(
+            jumps.backward(view.id, &mut doc, 1),
+            Some(&(doc.id(), expected))
+        );
+    }
 }